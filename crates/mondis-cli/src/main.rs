@@ -0,0 +1,297 @@
+//! Командная строка Mondis: список мониторов, переключение источника входа, питание и снимки состояния.
+//!
+//! Монитор задается номером шины (`5`, `i2c-5`), DRM-коннектором (`DP-2`, `card0-DP-2`),
+//! выходом XRandR или `all`.
+
+use anyhow::{anyhow, bail, Result};
+use mondis_core::cache::{cached_displays, connector_edid_hashes, validate_displays};
+use mondis_core::inputs::{input_label, resolve_input};
+use mondis_core::model::DisplayInfo;
+use mondis_core::power::PowerMode;
+use mondis_core::settings::{load_settings, update_settings};
+use mondis_core::snapshot::{self, XrandrState};
+use mondis_ddc::i2c;
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "Использование:
+  mondis list                                 мониторы
+  mondis input list <монитор>                 входы монитора (* — текущий)
+  mondis input get <монитор|all>              текущий вход
+  mondis input set <монитор|all> <вход>       переключить вход (HDMI-1, DP-1, USB-C, 0x11, метка)
+  mondis input label <монитор> <вход> [метка] задать метку входа (без метки — удалить)
+  mondis power get <монитор|all>              режим питания
+  mondis power set <монитор|all> <режим> [--dpms]
+                                             on, standby, suspend, off; если DDC не сработал — DPMS,
+                                             только для all или с --dpms (DPMS действует на все мониторы)
+  mondis power off-secondary                  выключить все, кроме основного
+  mondis snapshot list                        сохраненные снимки состояния
+  mondis snapshot save <монитор> [файл]       снимок всех параметров (по умолчанию в ~/.config/mondis/snapshots)
+  mondis snapshot restore <монитор> <файл>    восстановить снимок (монитор той же модели)
+
+Монитор: номер шины (5, i2c-5), коннектор (DP-2), выход XRandR или all.";
+
+// Мониторы из кэша панели; если кэша нет или он устарел — опрос адаптеров видеокарт
+fn displays() -> Vec<DisplayInfo> {
+    let check = validate_displays(cached_displays(), &connector_edid_hashes());
+    if !check.valid.is_empty() && check.is_clean() {
+        return check.valid;
+    }
+    mondis_ddc::adapters::display_adapters()
+        .iter()
+        .filter_map(|a| mondis_ddc::probe::probe_bus(a.bus).map(|(display, _edid)| display))
+        .collect()
+}
+
+fn matches(display: &DisplayInfo, spec: &str) -> bool {
+    let bus = spec.strip_prefix("i2c-").or_else(|| spec.strip_prefix("/dev/i2c-")).unwrap_or(spec);
+    if bus.parse::<u8>().ok() == Some(display.i2c_bus) {
+        return true;
+    }
+    let spec = spec.to_ascii_lowercase();
+    let connector = display.connector.as_deref().unwrap_or_default().to_ascii_lowercase();
+    let output = display.xrandr_output.as_deref().unwrap_or_default().to_ascii_lowercase();
+    connector == spec || connector.ends_with(&format!("-{}", spec)) || output == spec
+}
+
+fn select(spec: &str) -> Result<Vec<DisplayInfo>> {
+    let all = displays();
+    if all.is_empty() {
+        bail!("мониторы не найдены");
+    }
+    if spec == "all" {
+        return Ok(all.into_iter().filter(|d| d.supports_ddc).collect());
+    }
+    let selected: Vec<DisplayInfo> = all.into_iter().filter(|d| matches(d, spec)).collect();
+    if selected.is_empty() {
+        bail!("монитор '{}' не найден (см. mondis list)", spec);
+    }
+    Ok(selected)
+}
+
+fn select_one(spec: &str) -> Result<DisplayInfo> {
+    let mut selected = select(spec)?;
+    if spec == "all" || selected.len() > 1 {
+        bail!("'{}' — несколько мониторов, укажите один", spec);
+    }
+    Ok(selected.remove(0))
+}
+
+fn title(display: &DisplayInfo) -> String {
+    let port = display.connector.as_deref().or(display.xrandr_output.as_deref()).unwrap_or("?");
+    format!("i2c-{} {} ({})", display.i2c_bus, display.name, port)
+}
+
+fn cmd_list() -> Result<()> {
+    let displays = displays();
+    if displays.is_empty() {
+        bail!("мониторы не найдены");
+    }
+    for display in displays {
+        let ddc = if display.supports_ddc { "DDC/CI" } else { "без DDC/CI" };
+        println!("{}  {}", title(&display), ddc);
+    }
+    Ok(())
+}
+
+fn cmd_input(args: &[String]) -> Result<()> {
+    let settings = load_settings()?;
+    match args {
+        [cmd, monitor] if cmd == "list" => {
+            let display = select_one(monitor)?;
+            let current = i2c::get_input(display.i2c_bus).ok();
+            for value in i2c::supported_inputs(display.i2c_bus)? {
+                let mark = if current == Some(value) { "*" } else { " " };
+                println!("{} 0x{:02X}  {}", mark, value, input_label(&settings, &display, value));
+            }
+            Ok(())
+        }
+        [cmd, monitor] if cmd == "get" => {
+            for display in select(monitor)? {
+                match i2c::get_input(display.i2c_bus) {
+                    Ok(value) => println!("{}: {} (0x{:02X})", title(&display), input_label(&settings, &display, value), value),
+                    Err(e) => eprintln!("{}: {e:#}", title(&display)),
+                }
+            }
+            Ok(())
+        }
+        [cmd, monitor, input] if cmd == "set" => {
+            let mut failed = 0;
+            for display in select(monitor)? {
+                let result = resolve_input(&settings, &display, input)
+                    .ok_or_else(|| anyhow!("неизвестный вход '{}'", input))
+                    .and_then(|value| i2c::set_input(display.i2c_bus, value).map(|()| value));
+                match result {
+                    Ok(value) => println!("{}: {}", title(&display), input_label(&settings, &display, value)),
+                    Err(e) => {
+                        eprintln!("{}: {e:#}", title(&display));
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                bail!("вход не переключен на {} мониторах", failed);
+            }
+            Ok(())
+        }
+        [cmd, monitor, input, label @ ..] if cmd == "label" && label.len() <= 1 => {
+            let display = select_one(monitor)?;
+            let value = resolve_input(&settings, &display, input).ok_or_else(|| anyhow!("неизвестный вход '{}'", input))?;
+            update_settings(|settings| {
+                let labels = &mut settings.monitor_mut(&display).input_labels;
+                match label.first().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+                    Some(label) => {
+                        labels.insert(value, label.to_string());
+                    }
+                    None => {
+                        labels.remove(&value);
+                    }
+                }
+            })
+        }
+        _ => bail!("{}", USAGE),
+    }
+}
+
+fn cmd_power(args: &[String]) -> Result<()> {
+    match args {
+        [cmd, monitor] if cmd == "get" => {
+            for display in select(monitor)? {
+                match i2c::get_power(display.i2c_bus) {
+                    Ok(mode) => println!("{}: {}", title(&display), mode.name()),
+                    Err(e) => eprintln!("{}: {e:#}", title(&display)),
+                }
+            }
+            if let Ok(Some(level)) = mondis_x11::dpms::current_level() {
+                println!("DPMS: {}", level.name());
+            }
+            Ok(())
+        }
+        [cmd, monitor, mode, flags @ ..] if cmd == "set" && (flags.is_empty() || flags == ["--dpms"]) => {
+            let mode = PowerMode::parse(mode).ok_or_else(|| anyhow!("неизвестный режим '{}' (on, standby, suspend, off)", mode))?;
+            let mut failed = 0;
+            for display in select(monitor)? {
+                let result = if display.supports_ddc { i2c::set_power(display.i2c_bus, mode) } else { Err(anyhow!("нет DDC/CI")) };
+                match result {
+                    Ok(()) => println!("{}: {}", title(&display), mode.name()),
+                    Err(e) => {
+                        eprintln!("{}: {e:#}", title(&display));
+                        failed += 1;
+                    }
+                }
+            }
+            if failed == 0 {
+                return Ok(());
+            }
+            // DPMS действует на весь экран X11: применяется один раз и только если выбраны все
+            // мониторы или явно задан --dpms
+            let whole_screen = monitor == "all" || displays().iter().all(|d| matches(d, monitor));
+            if flags.is_empty() && !whole_screen {
+                bail!("питание не изменено на {} мониторах (DPMS выключил бы все мониторы: добавьте --dpms)", failed);
+            }
+            mondis_x11::dpms::force_level(mode)?;
+            println!("DPMS: {} (все мониторы)", mode.name());
+            Ok(())
+        }
+        [cmd] if cmd == "off-secondary" => {
+            let primary = mondis_x11::primary_output()?.ok_or_else(|| anyhow!("основной монитор не задан"))?;
+            let (mut switched, mut failed) = (0, 0);
+            for display in displays().into_iter().filter(|d| d.supports_ddc && d.xrandr_output.as_deref() != Some(primary.as_str())) {
+                match i2c::set_power(display.i2c_bus, PowerMode::Off) {
+                    Ok(()) => {
+                        println!("{}: off", title(&display));
+                        switched += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("{}: {e:#}", title(&display));
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                bail!("не выключено мониторов: {}", failed);
+            }
+            if switched == 0 {
+                bail!("нет мониторов с DDC/CI, кроме основного");
+            }
+            Ok(())
+        }
+        _ => bail!("{}", USAGE),
+    }
+}
+
+fn cmd_snapshot(args: &[String]) -> Result<()> {
+    match args {
+        [cmd] if cmd == "list" => {
+            for path in snapshot::list_snapshots() {
+                match snapshot::load_snapshot(&path) {
+                    Ok(snap) => println!(
+                        "{}  {} 0x{:04X} {}  параметров: {}",
+                        path.display(),
+                        snap.manufacturer,
+                        snap.product_code,
+                        snap.model.as_deref().unwrap_or("?"),
+                        snap.vcp.len()
+                    ),
+                    Err(e) => eprintln!("{e:#}"),
+                }
+            }
+            Ok(())
+        }
+        [cmd, monitor, file @ ..] if cmd == "save" && file.len() <= 1 => {
+            let display = select_one(monitor)?;
+            let mut snap = mondis_ddc::snapshot::capture(display.i2c_bus)?;
+            if let Some(out) = display.xrandr_output.as_deref() {
+                if let (Ok(brightness), Ok(gamma)) = (mondis_x11::xrandr::get_brightness(out), mondis_x11::xrandr::get_gamma(out)) {
+                    snap.xrandr = Some(XrandrState { brightness, gamma });
+                }
+            }
+            let path = snapshot::save_snapshot(&snap, file.first().map(Path::new))?;
+            println!("{}: параметров {} — {}", title(&display), snap.vcp.len(), path.display());
+            Ok(())
+        }
+        [cmd, monitor, file] if cmd == "restore" => {
+            let display = select_one(monitor)?;
+            let snap = snapshot::load_snapshot(Path::new(file))?;
+            let failed = mondis_ddc::snapshot::restore(display.i2c_bus, &snap)?;
+            for (code, e) in &failed {
+                eprintln!("{}: 0x{:02X}: {e:#}", title(&display), code);
+            }
+            if let (Some(state), Some(out)) = (&snap.xrandr, display.xrandr_output.as_deref()) {
+                mondis_x11::xrandr::set_gamma(out, state.gamma, state.brightness)?;
+            }
+            if !failed.is_empty() {
+                bail!("не восстановлено параметров: {}", failed.len());
+            }
+            println!("{}: снимок восстановлен", title(&display));
+            Ok(())
+        }
+        _ => bail!("{}", USAGE),
+    }
+}
+
+fn run(args: &[String]) -> Result<()> {
+    match args {
+        [cmd] if cmd == "list" => cmd_list(),
+        [cmd, rest @ ..] if cmd == "input" => cmd_input(rest),
+        [cmd, rest @ ..] if cmd == "power" => cmd_power(rest),
+        [cmd, rest @ ..] if cmd == "snapshot" => cmd_snapshot(rest),
+        [cmd] if cmd == "-h" || cmd == "--help" || cmd == "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => bail!("{}", USAGE),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("mondis: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
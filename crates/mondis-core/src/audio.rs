@@ -0,0 +1,18 @@
+//! Monitor speakers: volume (VCP 0x62) and mute (VCP 0x8D).
+
+pub const VCP_AUDIO_VOLUME: u8 = 0x62;
+pub const VCP_AUDIO_MUTE: u8 = 0x8D;
+
+/// VCP 0x8D value: 1 mutes, 2 unmutes.
+pub fn mute_value(muted: bool) -> u16 {
+    if muted { 0x01 } else { 0x02 }
+}
+
+/// Mute state from a VCP 0x8D reading (the high byte is screen blanking on MCCS 3 monitors).
+pub fn is_muted(raw: u16) -> Option<bool> {
+    match raw & 0xFF {
+        0x01 => Some(true),
+        0x02 => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::config_dir;
+use crate::edid::hash_short;
+use crate::model::{DisplayInfo, VideoCard};
+
+/// Current displays_cache.json schema. Version 1 was a bare `[VideoCard]` array.
+pub const CACHE_VERSION: u32 = 2;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DisplaysCache {
+    pub version: u32,
+    pub written_at: u64, // unix seconds
+    pub cards: Vec<VideoCard>,
+}
+
+pub fn displays_cache_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("displays_cache.json"))
+}
+
+/// Parse cache contents of any known version. `None` means the cache should be
+/// discarded (newer or unknown format, unreadable JSON).
+pub fn parse_displays_cache(data: &str) -> Option<Vec<VideoCard>> {
+    let value: serde_json::Value = match serde_json::from_str(data) {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::warn!("discarding unreadable displays cache: {e}");
+            return None;
+        }
+    };
+    if value.is_array() {
+        // v1: entries without EDID hash and connector cannot be validated and are dropped
+        let cards: Vec<VideoCard> = serde_json::from_value(value).ok()?;
+        let cards = cards
+            .into_iter()
+            .map(|mut card| {
+                card.displays.retain(|d| d.edid_hash.is_some() && d.connector.is_some());
+                card
+            })
+            .filter(|card| !card.displays.is_empty())
+            .collect();
+        return Some(cards);
+    }
+    match value.get("version").and_then(|v| v.as_u64()) {
+        Some(v) if v == CACHE_VERSION as u64 => serde_json::from_value::<DisplaysCache>(value).ok().map(|c| c.cards),
+        other => {
+            tracing::warn!("discarding displays cache with unsupported version {:?}", other);
+            None
+        }
+    }
+}
+
+pub fn read_displays_cache() -> Result<Option<Vec<VideoCard>>> {
+    let path = displays_cache_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&path).with_context(|| format!("failed to read cache {:?}", path))?;
+    Ok(parse_displays_cache(&data))
+}
+
+pub fn write_displays_cache(cards: &[VideoCard]) -> Result<()> {
+    let path = displays_cache_path()?;
+    let cache = DisplaysCache {
+        version: CACHE_VERSION,
+        written_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        cards: cards.to_vec(),
+    };
+    let data = serde_json::to_string_pretty(&cache).context("failed to serialize cache JSON")?;
+    // Write-then-rename so the panel and the tray never read a half-written file
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).with_context(|| format!("failed to write cache {:?}", tmp))?;
+    fs::rename(&tmp, &path).with_context(|| format!("failed to write cache {:?}", path))?;
+    Ok(())
+}
+
+/// All cached displays, flattened across video cards. Empty when there is no cache yet.
+pub fn cached_displays() -> Vec<DisplayInfo> {
+    match read_displays_cache() {
+        Ok(Some(cards)) => cards.into_iter().flat_map(|c| c.displays).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// EDID hash of every DRM connector in sysfs: `Some(hash)` when a monitor is
+/// connected and the kernel has its EDID, `None` otherwise.
+pub fn connector_edid_hashes() -> HashMap<String, Option<String>> {
+    let drm = Path::new("/sys/class/drm");
+    let Ok(entries) = fs::read_dir(drm) else { return HashMap::new() };
+    entries
+        .flatten()
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| name.starts_with("card") && name.contains('-'))
+        .map(|connector| {
+            let dir = drm.join(&connector);
+            let connected = fs::read_to_string(dir.join("status")).map(|s| s.trim() == "connected").unwrap_or(false);
+            let hash = fs::read(dir.join("edid"))
+                .ok()
+                .filter(|edid| connected && !edid.is_empty())
+                .map(|edid| hash_short(&edid));
+            (connector, hash)
+        })
+        .collect()
+}
+
+/// Cached displays checked against the monitors currently connected.
+#[derive(Clone, Debug, Default)]
+pub struct CacheValidation {
+    /// Same monitor on the same connector, or not verifiable (no connector / no DRM sysfs).
+    pub valid: Vec<DisplayInfo>,
+    /// Monitor unplugged or replaced, or the connector no longer exists.
+    pub stale: Vec<DisplayInfo>,
+    /// Connected connectors with no cached display.
+    pub new_connectors: Vec<String>,
+}
+
+impl CacheValidation {
+    pub fn is_clean(&self) -> bool {
+        self.stale.is_empty() && self.new_connectors.is_empty()
+    }
+}
+
+/// Compare cached displays with `connectors` (as returned by [`connector_edid_hashes`]).
+pub fn validate_displays(cached: Vec<DisplayInfo>, connectors: &HashMap<String, Option<String>>) -> CacheValidation {
+    let mut result = CacheValidation::default();
+    for display in cached {
+        let current = match display.connector.as_ref() {
+            // Nothing to compare against: keep
+            None => None,
+            Some(_) if connectors.is_empty() => None,
+            Some(conn) => Some(connectors.get(conn).cloned().flatten()),
+        };
+        match current {
+            Some(hash) if hash.is_none() || hash != display.edid_hash => result.stale.push(display),
+            _ => result.valid.push(display),
+        }
+    }
+    let mut new_connectors: Vec<String> = connectors
+        .iter()
+        .filter(|(conn, hash)| {
+            hash.is_some() && !result.valid.iter().any(|d| d.connector.as_ref() == Some(*conn))
+        })
+        .map(|(conn, _)| conn.clone())
+        .collect();
+    new_connectors.sort();
+    result.new_connectors = new_connectors;
+    result
+}
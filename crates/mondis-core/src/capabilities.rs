@@ -0,0 +1,112 @@
+//! Parser for the DDC/CI capabilities string, e.g.
+//! `(prot(monitor)type(lcd)model(U2720Q)mccs_ver(2.1)vcp(02 10 12 14(05 08 0B) 60(0F 11 1B)))`.
+
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub model: Option<String>,
+    pub mccs_version: Option<String>,
+    /// Supported VCP codes with the values listed for them (empty for continuous controls).
+    pub vcp: BTreeMap<u8, Vec<u8>>,
+}
+
+impl Capabilities {
+    /// Unknown sections and malformed codes are skipped; an unparsable string gives empty capabilities.
+    pub fn parse(caps: &str) -> Capabilities {
+        let mut result = Capabilities::default();
+        let caps = caps.trim();
+        let inner = caps.strip_prefix('(').map(|s| s.strip_suffix(')').unwrap_or(s)).unwrap_or(caps);
+        for (name, body) in sections(inner) {
+            match name.to_ascii_lowercase().as_str() {
+                "model" => result.model = Some(body.trim().to_string()),
+                "mccs_ver" => result.mccs_version = Some(body.trim().to_string()),
+                "vcp" => result.vcp = parse_vcp(body),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    pub fn supports(&self, code: u8) -> bool {
+        self.vcp.contains_key(&code)
+    }
+
+    /// Values the monitor lists for a non-continuous control (empty when none are listed).
+    pub fn values(&self, code: u8) -> &[u8] {
+        self.vcp.get(&code).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+// Top-level `name(body)` pairs; the body may contain nested parentheses
+fn sections(s: &str) -> Vec<(&str, &str)> {
+    let mut out = Vec::new();
+    let mut rest = s;
+    while let Some(open) = rest.find('(') {
+        let name = rest[..open].trim();
+        let Some(len) = matching_paren(&rest[open..]) else { break };
+        out.push((name, &rest[open + 1..open + len]));
+        rest = &rest[open + len + 1..];
+    }
+    out
+}
+
+// Offset of the ')' closing the '(' at the start of `s`
+fn matching_paren(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Hex bytes in a whitespace-separated list; some monitors omit the spaces ("0F1112")
+fn hex_bytes(s: &str) -> Vec<u8> {
+    s.split_whitespace()
+        .flat_map(|token| {
+            let bytes = token.as_bytes();
+            bytes
+                .chunks(2)
+                .filter_map(|pair| std::str::from_utf8(pair).ok().and_then(|p| u8::from_str_radix(p, 16).ok()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn parse_vcp(body: &str) -> BTreeMap<u8, Vec<u8>> {
+    let mut vcp = BTreeMap::new();
+    let mut rest = body;
+    loop {
+        let (codes, values) = match rest.find('(') {
+            Some(open) => {
+                let Some(len) = matching_paren(&rest[open..]) else { break };
+                let values = &rest[open + 1..open + len];
+                let codes = &rest[..open];
+                rest = &rest[open + len + 1..];
+                (codes, Some(values))
+            }
+            None => (std::mem::take(&mut rest), None),
+        };
+        let codes = hex_bytes(codes);
+        for &code in &codes {
+            vcp.entry(code).or_insert_with(Vec::new);
+        }
+        // The value list belongs to the code right before it
+        if let (Some(&code), Some(values)) = (codes.last(), values) {
+            vcp.insert(code, hex_bytes(values));
+        }
+        if rest.trim().is_empty() {
+            break;
+        }
+    }
+    vcp
+}
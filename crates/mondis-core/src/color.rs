@@ -0,0 +1,82 @@
+//! Color controls: preset (VCP 0x14), RGB video gains (0x16/0x18/0x1A) and black levels
+//! (0x6C/0x6E/0x70), and gains for a white point given in kelvin.
+
+pub const VCP_COLOR_PRESET: u8 = 0x14;
+pub const VCP_RED_GAIN: u8 = 0x16;
+pub const VCP_GREEN_GAIN: u8 = 0x18;
+pub const VCP_BLUE_GAIN: u8 = 0x1A;
+pub const VCP_RED_BLACK_LEVEL: u8 = 0x6C;
+pub const VCP_GREEN_BLACK_LEVEL: u8 = 0x6E;
+pub const VCP_BLUE_BLACK_LEVEL: u8 = 0x70;
+
+pub const GAIN_CODES: [u8; 3] = [VCP_RED_GAIN, VCP_GREEN_GAIN, VCP_BLUE_GAIN];
+pub const BLACK_LEVEL_CODES: [u8; 3] = [VCP_RED_BLACK_LEVEL, VCP_GREEN_BLACK_LEVEL, VCP_BLUE_BLACK_LEVEL];
+/// Controls stored in a saved calibration, in the order they are restored (the preset first:
+/// switching it resets the gains on most monitors).
+pub const CALIBRATION_CODES: [u8; 7] = [
+    VCP_COLOR_PRESET,
+    VCP_RED_GAIN,
+    VCP_GREEN_GAIN,
+    VCP_BLUE_GAIN,
+    VCP_RED_BLACK_LEVEL,
+    VCP_GREEN_BLACK_LEVEL,
+    VCP_BLUE_BLACK_LEVEL,
+];
+
+/// First user preset; gains usually take effect only in a user preset.
+pub const USER_PRESET: u8 = 0x0B;
+
+// MCCS 2.2 color preset values
+const PRESET_NAMES: &[(u8, &str)] = &[
+    (0x01, "sRGB"),
+    (0x02, "Native"),
+    (0x03, "4000 K"),
+    (0x04, "5000 K"),
+    (0x05, "6500 K"),
+    (0x06, "7500 K"),
+    (0x07, "8200 K"),
+    (0x08, "9300 K"),
+    (0x09, "10000 K"),
+    (0x0A, "11500 K"),
+    (0x0B, "User 1"),
+    (0x0C, "User 2"),
+    (0x0D, "User 3"),
+];
+
+/// MCCS name of a color preset value ("6500 K"), if it has one.
+pub fn preset_name(value: u8) -> Option<&'static str> {
+    PRESET_NAMES.iter().find(|(v, _)| *v == value).map(|(_, name)| *name)
+}
+
+/// Name shown for a color preset: MCCS name or the raw value.
+pub fn preset_label(value: u8) -> String {
+    preset_name(value).map(str::to_string).unwrap_or_else(|| format!("Пресет 0x{:02X}", value))
+}
+
+// Black body color (0..255 per channel), Tanner Helland's approximation
+fn blackbody_rgb(kelvin: u32) -> [f64; 3] {
+    let t = kelvin.clamp(1000, 40000) as f64 / 100.0;
+    let red = if t <= 66.0 { 255.0 } else { 329.698727446 * (t - 60.0).powf(-0.1332047592) };
+    let green = if t <= 66.0 {
+        99.4708025861 * t.ln() - 161.1195681661
+    } else {
+        288.1221695283 * (t - 60.0).powf(-0.0755148492)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.5177312231 * (t - 10.0).ln() - 305.0447927307
+    };
+    [red, green, blue].map(|c| c.clamp(0.0, 255.0))
+}
+
+/// Relative R/G/B gains (0..1, the largest is 1) that move a D65 (6500 K) white point to `kelvin`.
+pub fn white_point_gains(kelvin: u32) -> [f64; 3] {
+    let target = blackbody_rgb(kelvin);
+    let native = blackbody_rgb(6500);
+    let ratio = [0, 1, 2].map(|i| target[i] / native[i]);
+    let max = ratio.iter().copied().fold(f64::MIN_POSITIVE, f64::max);
+    ratio.map(|r| r / max)
+}
@@ -0,0 +1,50 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+
+/// `~/.config/mondis`, created on first use. Shared by the panel, the tray and helpers.
+pub fn config_dir() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    let dir = PathBuf::from(home).join(".config").join("mondis");
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create config dir {:?}", dir))?;
+    Ok(dir)
+}
+
+/// Replace `path` atomically: the data goes to a uniquely named temp file in the same
+/// directory, is synced to disk and then renamed over `path`.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let dir = path.parent().with_context(|| format!("no parent directory for {:?}", path))?;
+    let mut tmp = tempfile::NamedTempFile::new_in(dir).with_context(|| format!("failed to create a temp file in {:?}", dir))?;
+    tmp.write_all(data).with_context(|| format!("failed to write {:?}", tmp.path()))?;
+    tmp.as_file().sync_all().with_context(|| format!("failed to sync {:?}", tmp.path()))?;
+    tmp.persist(path).map_err(|e| e.error).with_context(|| format!("failed to replace {:?}", path))?;
+    Ok(())
+}
+
+/// Exclusive advisory lock (`flock`) on a file in the config directory, released when dropped.
+/// Serializes read-modify-write of a config file between the panel, the tray and the CLI, and
+/// between threads of one process.
+pub struct ConfigLock {
+    _file: File,
+}
+
+pub fn lock_config(name: &str) -> Result<ConfigLock> {
+    let path = config_dir()?.join(name);
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("failed to open lock file {:?}", path))?;
+    loop {
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+            return Ok(ConfigLock { _file: file });
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err).with_context(|| format!("failed to lock {:?}", path));
+        }
+    }
+}
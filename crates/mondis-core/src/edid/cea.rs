@@ -0,0 +1,192 @@
+//! CTA-861 (CEA-861) extension block: data block collection and extra detailed timings.
+//!
+//! Only the video and HDMI vendor-specific blocks are decoded — the blocks covered by real
+//! captures in `tests/data/edid/`. Other blocks are kept as raw bytes.
+
+use super::{DetailedTiming, Mode};
+
+pub const CEA_EXTENSION_TAG: u8 = 0x02;
+
+const OUI_HDMI: u32 = 0x000C03;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CeaExtension {
+    pub revision: u8,
+    pub underscan: bool,
+    pub basic_audio: bool,
+    pub ycbcr444: bool,
+    pub ycbcr422: bool,
+    pub native_dtds: u8,
+    pub data_blocks: Vec<DataBlock>,
+    pub detailed_timings: Vec<DetailedTiming>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataBlock {
+    Video(Vec<ShortVideoDescriptor>),
+    Hdmi(HdmiVsdb),
+    VendorSpecific { oui: u32, data: Vec<u8> },
+    /// Extended tag (7) block; `tag` is the extended tag code
+    Extended { tag: u8, data: Vec<u8> },
+    Unknown { tag: u8, data: Vec<u8> },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShortVideoDescriptor {
+    pub vic: u8,
+    pub native: bool,
+}
+
+impl ShortVideoDescriptor {
+    pub fn from_byte(b: u8) -> Self {
+        // Bit 7 marks a native format only for VIC 1..=64 (CTA-861-F)
+        if (129..=192).contains(&b) {
+            Self { vic: b & 0x7F, native: true }
+        } else {
+            Self { vic: b, native: false }
+        }
+    }
+
+    pub fn mode(&self) -> Option<Mode> {
+        vic_mode(self.vic)
+    }
+}
+
+/// HDMI Licensing VSDB (OUI 00-0C-03).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HdmiVsdb {
+    pub physical_address: (u8, u8, u8, u8),
+    pub supports_ai: bool,
+    pub deep_color_30: bool,
+    pub deep_color_36: bool,
+    pub deep_color_48: bool,
+    pub deep_color_y444: bool,
+    pub dvi_dual: bool,
+    pub max_tmds_mhz: Option<u16>,
+}
+
+impl CeaExtension {
+    pub fn parse(block: &[u8]) -> CeaExtension {
+        let revision = block[1];
+        let dtd_offset = block[2] as usize;
+        let flags = if revision >= 2 { block[3] } else { 0 };
+
+        let mut data_blocks = Vec::new();
+        // Data block collection occupies bytes 4..dtd_offset (no DTDs and no data when offset is 0)
+        if dtd_offset > 4 && revision >= 3 {
+            let end = dtd_offset.min(127);
+            let mut i = 4;
+            while i < end {
+                let tag = block[i] >> 5;
+                let len = (block[i] & 0x1F) as usize;
+                if i + 1 + len > end {
+                    break;
+                }
+                data_blocks.push(parse_data_block(tag, &block[i + 1..i + 1 + len]));
+                i += 1 + len;
+            }
+        }
+
+        let mut detailed_timings = Vec::new();
+        if dtd_offset >= 4 {
+            let mut i = dtd_offset;
+            while i + 18 <= 127 {
+                match DetailedTiming::parse(&block[i..i + 18]) {
+                    Some(t) => detailed_timings.push(t),
+                    None => break,
+                }
+                i += 18;
+            }
+        }
+
+        CeaExtension {
+            revision,
+            underscan: flags & 0x80 != 0,
+            basic_audio: flags & 0x40 != 0,
+            ycbcr444: flags & 0x20 != 0,
+            ycbcr422: flags & 0x10 != 0,
+            native_dtds: flags & 0x0F,
+            data_blocks,
+            detailed_timings,
+        }
+    }
+
+    pub fn video_descriptors(&self) -> Vec<ShortVideoDescriptor> {
+        self.data_blocks
+            .iter()
+            .filter_map(|b| match b {
+                DataBlock::Video(list) => Some(list.iter().copied()),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+}
+
+fn parse_data_block(tag: u8, p: &[u8]) -> DataBlock {
+    match tag {
+        2 => DataBlock::Video(p.iter().map(|&b| ShortVideoDescriptor::from_byte(b)).collect()),
+        3 if p.len() >= 3 => parse_vendor_block(p),
+        7 if !p.is_empty() => DataBlock::Extended { tag: p[0], data: p[1..].to_vec() },
+        _ => DataBlock::Unknown { tag, data: p.to_vec() },
+    }
+}
+
+fn parse_vendor_block(p: &[u8]) -> DataBlock {
+    let oui = p[0] as u32 | (p[1] as u32) << 8 | (p[2] as u32) << 16;
+    match oui {
+        OUI_HDMI if p.len() >= 5 => {
+            let flags = p.get(5).copied().unwrap_or(0);
+            DataBlock::Hdmi(HdmiVsdb {
+                physical_address: (p[3] >> 4, p[3] & 0x0F, p[4] >> 4, p[4] & 0x0F),
+                supports_ai: flags & 0x80 != 0,
+                deep_color_48: flags & 0x40 != 0,
+                deep_color_36: flags & 0x20 != 0,
+                deep_color_30: flags & 0x10 != 0,
+                deep_color_y444: flags & 0x08 != 0,
+                dvi_dual: flags & 0x01 != 0,
+                max_tmds_mhz: p.get(6).filter(|&&v| v > 0).map(|&v| v as u16 * 5),
+            })
+        }
+        _ => DataBlock::VendorSpecific { oui, data: p[3..].to_vec() },
+    }
+}
+
+/// Resolution and refresh of common CTA-861 Video Identification Codes.
+pub fn vic_mode(vic: u8) -> Option<Mode> {
+    let (w, h, r) = match vic {
+        1 => (640, 480, 60),
+        2 | 3 => (720, 480, 60),
+        4 => (1280, 720, 60),
+        5 => (1920, 1080, 60), // interlaced
+        6 | 7 => (720, 480, 60), // interlaced
+        16 => (1920, 1080, 60),
+        17 | 18 => (720, 576, 50),
+        19 => (1280, 720, 50),
+        20 => (1920, 1080, 50), // interlaced
+        21 | 22 => (720, 576, 50), // interlaced
+        31 => (1920, 1080, 50),
+        32 => (1920, 1080, 24),
+        33 => (1920, 1080, 25),
+        34 => (1920, 1080, 30),
+        60 => (1280, 720, 24),
+        61 => (1280, 720, 25),
+        62 => (1280, 720, 30),
+        63 => (1920, 1080, 120),
+        64 => (1920, 1080, 100),
+        93 => (3840, 2160, 24),
+        94 => (3840, 2160, 25),
+        95 => (3840, 2160, 30),
+        96 => (3840, 2160, 50),
+        97 => (3840, 2160, 60),
+        98 => (4096, 2160, 24),
+        99 => (4096, 2160, 25),
+        100 => (4096, 2160, 30),
+        101 => (4096, 2160, 50),
+        102 => (4096, 2160, 60),
+        117 => (3840, 2160, 100),
+        118 => (3840, 2160, 120),
+        _ => return None,
+    };
+    Some(Mode::new(w, h, r))
+}
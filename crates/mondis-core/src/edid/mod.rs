@@ -0,0 +1,493 @@
+//! EDID parser: base block (EDID 1.3/1.4) and CTA-861 extension blocks.
+//!
+//! `Edid::parse` accepts the raw bytes as read from I2C or `/sys/class/drm/*/edid`
+//! (128 bytes per block). Blocks with a bad checksum are still parsed and reported
+//! in `Edid::checksum_errors`.
+
+pub mod cea;
+
+use thiserror::Error;
+
+use self::cea::{CeaExtension, DataBlock};
+
+pub const EDID_BLOCK_SIZE: usize = 128;
+pub const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum EdidError {
+    #[error("EDID too short: {0} bytes")]
+    TooShort(usize),
+    #[error("invalid EDID header")]
+    BadHeader,
+}
+
+/// Checksum of one 128-byte block: all bytes must sum to 0 mod 256.
+pub fn block_checksum_ok(block: &[u8]) -> bool {
+    block.len() == EDID_BLOCK_SIZE && block.iter().fold(0u8, |acc, &b| acc.wrapping_add(b)) == 0
+}
+
+/// Short stable identity: FNV-1a 32-bit over the base block.
+pub fn hash_short(edid: &[u8]) -> String {
+    let mut hash: u32 = 0x811C9DC5;
+    let prime: u32 = 0x01000193;
+    let len = edid.len().min(EDID_BLOCK_SIZE);
+    for &b in &edid[..len] {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(prime);
+    }
+    format!("{:08x}", hash)
+}
+
+/// Three-letter PNP ID from bytes 8-9 (big-endian, 5 bits per letter, 'A' = 1).
+pub fn decode_pnp_id(hi: u8, lo: u8) -> String {
+    let id = u16::from_be_bytes([hi, lo]);
+    [(id >> 10) & 0x1F, (id >> 5) & 0x1F, id & 0x1F]
+        .iter()
+        .map(|&c| if (1..=26).contains(&c) { (b'A' + c as u8 - 1) as char } else { '?' })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edid {
+    pub raw: Vec<u8>,
+    pub version: u8,
+    pub revision: u8,
+    pub manufacturer: String, // PNP ID, e.g. "GSM"
+    pub product_code: u16,
+    pub serial_number: u32,
+    pub manufacture_week: Option<u8>,
+    pub manufacture_year: Option<u16>,
+    pub model_year: Option<u16>,
+    pub input: VideoInput,
+    pub screen_size_cm: Option<(u8, u8)>,
+    pub gamma: Option<f32>,
+    pub features: Features,
+    pub chromaticity: Chromaticity,
+    pub established_timings: Vec<Mode>,
+    pub standard_timings: Vec<Mode>,
+    pub detailed_timings: Vec<DetailedTiming>,
+    pub descriptors: Vec<Descriptor>,
+    pub extension_count: u8,
+    pub extensions: Vec<Extension>,
+    /// Indices of blocks whose checksum did not match.
+    pub checksum_errors: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VideoInput {
+    Digital { bit_depth: Option<u8>, interface: DigitalInterface },
+    Analog { signal_level: u8, blank_to_black: bool, separate_sync: bool, composite_sync: bool, sync_on_green: bool, serration: bool },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigitalInterface {
+    Undefined,
+    Dvi,
+    HdmiA,
+    HdmiB,
+    Mddi,
+    DisplayPort,
+    Other(u8),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Features {
+    pub dpms_standby: bool,
+    pub dpms_suspend: bool,
+    pub dpms_active_off: bool,
+    pub display_type: u8, // bits 4-3 of byte 24, meaning depends on input type
+    pub srgb_default: bool,
+    pub preferred_timing_native: bool,
+    pub continuous_frequency: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Chromaticity {
+    pub red: (f32, f32),
+    pub green: (f32, f32),
+    pub blue: (f32, f32),
+    pub white: (f32, f32),
+}
+
+/// Resolution and refresh rate of an established/standard timing or a CTA VIC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mode {
+    pub width: u16,
+    pub height: u16,
+    pub refresh: u16,
+}
+
+impl Mode {
+    pub const fn new(width: u16, height: u16, refresh: u16) -> Self {
+        Self { width, height, refresh }
+    }
+}
+
+/// 18-byte Detailed Timing Descriptor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DetailedTiming {
+    pub pixel_clock_khz: u32,
+    pub h_active: u16,
+    pub h_blank: u16,
+    pub h_front_porch: u16,
+    pub h_sync_width: u16,
+    pub v_active: u16,
+    pub v_blank: u16,
+    pub v_front_porch: u16,
+    pub v_sync_width: u16,
+    pub h_image_mm: u16,
+    pub v_image_mm: u16,
+    pub h_border: u8,
+    pub v_border: u8,
+    pub interlaced: bool,
+    pub flags: u8, // byte 17 as is (stereo and sync bits)
+}
+
+impl DetailedTiming {
+    pub fn parse(d: &[u8]) -> Option<DetailedTiming> {
+        if d.len() < 18 {
+            return None;
+        }
+        let clock = u16::from_le_bytes([d[0], d[1]]) as u32;
+        if clock == 0 {
+            return None;
+        }
+        Some(DetailedTiming {
+            pixel_clock_khz: clock * 10,
+            h_active: d[2] as u16 | ((d[4] as u16 & 0xF0) << 4),
+            h_blank: d[3] as u16 | ((d[4] as u16 & 0x0F) << 8),
+            v_active: d[5] as u16 | ((d[7] as u16 & 0xF0) << 4),
+            v_blank: d[6] as u16 | ((d[7] as u16 & 0x0F) << 8),
+            h_front_porch: d[8] as u16 | ((d[11] as u16 & 0xC0) << 2),
+            h_sync_width: d[9] as u16 | ((d[11] as u16 & 0x30) << 4),
+            v_front_porch: (d[10] as u16 >> 4) | ((d[11] as u16 & 0x0C) << 2),
+            v_sync_width: (d[10] as u16 & 0x0F) | ((d[11] as u16 & 0x03) << 4),
+            h_image_mm: d[12] as u16 | ((d[14] as u16 & 0xF0) << 4),
+            v_image_mm: d[13] as u16 | ((d[14] as u16 & 0x0F) << 8),
+            h_border: d[15],
+            v_border: d[16],
+            interlaced: d[17] & 0x80 != 0,
+            flags: d[17],
+        })
+    }
+
+    pub fn refresh_hz(&self) -> f64 {
+        let total = (self.h_active as u32 + self.h_blank as u32) * (self.v_active as u32 + self.v_blank as u32);
+        if total == 0 {
+            return 0.0;
+        }
+        let hz = self.pixel_clock_khz as f64 * 1000.0 / total as f64;
+        if self.interlaced { hz * 2.0 } else { hz }
+    }
+}
+
+/// Display Range Limits descriptor (0xFD).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeLimits {
+    pub min_v_hz: u16,
+    pub max_v_hz: u16,
+    pub min_h_khz: u16,
+    pub max_h_khz: u16,
+    pub max_pixel_clock_mhz: Option<u16>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Descriptor {
+    ProductName(String),
+    SerialNumber(String),
+    Text(String),
+    RangeLimits(RangeLimits),
+    WhitePoint { index: u8, x: f32, y: f32, gamma: Option<f32> },
+    StandardTimings(Vec<Mode>),
+    ColorManagement,
+    Cvt3,
+    EstablishedTimings3(Vec<u8>),
+    Dummy,
+    Manufacturer { tag: u8, data: Vec<u8> },
+    Unknown { tag: u8, data: Vec<u8> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Extension {
+    Cea(CeaExtension),
+    Unknown { tag: u8, data: Vec<u8> },
+}
+
+impl Edid {
+    pub fn parse(bytes: &[u8]) -> Result<Edid, EdidError> {
+        if bytes.len() < EDID_BLOCK_SIZE {
+            return Err(EdidError::TooShort(bytes.len()));
+        }
+        if bytes[..8] != EDID_HEADER {
+            return Err(EdidError::BadHeader);
+        }
+        let b = &bytes[..EDID_BLOCK_SIZE];
+
+        let (manufacture_week, manufacture_year, model_year) = match (b[16], b[17]) {
+            (0xFF, y) => (None, None, Some(1990 + y as u16)),
+            (w, y) => ((1..=54).contains(&w).then_some(w), (y > 0).then_some(1990 + y as u16), None),
+        };
+
+        let mut detailed_timings = Vec::new();
+        let mut descriptors = Vec::new();
+        for i in 0..4 {
+            let d = &b[54 + i * 18..72 + i * 18];
+            if d[0] != 0 || d[1] != 0 {
+                if let Some(t) = DetailedTiming::parse(d) {
+                    detailed_timings.push(t);
+                }
+            } else {
+                descriptors.push(parse_descriptor(d));
+            }
+        }
+
+        let mut checksum_errors = Vec::new();
+        let mut extensions = Vec::new();
+        for (index, block) in bytes.chunks(EDID_BLOCK_SIZE).enumerate() {
+            if !block_checksum_ok(block) {
+                checksum_errors.push(index);
+            }
+            if index == 0 || block.len() < EDID_BLOCK_SIZE {
+                continue;
+            }
+            extensions.push(match block[0] {
+                cea::CEA_EXTENSION_TAG => Extension::Cea(CeaExtension::parse(block)),
+                tag => Extension::Unknown { tag, data: block.to_vec() },
+            });
+        }
+
+        Ok(Edid {
+            raw: bytes.to_vec(),
+            version: b[18],
+            revision: b[19],
+            manufacturer: decode_pnp_id(b[8], b[9]),
+            product_code: u16::from_le_bytes([b[10], b[11]]),
+            serial_number: u32::from_le_bytes([b[12], b[13], b[14], b[15]]),
+            manufacture_week,
+            manufacture_year,
+            model_year,
+            input: parse_input(b[20]),
+            screen_size_cm: (b[21] > 0 && b[22] > 0).then_some((b[21], b[22])),
+            gamma: (b[23] != 0xFF).then(|| (b[23] as f32 + 100.0) / 100.0),
+            features: Features {
+                dpms_standby: b[24] & 0x80 != 0,
+                dpms_suspend: b[24] & 0x40 != 0,
+                dpms_active_off: b[24] & 0x20 != 0,
+                display_type: (b[24] >> 3) & 0x03,
+                srgb_default: b[24] & 0x04 != 0,
+                preferred_timing_native: b[24] & 0x02 != 0,
+                continuous_frequency: b[24] & 0x01 != 0,
+            },
+            chromaticity: parse_chromaticity(&b[25..35]),
+            established_timings: parse_established(&b[35..38]),
+            standard_timings: b[38..54].chunks(2).filter_map(|s| parse_standard_timing(s[0], s[1])).collect(),
+            detailed_timings,
+            descriptors,
+            extension_count: b[126],
+            extensions,
+            checksum_errors,
+        })
+    }
+
+    /// Monitor name from the 0xFC descriptor.
+    pub fn name(&self) -> Option<&str> {
+        self.descriptors.iter().find_map(|d| match d {
+            Descriptor::ProductName(s) if !s.is_empty() => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Vendor name for the manufacturer PNP ID, falling back to the code.
+    pub fn manufacturer_name(&self) -> String {
+        crate::pnp::display_name(&self.manufacturer)
+    }
+
+    /// Serial number string from the 0xFF descriptor.
+    pub fn serial_string(&self) -> Option<&str> {
+        self.descriptors.iter().find_map(|d| match d {
+            Descriptor::SerialNumber(s) if !s.is_empty() => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn range_limits(&self) -> Option<&RangeLimits> {
+        self.descriptors.iter().find_map(|d| match d {
+            Descriptor::RangeLimits(r) => Some(r),
+            _ => None,
+        })
+    }
+
+    /// First detailed timing — the preferred mode.
+    pub fn preferred_timing(&self) -> Option<&DetailedTiming> {
+        self.detailed_timings.first()
+    }
+
+    /// Detailed timings from the base block and all extensions.
+    pub fn all_detailed_timings(&self) -> Vec<DetailedTiming> {
+        let mut list = self.detailed_timings.clone();
+        for ext in &self.extensions {
+            if let Extension::Cea(cea) = ext {
+                list.extend(cea.detailed_timings.iter().copied());
+            }
+        }
+        list
+    }
+
+    pub fn cea_extensions(&self) -> impl Iterator<Item = &CeaExtension> {
+        self.extensions.iter().filter_map(|e| match e {
+            Extension::Cea(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    pub fn cea_data_blocks(&self) -> impl Iterator<Item = &DataBlock> {
+        self.cea_extensions().flat_map(|c| c.data_blocks.iter())
+    }
+
+    pub fn hdmi(&self) -> Option<&cea::HdmiVsdb> {
+        self.cea_data_blocks().find_map(|b| match b {
+            DataBlock::Hdmi(h) => Some(h),
+            _ => None,
+        })
+    }
+}
+
+fn parse_input(b: u8) -> VideoInput {
+    if b & 0x80 != 0 {
+        let bit_depth = match (b >> 4) & 0x07 {
+            1 => Some(6),
+            2 => Some(8),
+            3 => Some(10),
+            4 => Some(12),
+            5 => Some(14),
+            6 => Some(16),
+            _ => None,
+        };
+        let interface = match b & 0x0F {
+            0 => DigitalInterface::Undefined,
+            1 => DigitalInterface::Dvi,
+            2 => DigitalInterface::HdmiA,
+            3 => DigitalInterface::HdmiB,
+            4 => DigitalInterface::Mddi,
+            5 => DigitalInterface::DisplayPort,
+            n => DigitalInterface::Other(n),
+        };
+        VideoInput::Digital { bit_depth, interface }
+    } else {
+        VideoInput::Analog {
+            signal_level: (b >> 5) & 0x03,
+            blank_to_black: b & 0x10 != 0,
+            separate_sync: b & 0x08 != 0,
+            composite_sync: b & 0x04 != 0,
+            sync_on_green: b & 0x02 != 0,
+            serration: b & 0x01 != 0,
+        }
+    }
+}
+
+// 10-bit CIE coordinates: high 8 bits in bytes 27-34, low 2 bits packed in bytes 25-26
+fn parse_chromaticity(c: &[u8]) -> Chromaticity {
+    let coord = |hi: u8, lo: u8| ((hi as u16) << 2 | lo as u16) as f32 / 1024.0;
+    Chromaticity {
+        red: (coord(c[2], (c[0] >> 6) & 3), coord(c[3], (c[0] >> 4) & 3)),
+        green: (coord(c[4], (c[0] >> 2) & 3), coord(c[5], c[0] & 3)),
+        blue: (coord(c[6], (c[1] >> 6) & 3), coord(c[7], (c[1] >> 4) & 3)),
+        white: (coord(c[8], (c[1] >> 2) & 3), coord(c[9], c[1] & 3)),
+    }
+}
+
+const ESTABLISHED_TIMINGS: [Mode; 17] = [
+    Mode::new(720, 400, 70),
+    Mode::new(720, 400, 88),
+    Mode::new(640, 480, 60),
+    Mode::new(640, 480, 67),
+    Mode::new(640, 480, 72),
+    Mode::new(640, 480, 75),
+    Mode::new(800, 600, 56),
+    Mode::new(800, 600, 60),
+    Mode::new(800, 600, 72),
+    Mode::new(800, 600, 75),
+    Mode::new(832, 624, 75),
+    Mode::new(1024, 768, 87), // interlaced
+    Mode::new(1024, 768, 60),
+    Mode::new(1024, 768, 70),
+    Mode::new(1024, 768, 75),
+    Mode::new(1280, 1024, 75),
+    Mode::new(1152, 870, 75),
+];
+
+fn parse_established(e: &[u8]) -> Vec<Mode> {
+    let bits = (e[0] as u32) << 16 | (e[1] as u32) << 8 | e[2] as u32;
+    ESTABLISHED_TIMINGS
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| bits & (1 << (23 - i)) != 0)
+        .map(|(_, m)| *m)
+        .collect()
+}
+
+fn parse_standard_timing(b0: u8, b1: u8) -> Option<Mode> {
+    if (b0 == 0x01 && b1 == 0x01) || b0 == 0x00 {
+        return None;
+    }
+    let width = (b0 as u16 + 31) * 8;
+    let height = match b1 >> 6 {
+        0 => width * 10 / 16, // 16:10 (EDID 1.3+)
+        1 => width * 3 / 4,
+        2 => width * 4 / 5,
+        _ => width * 9 / 16,
+    };
+    Some(Mode::new(width, height, (b1 & 0x3F) as u16 + 60))
+}
+
+fn descriptor_text(bytes: &[u8]) -> String {
+    let text: String = bytes
+        .iter()
+        .take_while(|&&b| b != 0x0A && b != 0x00)
+        .map(|&b| if (0x20..=0x7E).contains(&b) { b as char } else { '?' })
+        .collect();
+    text.trim_end().to_string()
+}
+
+fn parse_descriptor(d: &[u8]) -> Descriptor {
+    let tag = d[3];
+    let payload = &d[5..18];
+    match tag {
+        0xFF => Descriptor::SerialNumber(descriptor_text(payload)),
+        0xFE => Descriptor::Text(descriptor_text(payload)),
+        0xFC => Descriptor::ProductName(descriptor_text(payload)),
+        0xFD => {
+            // Byte 4: offsets for rates > 255 (EDID 1.4)
+            let v_max_off = if d[4] & 0x02 != 0 { 255 } else { 0 };
+            let v_min_off = if d[4] & 0x03 == 0x03 { 255 } else { 0 };
+            let h_max_off = if d[4] & 0x08 != 0 { 255 } else { 0 };
+            let h_min_off = if d[4] & 0x0C == 0x0C { 255 } else { 0 };
+            Descriptor::RangeLimits(RangeLimits {
+                min_v_hz: d[5] as u16 + v_min_off,
+                max_v_hz: d[6] as u16 + v_max_off,
+                min_h_khz: d[7] as u16 + h_min_off,
+                max_h_khz: d[8] as u16 + h_max_off,
+                max_pixel_clock_mhz: (d[9] > 0).then_some(d[9] as u16 * 10),
+            })
+        }
+        0xFB => {
+            let x = ((d[7] as u16) << 2 | ((d[6] as u16 >> 2) & 3)) as f32 / 1024.0;
+            let y = ((d[8] as u16) << 2 | (d[6] as u16 & 3)) as f32 / 1024.0;
+            Descriptor::WhitePoint {
+                index: d[5],
+                x,
+                y,
+                gamma: (d[9] != 0xFF).then(|| (d[9] as f32 + 100.0) / 100.0),
+            }
+        }
+        0xFA => Descriptor::StandardTimings(
+            d[5..17].chunks(2).filter_map(|s| parse_standard_timing(s[0], s[1])).collect(),
+        ),
+        0xF9 => Descriptor::ColorManagement,
+        0xF8 => Descriptor::Cvt3,
+        0xF7 => Descriptor::EstablishedTimings3(d[6..12].to_vec()),
+        0x10 => Descriptor::Dummy,
+        0x00..=0x0F => Descriptor::Manufacturer { tag, data: payload.to_vec() },
+        _ => Descriptor::Unknown { tag, data: payload.to_vec() },
+    }
+}
@@ -0,0 +1,189 @@
+//! History of applied monitor adjustments (`~/.config/mondis/history.json`): every committed
+//! transaction becomes a step with the monitor, setting, old and new value and the time.
+//! Undo and redo move a cursor through the steps; [`History::go_to`] returns to the state right
+//! after any step. A new step after an undo drops the undone ones, as in an editor.
+//!
+//! Entries remember the monitor by its settings key, not by bus or output: replays move each
+//! setting to where that monitor is connected now and skip monitors that are not connected.
+//! The file is changed only through [`update_history`], under the same kind of lock and atomic
+//! write as the settings; a file that failed to load is never overwritten.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::{config_dir, lock_config, write_atomic};
+use crate::model::DisplayInfo;
+use crate::settings::monitor_key;
+use crate::transaction::{restore_order, Setting, Value};
+
+/// Oldest steps are dropped beyond this.
+pub const MAX_STEPS: usize = 200;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Settings key of the monitor ([`monitor_key`]); empty in entries that predate it, which
+    /// cannot be replayed.
+    #[serde(default)]
+    pub monitor_key: String,
+    /// Monitor name and port at the time of the change, for display.
+    pub monitor: String,
+    pub setting: Setting,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Changes confirmed together.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    /// Unix time of the commit.
+    pub time: u64,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    pub steps: Vec<Step>,
+    /// Number of steps in effect; steps from `cursor` on were undone and can be redone.
+    pub cursor: usize,
+}
+
+impl History {
+    pub fn push(&mut self, step: Step) {
+        if step.entries.is_empty() {
+            return;
+        }
+        self.steps.truncate(self.cursor);
+        self.steps.push(step);
+        if self.steps.len() > MAX_STEPS {
+            self.steps.drain(..self.steps.len() - MAX_STEPS);
+        }
+        self.cursor = self.steps.len();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.cursor > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.cursor < self.steps.len()
+    }
+
+    /// Values to apply to undo the last step in effect.
+    pub fn undo(&mut self, displays: &[DisplayInfo]) -> Option<Vec<(Setting, Value)>> {
+        self.can_undo().then(|| self.go_to(self.cursor - 1, displays))
+    }
+
+    /// Values to apply to redo the first undone step.
+    pub fn redo(&mut self, displays: &[DisplayInfo]) -> Option<Vec<(Setting, Value)>> {
+        self.can_redo().then(|| self.go_to(self.cursor + 1, displays))
+    }
+
+    /// Number of steps committed at or before `time`: the cursor for the state at that time.
+    pub fn cursor_at(&self, time: u64) -> usize {
+        self.steps.iter().take_while(|s| s.time <= time).count()
+    }
+
+    /// Move to the state after the first `cursor` steps; returns the values to apply to the
+    /// connected `displays`, in restore order. Going back, a setting changed by several steps gets
+    /// the old value of the earliest; going forward, the new value of the latest.
+    pub fn go_to(&mut self, cursor: usize, displays: &[DisplayInfo]) -> Vec<(Setting, Value)> {
+        let cursor = cursor.min(self.steps.len());
+        let mut values: BTreeMap<(&str, &Setting), &Value> = BTreeMap::new();
+        if cursor < self.cursor {
+            for step in self.steps[cursor..self.cursor].iter().rev() {
+                for e in &step.entries {
+                    values.insert((&e.monitor_key, &e.setting), &e.old);
+                }
+            }
+        } else {
+            for step in &self.steps[self.cursor..cursor] {
+                for e in &step.entries {
+                    values.insert((&e.monitor_key, &e.setting), &e.new);
+                }
+            }
+        }
+        let values = values
+            .into_iter()
+            .filter_map(|((key, setting), value)| match resolve_setting(setting, key, displays) {
+                Some(setting) => Some((setting, value.clone())),
+                None => {
+                    tracing::info!("history: skipping {:?}, monitor {:?} is not connected", setting, key);
+                    None
+                }
+            })
+            .collect();
+        self.cursor = cursor;
+        restore_order(values)
+    }
+}
+
+/// Monitor a setting refers to now: the one on its bus or output.
+pub fn display_for<'a>(displays: &'a [DisplayInfo], setting: &Setting) -> Option<&'a DisplayInfo> {
+    match setting {
+        Setting::Vcp { bus, .. } => displays.iter().find(|d| d.i2c_bus == *bus),
+        Setting::XrandrBrightness { output } | Setting::XrandrGamma { output } | Setting::Layout { output } => {
+            displays.iter().find(|d| d.xrandr_output.as_ref() == Some(output))
+        }
+    }
+}
+
+/// `setting` of the monitor with settings key `key`, moved to the bus or output that monitor
+/// has among `displays`; `None` when it is not connected.
+pub fn resolve_setting(setting: &Setting, key: &str, displays: &[DisplayInfo]) -> Option<Setting> {
+    let display = displays.iter().find(|d| !key.is_empty() && monitor_key(d) == key)?;
+    Some(match setting {
+        Setting::Vcp { code, .. } => Setting::Vcp { bus: display.i2c_bus, code: *code },
+        Setting::XrandrBrightness { .. } => Setting::XrandrBrightness { output: display.xrandr_output.clone()? },
+        Setting::XrandrGamma { .. } => Setting::XrandrGamma { output: display.xrandr_output.clone()? },
+        Setting::Layout { .. } => Setting::Layout { output: display.xrandr_output.clone()? },
+    })
+}
+
+/// Record a change made outside the panel's transactions (tray actions) as a step of its own.
+pub fn record_change(display: &DisplayInfo, setting: Setting, old: Value, new: Value) -> Result<()> {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    let port = display.xrandr_output.clone().unwrap_or_else(|| format!("i2c-{}", display.i2c_bus));
+    let entry = Entry { monitor_key: monitor_key(display), monitor: format!("{} ({})", display.name, port), setting, old, new };
+    update_history(|h| h.push(Step { time, entries: vec![entry] }))
+}
+
+pub fn history_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("history.json"))
+}
+
+/// Empty history when there is no file; an unreadable or invalid file is an error.
+pub fn load_history() -> Result<History> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(History::default());
+    }
+    let content = fs::read_to_string(&path).with_context(|| format!("failed to read history {:?}", path))?;
+    let mut history: History = serde_json::from_str(&content).with_context(|| format!("invalid history {:?}", path))?;
+    history.cursor = history.cursor.min(history.steps.len());
+    Ok(history)
+}
+
+/// History for reading only: empty when loading fails, with the error logged.
+pub fn read_history() -> History {
+    load_history().unwrap_or_else(|e| {
+        tracing::error!("using empty history: {e:#}");
+        History::default()
+    })
+}
+
+/// Load, change and write the history as one step, holding `history.lock`. Nothing is written
+/// when loading fails.
+pub fn update_history<R>(change: impl FnOnce(&mut History) -> R) -> Result<R> {
+    let _guard = lock_config("history.lock")?;
+    let mut history = load_history()?;
+    let result = change(&mut history);
+    let path = history_path()?;
+    let serialized = serde_json::to_string_pretty(&history).context("failed to serialize history")?;
+    write_atomic(&path, serialized.as_bytes()).with_context(|| format!("failed to write history {:?}", path))?;
+    Ok(result)
+}
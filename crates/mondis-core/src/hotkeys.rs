@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+/// What a global hotkey does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    BrightnessUp,
+    BrightnessDown,
+    CycleProfile,
+    ToggleNightMode,
+    /// Switch the input source (VCP 0x60) to the binding's `input`.
+    SwitchInput,
+}
+
+/// Which monitors a brightness hotkey applies to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyTarget {
+    /// Monitor under the mouse pointer; falls back to all monitors when it can't be determined.
+    #[default]
+    Focused,
+    All,
+}
+
+/// One entry of the `hotkeys` list in settings.json, e.g.
+/// `{ "keys": "Ctrl+Alt+Page_Up", "action": "brightness_up", "target": "focused" }` or
+/// `{ "keys": "Ctrl+Alt+1", "action": "switch_input", "input": "HDMI-1", "target": "all" }`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    pub keys: String,
+    pub action: HotkeyAction,
+    #[serde(default)]
+    pub target: HotkeyTarget,
+    /// Input for `switch_input`: MCCS name, user label or VCP 0x60 value (see [`crate::inputs::resolve_input`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+}
+
+impl HotkeyBinding {
+    pub fn new(keys: &str, action: HotkeyAction, target: HotkeyTarget) -> Self {
+        Self { keys: keys.to_string(), action, target, input: None }
+    }
+
+    pub fn switch_input(keys: &str, input: &str, target: HotkeyTarget) -> Self {
+        Self { input: Some(input.to_string()), ..Self::new(keys, HotkeyAction::SwitchInput, target) }
+    }
+
+    pub fn description(&self) -> String {
+        let what = match self.action {
+            HotkeyAction::BrightnessUp => "Яркость +",
+            HotkeyAction::BrightnessDown => "Яркость -",
+            HotkeyAction::CycleProfile => "Следующий профиль",
+            HotkeyAction::ToggleNightMode => "Ночной режим",
+            HotkeyAction::SwitchInput => {
+                let what = format!("Вход {}", self.input.as_deref().unwrap_or("?"));
+                return match self.target {
+                    HotkeyTarget::All => format!("{} (все мониторы)", what),
+                    HotkeyTarget::Focused => what,
+                };
+            }
+        };
+        match (self.action, self.target) {
+            (HotkeyAction::BrightnessUp | HotkeyAction::BrightnessDown, HotkeyTarget::All) => format!("{} (все мониторы)", what),
+            _ => what.to_string(),
+        }
+    }
+}
+
+pub fn default_bindings() -> Vec<HotkeyBinding> {
+    vec![
+        HotkeyBinding::new("Ctrl+Alt+Page_Up", HotkeyAction::BrightnessUp, HotkeyTarget::Focused),
+        HotkeyBinding::new("Ctrl+Alt+Page_Down", HotkeyAction::BrightnessDown, HotkeyTarget::Focused),
+        HotkeyBinding::new("Ctrl+Alt+Shift+Page_Up", HotkeyAction::BrightnessUp, HotkeyTarget::All),
+        HotkeyBinding::new("Ctrl+Alt+Shift+Page_Down", HotkeyAction::BrightnessDown, HotkeyTarget::All),
+        HotkeyBinding::new("Ctrl+Alt+P", HotkeyAction::CycleProfile, HotkeyTarget::All),
+        HotkeyBinding::new("Ctrl+Alt+N", HotkeyAction::ToggleNightMode, HotkeyTarget::All),
+    ]
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub super_key: bool,
+}
+
+/// Parsed form of a `keys` string: modifiers plus a single key name (X keysym name, e.g. `Page_Up`, `F5`, `n`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Accelerator {
+    pub modifiers: Modifiers,
+    pub key: String,
+}
+
+impl Accelerator {
+    pub fn parse(keys: &str) -> Option<Accelerator> {
+        let mut modifiers = Modifiers::default();
+        let mut key: Option<String> = None;
+        for part in keys.split('+').map(str::trim) {
+            match part.to_ascii_lowercase().as_str() {
+                "" => return None,
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" | "mod1" => modifiers.alt = true,
+                "shift" => modifiers.shift = true,
+                "super" | "win" | "logo" | "mod4" => modifiers.super_key = true,
+                _ => {
+                    if key.is_some() { return None; }
+                    key = Some(part.to_string());
+                }
+            }
+        }
+        key.map(|key| Accelerator { modifiers, key })
+    }
+
+    /// Trigger string in the format of the XDG GlobalShortcuts portal ("CTRL+ALT+Page_Up").
+    pub fn to_portal_trigger(&self) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        if self.modifiers.ctrl { parts.push("CTRL"); }
+        if self.modifiers.alt { parts.push("ALT"); }
+        if self.modifiers.shift { parts.push("SHIFT"); }
+        if self.modifiers.super_key { parts.push("LOGO"); }
+        parts.push(&self.key);
+        parts.join("+")
+    }
+}
@@ -0,0 +1,68 @@
+//! Input source (VCP 0x60): MCCS value names, user labels and parsing of input names
+//! given in hotkeys and on the command line.
+
+use crate::model::DisplayInfo;
+use crate::settings::UiSettings;
+
+pub const VCP_INPUT_SOURCE: u8 = 0x60;
+
+// MCCS 2.2 input source values; 0x1B is not in the standard but used for USB-C by Dell, LG and others
+const STANDARD_NAMES: &[(u8, &str)] = &[
+    (0x01, "VGA-1"),
+    (0x02, "VGA-2"),
+    (0x03, "DVI-1"),
+    (0x04, "DVI-2"),
+    (0x05, "Composite-1"),
+    (0x06, "Composite-2"),
+    (0x07, "S-Video-1"),
+    (0x08, "S-Video-2"),
+    (0x09, "Tuner-1"),
+    (0x0A, "Tuner-2"),
+    (0x0B, "Tuner-3"),
+    (0x0C, "Component-1"),
+    (0x0D, "Component-2"),
+    (0x0E, "Component-3"),
+    (0x0F, "DP-1"),
+    (0x10, "DP-2"),
+    (0x11, "HDMI-1"),
+    (0x12, "HDMI-2"),
+    (0x1B, "USB-C"),
+];
+
+/// MCCS name of an input source value ("HDMI-1"), if it has one.
+pub fn standard_name(value: u8) -> Option<&'static str> {
+    STANDARD_NAMES.iter().find(|(v, _)| *v == value).map(|(_, name)| *name)
+}
+
+/// Name shown for an input of the monitor: user label, MCCS name or the raw value.
+pub fn input_label(settings: &UiSettings, display: &DisplayInfo, value: u8) -> String {
+    if let Some(label) = settings.monitor(display).and_then(|m| m.input_labels.get(&value)) {
+        return label.clone();
+    }
+    standard_name(value).map(str::to_string).unwrap_or_else(|| format!("Вход 0x{:02X}", value))
+}
+
+// "HDMI-1", "hdmi1", "HDMI 1" and "DisplayPort-1"/"DP1" compare equal
+fn normalize(name: &str) -> String {
+    let name = name.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_lowercase();
+    name.replacen("displayport", "dp", 1)
+}
+
+/// Input value for a name given by the user: a label of the monitor, an MCCS name
+/// or a number (`0x11`, `17`).
+pub fn resolve_input(settings: &UiSettings, display: &DisplayInfo, name: &str) -> Option<u8> {
+    let name = name.trim();
+    if let Some(hex) = name.strip_prefix("0x").or_else(|| name.strip_prefix("0X")) {
+        return u8::from_str_radix(hex, 16).ok();
+    }
+    if let Ok(value) = name.parse::<u8>() {
+        return Some(value);
+    }
+    if let Some(monitor) = settings.monitor(display) {
+        if let Some((&value, _)) = monitor.input_labels.iter().find(|(_, label)| label.trim().to_lowercase() == name.to_lowercase()) {
+            return Some(value);
+        }
+    }
+    let wanted = normalize(name);
+    STANDARD_NAMES.iter().find(|(_, n)| normalize(n) == wanted).map(|(v, _)| *v)
+}
@@ -1 +1,19 @@
 pub mod model;
+pub mod config;
+pub mod cache;
+pub mod settings;
+pub mod profile;
+pub mod hotkeys;
+pub mod edid;
+pub mod pnp;
+pub mod pci;
+pub mod quirks;
+pub mod capabilities;
+pub mod inputs;
+pub mod power;
+pub mod audio;
+pub mod color;
+pub mod vcp;
+pub mod snapshot;
+pub mod transaction;
+pub mod history;
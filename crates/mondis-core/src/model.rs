@@ -21,3 +21,25 @@ pub struct BrightnessInfo {
     pub supported: bool,
     pub value: Option<u8>, // 0..100
 }
+
+// Monitor as discovered by the direct I2C detection (panel/tray); persisted in displays_cache.json
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DisplayInfo {
+    pub i2c_bus: u8,
+    pub name: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub connector: Option<String>, // DRM connector, e.g. card0-DP-2
+    pub supports_ddc: bool,
+    pub xrandr_output: Option<String>,
+    pub edid_hash: Option<String>,
+    pub card_name: Option<String>,
+    pub port_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VideoCard {
+    pub name: String,
+    pub displays: Vec<DisplayInfo>,
+}
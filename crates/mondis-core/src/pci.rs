@@ -0,0 +1,201 @@
+//! GPU naming from the system `pci.ids` database and DRM card -> PCI device mapping via sysfs.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+const SYSTEM_PCI_IDS: &[&str] = &["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids"];
+
+#[derive(Clone, Debug, Default)]
+pub struct PciVendor {
+    pub name: String,
+    pub devices: HashMap<u16, PciDevice>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PciDevice {
+    pub name: String,
+    /// (subsystem vendor, subsystem device) -> subsystem name
+    pub subsystems: HashMap<(u16, u16), String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PciIds {
+    pub vendors: HashMap<u16, PciVendor>,
+}
+
+fn parse_hex_u16(s: &str) -> Option<u16> {
+    u16::from_str_radix(s.trim().trim_start_matches("0x"), 16).ok()
+}
+
+impl PciIds {
+    /// Parse the vendor/device/subsystem section of pci.ids (the class list after "C xx" is skipped).
+    pub fn parse(text: &str) -> PciIds {
+        let mut vendors: HashMap<u16, PciVendor> = HashMap::new();
+        let mut vendor: Option<u16> = None;
+        let mut device: Option<u16> = None;
+
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with("C ") {
+                break;
+            }
+            if let Some(sub) = line.strip_prefix("\t\t") {
+                // "\t\t1043 881d  Name"
+                let (ids, name) = sub.split_once("  ").unwrap_or((sub, ""));
+                let mut it = ids.split_whitespace();
+                let (Some(sv), Some(sd)) = (it.next().and_then(parse_hex_u16), it.next().and_then(parse_hex_u16)) else { continue };
+                if let (Some(v), Some(d)) = (vendor, device) {
+                    if let Some(dev) = vendors.get_mut(&v).and_then(|vd| vd.devices.get_mut(&d)) {
+                        dev.subsystems.insert((sv, sd), name.trim().to_string());
+                    }
+                }
+            } else if let Some(dev_line) = line.strip_prefix('\t') {
+                let (id, name) = dev_line.split_once("  ").unwrap_or((dev_line, ""));
+                device = parse_hex_u16(id);
+                if let (Some(v), Some(d)) = (vendor, device) {
+                    if let Some(vd) = vendors.get_mut(&v) {
+                        vd.devices.insert(d, PciDevice { name: name.trim().to_string(), subsystems: HashMap::new() });
+                    }
+                }
+            } else {
+                let (id, name) = line.split_once("  ").unwrap_or((line, ""));
+                vendor = parse_hex_u16(id);
+                device = None;
+                if let Some(v) = vendor {
+                    vendors.insert(v, PciVendor { name: name.trim().to_string(), devices: HashMap::new() });
+                }
+            }
+        }
+        PciIds { vendors }
+    }
+
+    /// System pci.ids, parsed once per process; `None` if no database is installed.
+    pub fn system() -> Option<&'static PciIds> {
+        static IDS: OnceLock<Option<PciIds>> = OnceLock::new();
+        IDS.get_or_init(|| {
+            SYSTEM_PCI_IDS
+                .iter()
+                .find_map(|p| std::fs::read_to_string(p).ok())
+                .map(|text| PciIds::parse(&text))
+        })
+        .as_ref()
+    }
+
+    pub fn vendor_name(&self, vendor: u16) -> Option<&str> {
+        self.vendors.get(&vendor).map(|v| v.name.as_str())
+    }
+
+    pub fn device_name(&self, vendor: u16, device: u16) -> Option<&str> {
+        self.vendors.get(&vendor)?.devices.get(&device).map(|d| d.name.as_str())
+    }
+
+    pub fn subsystem_name(&self, vendor: u16, device: u16, sub_vendor: u16, sub_device: u16) -> Option<&str> {
+        self.vendors.get(&vendor)?
+            .devices.get(&device)?
+            .subsystems.get(&(sub_vendor, sub_device))
+            .map(|s| s.as_str())
+    }
+}
+
+/// Short brand from a pci.ids name: the bracketed part if any
+/// ("Advanced Micro Devices, Inc. [AMD/ATI]" -> "AMD/ATI", "GA106 [GeForce RTX 3060]" -> "GeForce RTX 3060"),
+/// otherwise the name without a trailing company suffix ("NVIDIA Corporation" -> "NVIDIA").
+pub fn short_name(name: &str) -> String {
+    if let (Some(start), Some(end)) = (name.find('['), name.rfind(']')) {
+        if start < end {
+            return name[start + 1..end].trim().to_string();
+        }
+    }
+    let mut short = name.trim();
+    for suffix in [" Corporation", " Corp.", " Inc.", " Inc", " Co., Ltd.", " Co., Ltd", " Ltd.", " Limited", ","] {
+        short = short.strip_suffix(suffix).unwrap_or(short).trim_end();
+    }
+    short.to_string()
+}
+
+/// PCI device behind a DRM card (/sys/class/drm/cardN/device).
+#[derive(Clone, Debug, Default)]
+pub struct GpuInfo {
+    pub card: String,  // "card0"
+    pub slot: String,  // PCI slot, e.g. "0000:01:00.0"
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub subsystem_vendor_id: Option<u16>,
+    pub subsystem_device_id: Option<u16>,
+    pub driver: Option<String>,
+    pub vendor: Option<String>,
+    pub device: Option<String>,
+    pub subsystem_vendor: Option<String>,
+    pub subsystem: Option<String>,
+}
+
+fn read_id(dir: &Path, file: &str) -> Option<u16> {
+    std::fs::read_to_string(dir.join(file)).ok().and_then(|s| parse_hex_u16(&s))
+}
+
+impl GpuInfo {
+    /// Read IDs for `card` ("card0") from sysfs and resolve names with the system pci.ids.
+    pub fn from_card(card: &str) -> Option<GpuInfo> {
+        let device_dir = Path::new("/sys/class/drm").join(card).join("device");
+        let mut gpu = GpuInfo {
+            card: card.to_string(),
+            slot: std::fs::canonicalize(&device_dir)
+                .ok()
+                .and_then(|p| p.file_name().map(|f| f.to_string_lossy().to_string()))
+                .unwrap_or_default(),
+            vendor_id: read_id(&device_dir, "vendor")?,
+            device_id: read_id(&device_dir, "device")?,
+            subsystem_vendor_id: read_id(&device_dir, "subsystem_vendor"),
+            subsystem_device_id: read_id(&device_dir, "subsystem_device"),
+            driver: std::fs::read_link(device_dir.join("driver"))
+                .ok()
+                .and_then(|p| p.file_name().map(|f| f.to_string_lossy().to_string())),
+            ..GpuInfo::default()
+        };
+        if let Some(ids) = PciIds::system() {
+            gpu.resolve_names(ids);
+        }
+        Some(gpu)
+    }
+
+    pub fn resolve_names(&mut self, ids: &PciIds) {
+        self.vendor = ids.vendor_name(self.vendor_id).map(str::to_string);
+        self.device = ids.device_name(self.vendor_id, self.device_id).map(str::to_string);
+        if let (Some(sv), Some(sd)) = (self.subsystem_vendor_id, self.subsystem_device_id) {
+            self.subsystem_vendor = ids.vendor_name(sv).map(str::to_string);
+            self.subsystem = ids.subsystem_name(self.vendor_id, self.device_id, sv, sd).map(str::to_string);
+        }
+    }
+
+    /// "NVIDIA GeForce RTX 3060 (ASUSTeK Computer)"; IDs are shown for anything missing from pci.ids.
+    pub fn display_name(&self) -> String {
+        let vendor = self.vendor.as_deref().map(short_name).unwrap_or_else(|| format!("{:04x}", self.vendor_id));
+        let device = self.device.as_deref().map(short_name).unwrap_or_else(|| format!("{:04x}", self.device_id));
+        let mut name = format!("{} {}", vendor, device);
+        // Board partner, when it is not the chip vendor itself
+        if let Some(sub_vendor) = self.subsystem_vendor.as_deref() {
+            if self.subsystem_vendor_id != Some(self.vendor_id) {
+                name.push_str(&format!(" ({})", short_name(sub_vendor)));
+            }
+        }
+        name
+    }
+}
+
+/// All DRM cards (card0, card1, ...) that are PCI devices.
+pub fn list_gpus() -> Vec<GpuInfo> {
+    let mut cards: Vec<String> = std::fs::read_dir("/sys/class/drm")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.file_name().into_string().ok())
+                .filter(|n| n.starts_with("card") && !n.contains('-'))
+                .collect()
+        })
+        .unwrap_or_default();
+    cards.sort();
+    cards.iter().filter_map(|c| GpuInfo::from_card(c)).collect()
+}
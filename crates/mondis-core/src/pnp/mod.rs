@@ -0,0 +1,41 @@
+//! PNP vendor IDs (the three-letter EDID manufacturer codes) to company names.
+//!
+//! The embedded table is generated from hwdata `pnp.ids` by `scripts/gen-pnp-ids.py`.
+//! Codes missing from it are looked up in the system copy of `pnp.ids`, if installed.
+
+mod table;
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+pub use table::PNP_IDS;
+
+const SYSTEM_PNP_IDS: &[&str] = &["/usr/share/hwdata/pnp.ids", "/usr/share/misc/pnp.ids"];
+
+fn system_table() -> &'static HashMap<String, String> {
+    static TABLE: OnceLock<HashMap<String, String>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let Some(text) = SYSTEM_PNP_IDS.iter().find_map(|p| std::fs::read_to_string(p).ok()) else {
+            return HashMap::new();
+        };
+        text.lines()
+            .filter(|l| !l.starts_with('#'))
+            .filter_map(|l| l.split_once('\t'))
+            .map(|(code, name)| (code.trim().to_string(), name.trim().to_string()))
+            .collect()
+    })
+}
+
+/// Full vendor name for a PNP ID, e.g. "GSM" -> "LG Electronics".
+pub fn vendor_name(code: &str) -> Option<String> {
+    let code = code.trim().to_ascii_uppercase();
+    if let Ok(i) = PNP_IDS.binary_search_by(|(c, _)| (*c).cmp(code.as_str())) {
+        return Some(PNP_IDS[i].1.to_string());
+    }
+    system_table().get(&code).cloned()
+}
+
+/// Vendor name for display: the full name if known, otherwise the code itself.
+pub fn display_name(code: &str) -> String {
+    vendor_name(code).unwrap_or_else(|| code.to_string())
+}
@@ -0,0 +1,122 @@
+// Generated by scripts/gen-pnp-ids.py from pnp-ids-subset.txt (117 IDs) — do not edit by hand.
+// Sorted by code for binary search.
+
+pub static PNP_IDS: &[(&str, &str)] = &[
+    ("AAC", "AcerView"),
+    ("ACI", "Ancor Communications Inc"),
+    ("ACR", "Acer Technologies"),
+    ("ADI", "ADI Systems Inc"),
+    ("AIC", "Arnos Insturments & Computer Systems"),
+    ("AMW", "AMW"),
+    ("AOC", "AOC International (USA) Ltd."),
+    ("API", "A Plus Info Corporation"),
+    ("APP", "Apple Computer Inc"),
+    ("ARS", "Arescom Inc"),
+    ("AUO", "AU Optronics"),
+    ("AUS", "ASUSTek COMPUTER INC"),
+    ("AVT", "Avtek (Electronics) Pty Ltd"),
+    ("BBY", "Best Buy"),
+    ("BEK", "Beko Elektronik A.S."),
+    ("BMM", "BMM"),
+    ("BNO", "Bang & Olufsen"),
+    ("BNQ", "BenQ Corporation"),
+    ("BOE", "BOE Display Technology"),
+    ("CMN", "Chimei Innolux Corporation"),
+    ("CMO", "Chi Mei Optoelectronics corp."),
+    ("CPL", "Compal Electronics Inc"),
+    ("CPQ", "Compaq Computer Company"),
+    ("CPT", "Chunghwa Picture Tubes, LTD"),
+    ("CTX", "Creatix Polymedia GmbH"),
+    ("CVT", "Chunghwa Picture Tubes, LTD"),
+    ("DEL", "Dell Inc."),
+    ("DON", "DENON, Ltd."),
+    ("DPC", "Delta Electronics Inc"),
+    ("DWE", "Daewoo Electronics Company Ltd"),
+    ("ECS", "Elitegroup Computer Systems Company Ltd"),
+    ("EIZ", "Eizo Nanao Corporation"),
+    ("ELO", "Elo TouchSystems Inc"),
+    ("ENC", "Eizo Nanao Corporation"),
+    ("EPH", "Epiphan Systems Inc."),
+    ("EPI", "Envision Peripherals, Inc"),
+    ("FCM", "Funai Electric Company of Taiwan"),
+    ("FUJ", "Fujitsu Ltd"),
+    ("FUS", "Fujitsu Siemens Computers GmbH"),
+    ("GBT", "GIGA-BYTE TECHNOLOGY CO., LTD."),
+    ("GGL", "Google Inc."),
+    ("GSM", "LG Electronics"),
+    ("GWY", "Gateway 2000"),
+    ("HEI", "Hyundai Electronics Industries Co., Ltd."),
+    ("HIQ", "Kaohsiung Opto Electronics Americas, Inc."),
+    ("HIT", "Hitachi America Ltd"),
+    ("HKC", "HKC OVERSEAS LIMITED"),
+    ("HPN", "HP Inc."),
+    ("HSD", "HannStar Display Corp"),
+    ("HSL", "Hansol Electronics"),
+    ("HTC", "Hitachi Ltd"),
+    ("HWP", "Hewlett Packard"),
+    ("HYO", "HYC CO., LTD."),
+    ("IBM", "IBM Brasil"),
+    ("ICL", "Fujitsu ICL"),
+    ("IFS", "InFocus Corporation"),
+    ("IQT", "IMAGEQUEST Co., Ltd"),
+    ("IVM", "Iiyama North America"),
+    ("IVO", "InfoVision Optoelectronics (Kunshan) Co.,Ltd."),
+    ("KDS", "KDS USA"),
+    ("KFC", "KFC Computek"),
+    ("LCD", "Toshiba Matsushita Display Technology Co., Ltd"),
+    ("LEN", "Lenovo Group Limited"),
+    ("LGD", "LG Display"),
+    ("LGP", "LG.Philips"),
+    ("LKM", "ADLAS / AZALEA"),
+    ("LNK", "LINK Technologies Inc"),
+    ("LPL", "LG Philips"),
+    ("LTN", "Lite-On"),
+    ("MAG", "MAG InnoVision"),
+    ("MAX", "Rogen Tech Distribution Inc"),
+    ("MEI", "Panasonic Industry Company"),
+    ("MEL", "Mitsubishi Electric Corporation"),
+    ("MIR", "Miro Computer Prod."),
+    ("MJI", "MARANTZ JAPAN, INC."),
+    ("MSI", "Microstep"),
+    ("MST", "MS Telematica"),
+    ("MTC", "Mars-Tech Corporation"),
+    ("NAN", "Nanao"),
+    ("NEC", "NEC Corporation"),
+    ("NOK", "Nokia Display Products"),
+    ("NVD", "Nvidia"),
+    ("ONK", "ONKYO Corporation"),
+    ("OQI", "Optiquest"),
+    ("PDC", "Polaroid"),
+    ("PGS", "Princeton Graphic Systems"),
+    ("PHL", "Philips Consumer Electronics Company"),
+    ("PIO", "Pioneer Electronic Corporation"),
+    ("PNR", "Planar Systems, Inc."),
+    ("PRT", "Parallan Comp Inc"),
+    ("QDS", "Quanta Display Inc."),
+    ("REL", "Reliance Electric Ind Corporation"),
+    ("RHT", "Red Hat, Inc."),
+    ("SAM", "Samsung Electric Company"),
+    ("SAN", "Sanyo Electric Co.,Ltd."),
+    ("SDC", "Samsung Display Corp."),
+    ("SEC", "Seiko Epson Corporation"),
+    ("SGI", "Silicon Graphics Inc"),
+    ("SHP", "Sharp Corporation"),
+    ("SII", "Silicon Image, Inc."),
+    ("SMI", "Smile"),
+    ("SNY", "Sony"),
+    ("SPT", "Sceptre Tech Inc"),
+    ("SRC", "Integrated Tech Express Inc"),
+    ("STN", "Samsung Electronics America"),
+    ("TAT", "Teleliaison Inc"),
+    ("TCL", "Technical Concepts Ltd"),
+    ("TOS", "Toshiba Corporation"),
+    ("TSB", "Toshiba America Info Systems Inc"),
+    ("UNM", "Unisys Corporation"),
+    ("VIZ", "VIZIO, Inc"),
+    ("VSC", "ViewSonic Corporation"),
+    ("WDE", "Westinghouse Digital Electronics"),
+    ("XLX", "Xilinx, Inc."),
+    ("XMI", "Xiaomi Communications Co., Ltd."),
+    ("YMH", "Yamaha Corporation"),
+    ("ZCM", "Zenith Data Systems"),
+];
@@ -0,0 +1,65 @@
+//! Monitor power mode: VCP 0xD6 values and the matching DPMS levels.
+
+pub const VCP_POWER_MODE: u8 = 0xD6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerMode {
+    On,
+    Standby,
+    Suspend,
+    Off,
+}
+
+impl PowerMode {
+    pub const ALL: [PowerMode; 4] = [PowerMode::On, PowerMode::Standby, PowerMode::Suspend, PowerMode::Off];
+
+    /// Value written to VCP 0xD6 (Off is the soft off: the monitor still wakes up over DDC or video).
+    pub fn vcp_value(self) -> u16 {
+        match self {
+            PowerMode::On => 0x01,
+            PowerMode::Standby => 0x02,
+            PowerMode::Suspend => 0x03,
+            PowerMode::Off => 0x04,
+        }
+    }
+
+    /// Mode from a VCP 0xD6 reading; 0x05 (off via the power button) reads as Off.
+    pub fn from_vcp(value: u16) -> Option<PowerMode> {
+        match value & 0xFF {
+            0x01 => Some(PowerMode::On),
+            0x02 => Some(PowerMode::Standby),
+            0x03 => Some(PowerMode::Suspend),
+            0x04 | 0x05 => Some(PowerMode::Off),
+            _ => None,
+        }
+    }
+
+    /// `on`, `standby`, `suspend`, `off` (command line and settings).
+    pub fn parse(s: &str) -> Option<PowerMode> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "on" => Some(PowerMode::On),
+            "standby" => Some(PowerMode::Standby),
+            "suspend" => Some(PowerMode::Suspend),
+            "off" => Some(PowerMode::Off),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerMode::On => "on",
+            PowerMode::Standby => "standby",
+            PowerMode::Suspend => "suspend",
+            PowerMode::Off => "off",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerMode::On => "Включен",
+            PowerMode::Standby => "Ожидание",
+            PowerMode::Suspend => "Сон",
+            PowerMode::Off => "Выключен",
+        }
+    }
+}
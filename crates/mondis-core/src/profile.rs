@@ -0,0 +1,55 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_dir;
+
+/// Main profile the panel used to write; migrated into `profile_brightness` of the settings.
+pub fn profile_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("brightness_profile.xml"))
+}
+
+/// Directory with additional named profiles (XML), used for profile cycling.
+pub fn profiles_dir() -> Result<PathBuf> {
+    Ok(config_dir()?.join("profiles"))
+}
+
+/// `profiles/*.xml` sorted by file name.
+pub fn list_profiles() -> Vec<PathBuf> {
+    let Ok(dir) = profiles_dir() else { return Vec::new() };
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut list: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().map(|e| e == "xml").unwrap_or(false))
+        .collect();
+    list.sort();
+    list
+}
+
+/// bus -> brightness from a profile file.
+pub fn load_profile(path: &Path) -> Result<HashMap<u8, u8>> {
+    let content = fs::read_to_string(path).with_context(|| format!("failed to read profile {:?}", path))?;
+
+    let mut brightness_values = HashMap::new();
+
+    // Простой парсинг XML (можно заменить на полноценный XML парсер)
+    for line in content.lines() {
+        if line.trim().starts_with("<display") {
+            if let (Some(bus), Some(brightness)) = (xml_attr(line, "bus"), xml_attr(line, "brightness")) {
+                if let (Ok(bus), Ok(brightness)) = (bus.parse::<u8>(), brightness.parse::<u8>()) {
+                    brightness_values.insert(bus, brightness);
+                }
+            }
+        }
+    }
+    Ok(brightness_values)
+}
+
+fn xml_attr<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!(" {}=\"", name);
+    let start = line.find(&pattern)? + pattern.len();
+    let end = line[start..].find('"')? + start;
+    Some(&line[start..end])
+}
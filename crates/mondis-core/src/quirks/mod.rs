@@ -0,0 +1,242 @@
+//! Monitor quirks: known firmware bugs keyed by EDID manufacturer, product code
+//! and firmware version.
+//!
+//! The built-in list (`builtin.json`) is embedded at compile time; entries from
+//! `~/.config/mondis/quirks.json` (same format) are applied after it, so users can
+//! add monitors or override built-in behaviour without a rebuild.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::config::config_dir;
+
+pub const BUILTIN_QUIRKS: &str = include_str!("builtin.json");
+
+/// DDC timing overrides in milliseconds; unset fields keep the default.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimingOverride {
+    pub reply_delay_ms: Option<u64>,
+    pub command_interval_ms: Option<u64>,
+    pub capabilities_delay_ms: Option<u64>,
+    pub retries: Option<u32>,
+    pub retry_backoff_ms: Option<u64>,
+}
+
+impl TimingOverride {
+    fn merge(&mut self, other: &TimingOverride) {
+        self.reply_delay_ms = other.reply_delay_ms.or(self.reply_delay_ms);
+        self.command_interval_ms = other.command_interval_ms.or(self.command_interval_ms);
+        self.capabilities_delay_ms = other.capabilities_delay_ms.or(self.capabilities_delay_ms);
+        self.retries = other.retries.or(self.retries);
+        self.retry_backoff_ms = other.retry_backoff_ms.or(self.retry_backoff_ms);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VcpMax {
+    #[serde(deserialize_with = "de_code")]
+    pub code: u8,
+    pub max: u16,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedValue {
+    #[serde(deserialize_with = "de_code")]
+    pub value: u8,
+    pub name: String,
+}
+
+/// Vendor-specific audio source control (MCCS has no standard code for it).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioInputQuirk {
+    #[serde(deserialize_with = "de_code")]
+    pub code: u8,
+    /// Selectable sources; when empty, the values listed for `code` in the capabilities string.
+    #[serde(default)]
+    pub inputs: Vec<NamedValue>,
+}
+
+/// One entry of quirks.json. Codes may be written as numbers or hex strings ("0x10").
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuirkEntry {
+    pub manufacturer: String, // PNP ID, e.g. "GSM"
+    #[serde(deserialize_with = "de_opt_product")]
+    pub product: Option<u16>, // EDID product code; any if unset
+    pub firmware: Option<String>, // MCCS firmware level "major.minor"; any if unset
+    pub note: Option<String>,
+    pub timing: TimingOverride,
+    /// Controls that do not work and are not shown.
+    #[serde(deserialize_with = "de_codes")]
+    pub hide_vcp: Vec<u8>,
+    /// Controls whose writes the monitor ignores; shown read-only.
+    #[serde(deserialize_with = "de_codes")]
+    pub read_only_vcp: Vec<u8>,
+    /// Real maximum for controls that report a wrong one.
+    pub vcp_max: Vec<VcpMax>,
+    pub audio_input: Option<AudioInputQuirk>,
+    /// Manufacturer-specific or auto-setup controls that hold state and belong in snapshots.
+    #[serde(deserialize_with = "de_codes")]
+    pub snapshot_vcp: Vec<u8>,
+}
+
+impl QuirkEntry {
+    fn matches(&self, manufacturer: &str, product: u16, firmware: Option<&str>) -> bool {
+        self.manufacturer.eq_ignore_ascii_case(manufacturer.trim())
+            && self.product.is_none_or(|p| p == product)
+            && self.firmware.as_deref().is_none_or(|f| firmware.is_some_and(|cur| same_firmware(f, cur)))
+    }
+
+    // More specific entries are applied later and win
+    fn specificity(&self) -> u8 {
+        self.product.is_some() as u8 + self.firmware.is_some() as u8
+    }
+}
+
+// "1.2" == "01.02" == "1.02"
+fn same_firmware(a: &str, b: &str) -> bool {
+    let parts = |s: &str| s.trim().split('.').map(|p| p.trim().parse::<u32>().ok()).collect::<Vec<_>>();
+    parts(a) == parts(b)
+}
+
+fn parse_code<E: serde::de::Error>(v: &serde_json::Value) -> Result<u64, E> {
+    match v {
+        serde_json::Value::Number(n) => n.as_u64().ok_or_else(|| E::custom(format!("invalid code {}", n))),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => s.parse(),
+            };
+            parsed.map_err(|_| E::custom(format!("invalid code {:?}", s)))
+        }
+        other => Err(E::custom(format!("invalid code {}", other))),
+    }
+}
+
+fn de_code<'de, D: Deserializer<'de>>(d: D) -> Result<u8, D::Error> {
+    let v = serde_json::Value::deserialize(d)?;
+    u8::try_from(parse_code::<D::Error>(&v)?).map_err(serde::de::Error::custom)
+}
+
+fn de_codes<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+    Vec::<serde_json::Value>::deserialize(d)?
+        .iter()
+        .map(|v| u8::try_from(parse_code::<D::Error>(v)?).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn de_opt_product<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u16>, D::Error> {
+    match Option::<serde_json::Value>::deserialize(d)? {
+        None => Ok(None),
+        Some(v) => u16::try_from(parse_code::<D::Error>(&v)?).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct QuirksFile {
+    #[serde(default)]
+    pub quirks: Vec<QuirkEntry>,
+}
+
+/// Everything known about one monitor, merged from all matching entries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MonitorQuirks {
+    pub timing: TimingOverride,
+    pub hidden: BTreeSet<u8>,
+    pub read_only: BTreeSet<u8>,
+    pub max: BTreeMap<u8, u16>,
+    pub notes: Vec<String>,
+    pub audio_input: Option<AudioInputQuirk>,
+    pub snapshot: BTreeSet<u8>,
+}
+
+impl MonitorQuirks {
+    pub fn is_hidden(&self, vcp: u8) -> bool {
+        self.hidden.contains(&vcp)
+    }
+
+    pub fn is_read_only(&self, vcp: u8) -> bool {
+        self.read_only.contains(&vcp)
+    }
+
+    pub fn max_for(&self, vcp: u8) -> Option<u16> {
+        self.max.get(&vcp).copied()
+    }
+
+    pub fn in_snapshot(&self, vcp: u8) -> bool {
+        self.snapshot.contains(&vcp)
+    }
+
+    fn apply(&mut self, entry: &QuirkEntry) {
+        self.timing.merge(&entry.timing);
+        self.hidden.extend(&entry.hide_vcp);
+        self.read_only.extend(&entry.read_only_vcp);
+        self.max.extend(entry.vcp_max.iter().map(|m| (m.code, m.max)));
+        self.notes.extend(entry.note.clone());
+        self.snapshot.extend(&entry.snapshot_vcp);
+        if entry.audio_input.is_some() {
+            self.audio_input = entry.audio_input.clone();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct QuirkDb {
+    pub entries: Vec<QuirkEntry>,
+}
+
+pub fn user_quirks_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("quirks.json"))
+}
+
+impl QuirkDb {
+    pub fn parse(json: &str) -> Result<QuirkDb> {
+        let file: QuirksFile = serde_json::from_str(json).context("failed to parse quirks JSON")?;
+        Ok(QuirkDb { entries: file.quirks })
+    }
+
+    pub fn builtin() -> QuirkDb {
+        QuirkDb::parse(BUILTIN_QUIRKS).expect("embedded builtin.json is valid")
+    }
+
+    /// Built-in entries followed by the user's file (a broken user file is logged and skipped).
+    pub fn load() -> QuirkDb {
+        let mut db = QuirkDb::builtin();
+        let Ok(path) = user_quirks_path() else { return db };
+        let Ok(text) = std::fs::read_to_string(&path) else { return db };
+        match QuirkDb::parse(&text) {
+            Ok(user) => db.entries.extend(user.entries),
+            Err(e) => tracing::warn!("ignoring {:?}: {e:#}", path),
+        }
+        db
+    }
+
+    /// Loaded once per process.
+    pub fn global() -> &'static QuirkDb {
+        static DB: OnceLock<QuirkDb> = OnceLock::new();
+        DB.get_or_init(QuirkDb::load)
+    }
+
+    /// Quirks for a monitor. `firmware` entries only match when the firmware level is known.
+    pub fn lookup(&self, manufacturer: &str, product: u16, firmware: Option<&str>) -> MonitorQuirks {
+        let mut matching: Vec<(u8, usize, &QuirkEntry)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.matches(manufacturer, product, firmware))
+            .map(|(i, e)| (e.specificity(), i, e))
+            .collect();
+        // Less specific first; at equal specificity, later entries (user file) win
+        matching.sort_by_key(|(spec, i, _)| (*spec, *i));
+        let mut quirks = MonitorQuirks::default();
+        for (_, _, entry) in matching {
+            quirks.apply(entry);
+        }
+        quirks
+    }
+}
@@ -0,0 +1,273 @@
+//! Persistent settings (`~/.config/mondis/settings.json`), shared by the panel, the tray and the CLI.
+//!
+//! Per-monitor values are keyed by monitor identity ([`monitor_key`]), not by I2C bus: buses
+//! change when monitors are replugged. Version 1 files (no `version` field, maps keyed by bus)
+//! and the panel's `brightness_profile.xml` are migrated on first load.
+//!
+//! Loads and writes hold an advisory lock on `settings.lock`, shared by every process, and
+//! writes are atomic ([`write_atomic`]). Use [`update_settings`] for read-modify-write: the lock
+//! covers the load, the change and the write, so concurrent writers do not lose each other's
+//! changes, and a file that failed to load is never overwritten with defaults.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cache::cached_displays;
+use crate::config::{config_dir, lock_config, write_atomic, ConfigLock};
+use crate::hotkeys::{default_bindings, HotkeyBinding};
+use crate::model::DisplayInfo;
+use crate::profile;
+use crate::transaction::{Category, ConfirmPolicy};
+
+/// Current settings.json schema.
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Brightness control chosen for a monitor that supports both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlMethod {
+    Ddc,
+    Xrandr,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorSettings {
+    /// Monitor name when last saved, for people reading the file.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control: Option<ControlMethod>,
+    /// Last brightness set through each method, percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness_ddc: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness_xrandr: Option<u8>,
+    /// Brightness confirmed in the panel; the first profile cycled by the tray hotkey.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_brightness: Option<u8>,
+    /// Monitor speaker volume (VCP 0x62) and mute (VCP 0x8D).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub muted: Option<bool>,
+    /// Audio source, a value of the monitor's quirk `audio_input` code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_input: Option<u8>,
+    /// VCP 0x60 value -> user label.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub input_labels: BTreeMap<u8, String>,
+    /// VCP code -> raw value (color::CALIBRATION_CODES).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub color_calibration: BTreeMap<u8, u16>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    pub version: u32,
+    pub monitors: BTreeMap<String, MonitorSettings>, // monitor_key -> settings
+    pub hotkeys: Vec<HotkeyBinding>,
+    pub brightness_step: u8, // percent per hotkey press
+    pub osd_enabled: bool,
+    pub osd_timeout_ms: u32, // how long the OSD stays fully visible before fading out
+    pub verify_writes: bool, // read DDC controls back after writing and show the real value
+    pub poll_interval_ms: u32, // background readback of visible controls; 0 disables
+    pub idle_power_off_s: u32, // power down all but the primary monitor after this much user idle time; 0 disables
+    pub confirm_timeout_s: u32, // changes that need confirmation are rolled back after this many seconds
+    pub confirm_policies: BTreeMap<Category, ConfirmPolicy>, // overrides of Category::default_policy
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            monitors: BTreeMap::new(),
+            hotkeys: default_bindings(),
+            brightness_step: 10,
+            osd_enabled: true,
+            osd_timeout_ms: 1500,
+            verify_writes: true,
+            poll_interval_ms: 3000,
+            idle_power_off_s: 0,
+            confirm_timeout_s: 20,
+            confirm_policies: BTreeMap::new(),
+        }
+    }
+}
+
+/// Settings key of a monitor: its EDID hash, or the I2C bus when the EDID is unknown.
+pub fn monitor_key(display: &DisplayInfo) -> String {
+    match &display.edid_hash {
+        Some(hash) => format!("edid-{}", hash),
+        None => format!("i2c-{}", display.i2c_bus),
+    }
+}
+
+impl UiSettings {
+    pub fn monitor(&self, display: &DisplayInfo) -> Option<&MonitorSettings> {
+        self.monitors.get(&monitor_key(display))
+    }
+
+    /// Settings of the monitor, created when missing; refreshes the stored name.
+    pub fn monitor_mut(&mut self, display: &DisplayInfo) -> &mut MonitorSettings {
+        let monitor = self.monitors.entry(monitor_key(display)).or_default();
+        monitor.name.clone_from(&display.name);
+        monitor
+    }
+
+    /// Take over brightness values of the legacy `brightness_profile.xml` (bus -> percent) for
+    /// monitors that have none yet.
+    pub fn import_brightness_profile(&mut self, profile: &HashMap<u8, u8>, displays: &[DisplayInfo]) {
+        for (&bus, &brightness) in profile {
+            let monitor = legacy_monitor(self, bus, displays);
+            monitor.profile_brightness.get_or_insert(brightness);
+        }
+    }
+}
+
+// Version 1: per-monitor maps keyed by I2C bus, brightness method as a string
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct SettingsV1 {
+    control_prefs: HashMap<u8, String>,
+    last_values_ddc: HashMap<u8, u8>,
+    last_values_xrandr: HashMap<u8, u8>,
+    last_volume: HashMap<u8, u8>,
+    last_muted: HashMap<u8, bool>,
+    input_labels: HashMap<u8, BTreeMap<u8, String>>,
+    color_calibrations: HashMap<u8, BTreeMap<u8, u16>>,
+}
+
+const V1_MONITOR_FIELDS: [&str; 7] =
+    ["control_prefs", "last_values_ddc", "last_values_xrandr", "last_volume", "last_muted", "input_labels", "color_calibrations"];
+
+// Monitor on `bus` at migration time; buses not in the displays cache keep a bus key
+fn legacy_monitor<'a>(settings: &'a mut UiSettings, bus: u8, displays: &[DisplayInfo]) -> &'a mut MonitorSettings {
+    match displays.iter().find(|d| d.i2c_bus == bus) {
+        Some(display) => settings.monitor_mut(display),
+        None => settings.monitors.entry(format!("i2c-{}", bus)).or_default(),
+    }
+}
+
+/// Parse settings of any known version; version 1 per-bus values are assigned to the monitors
+/// on those buses in `displays`. The flag tells whether the file was migrated.
+pub fn parse_settings(data: &str, displays: &[DisplayInfo]) -> Result<(UiSettings, bool)> {
+    let mut value: serde_json::Value = serde_json::from_str(data).context("invalid JSON")?;
+    let object = value.as_object_mut().ok_or_else(|| anyhow!("expected a JSON object"))?;
+    match object.get("version").map(|v| v.as_u64()) {
+        None => {}
+        Some(Some(v)) if v == SETTINGS_VERSION as u64 => {
+            return Ok((serde_json::from_value(value).context("invalid settings")?, false));
+        }
+        Some(Some(v)) if v > SETTINGS_VERSION as u64 => bail!("settings version {} is newer than supported {}", v, SETTINGS_VERSION),
+        Some(other) => bail!("unsupported settings version {:?}", other),
+    }
+
+    let mut legacy = serde_json::Map::new();
+    for field in V1_MONITOR_FIELDS {
+        if let Some(v) = object.remove(field) {
+            legacy.insert(field.to_string(), v);
+        }
+    }
+    let v1: SettingsV1 = serde_json::from_value(legacy.into()).context("invalid version 1 settings")?;
+    let mut settings: UiSettings = serde_json::from_value(value).context("invalid version 1 settings")?;
+    settings.version = SETTINGS_VERSION;
+    for (bus, pref) in v1.control_prefs {
+        let control = if pref == "xrandr" { ControlMethod::Xrandr } else { ControlMethod::Ddc };
+        legacy_monitor(&mut settings, bus, displays).control = Some(control);
+    }
+    for (bus, v) in v1.last_values_ddc {
+        legacy_monitor(&mut settings, bus, displays).brightness_ddc = Some(v);
+    }
+    for (bus, v) in v1.last_values_xrandr {
+        legacy_monitor(&mut settings, bus, displays).brightness_xrandr = Some(v);
+    }
+    for (bus, v) in v1.last_volume {
+        legacy_monitor(&mut settings, bus, displays).volume = Some(v);
+    }
+    for (bus, v) in v1.last_muted {
+        legacy_monitor(&mut settings, bus, displays).muted = Some(v);
+    }
+    for (bus, labels) in v1.input_labels {
+        legacy_monitor(&mut settings, bus, displays).input_labels = labels;
+    }
+    for (bus, values) in v1.color_calibrations {
+        legacy_monitor(&mut settings, bus, displays).color_calibration = values;
+    }
+    Ok((settings, true))
+}
+
+pub fn settings_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("settings.json"))
+}
+
+// Serializes loads that may migrate and all writes, across processes
+fn lock() -> Result<ConfigLock> {
+    lock_config("settings.lock")
+}
+
+/// Load the settings, migrating older files (the version 1 file is kept as `settings.json.v1`).
+/// Defaults when there is no file; an unreadable or invalid file is an error.
+pub fn load_settings() -> Result<UiSettings> {
+    let _guard = lock()?;
+    load_locked()
+}
+
+fn load_locked() -> Result<UiSettings> {
+    let path = settings_path()?;
+    let (mut settings, migrated) = if path.exists() {
+        let data = fs::read_to_string(&path).with_context(|| format!("failed to read settings {:?}", path))?;
+        parse_settings(&data, &cached_displays()).with_context(|| format!("failed to load settings {:?}", path))?
+    } else {
+        (UiSettings::default(), false)
+    };
+    let profile_path = profile::profile_path()?;
+    let import_profile = (migrated || !path.exists()) && profile_path.exists();
+    if import_profile {
+        let values = profile::load_profile(&profile_path)?;
+        settings.import_brightness_profile(&values, &cached_displays());
+    }
+    if migrated {
+        let backup = path.with_extension("json.v1");
+        fs::copy(&path, &backup).with_context(|| format!("failed to back up settings to {:?}", backup))?;
+    }
+    if migrated || import_profile {
+        write_locked(&settings)?;
+        tracing::info!("migrated settings to version {}", SETTINGS_VERSION);
+    }
+    Ok(settings)
+}
+
+/// Settings for reading only: defaults when loading fails, with the error logged.
+/// Never write back what this returns; use [`update_settings`].
+pub fn read_settings() -> UiSettings {
+    load_settings().unwrap_or_else(|e| {
+        tracing::error!("using default settings: {e:#}");
+        UiSettings::default()
+    })
+}
+
+/// Replace the settings file atomically.
+pub fn write_settings(settings: &UiSettings) -> Result<()> {
+    let _guard = lock()?;
+    write_locked(settings)
+}
+
+fn write_locked(settings: &UiSettings) -> Result<()> {
+    let path = settings_path()?;
+    let serialized = serde_json::to_string_pretty(settings).context("failed to serialize settings")?;
+    write_atomic(&path, serialized.as_bytes()).with_context(|| format!("failed to write settings {:?}", path))
+}
+
+/// Load, change and write the settings as one step. Nothing is written when loading fails.
+pub fn update_settings<R>(change: impl FnOnce(&mut UiSettings) -> R) -> Result<R> {
+    let _guard = lock()?;
+    let mut settings = load_locked()?;
+    let result = change(&mut settings);
+    write_locked(&settings)?;
+    Ok(result)
+}
@@ -0,0 +1,119 @@
+//! Monitor state snapshots: every readable VCP control plus XRandR brightness and gamma,
+//! saved as JSON in `~/.config/mondis/snapshots`. A snapshot can be restored on the same
+//! monitor or imported on another one of the same model (same EDID manufacturer and product code).
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_dir;
+use crate::inputs::VCP_INPUT_SOURCE;
+use crate::power::VCP_POWER_MODE;
+use crate::quirks::MonitorQuirks;
+use crate::vcp::{self, VcpKind};
+
+// Not state: restoring the input or power mode would switch the monitor away, 0x02/0x52 are protocol codes
+const EXCLUDED: [u8; 4] = [0x02, 0x52, VCP_INPUT_SOURCE, VCP_POWER_MODE];
+// Auto setup and auto color setup start an adjustment when written
+const AUTO_SETUP: [u8; 2] = [0x1E, 0x1F];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct XrandrState {
+    pub brightness: u8, // percent
+    pub gamma: (f32, f32, f32),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// EDID manufacturer PNP ID and product code: snapshots apply to monitors of the same model.
+    pub manufacturer: String,
+    pub product_code: u16,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    /// Unix time of the capture.
+    pub created: u64,
+    /// VCP code -> raw value.
+    pub vcp: BTreeMap<u8, u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xrandr: Option<XrandrState>,
+}
+
+/// A declared code whose value belongs in a snapshot (readable and writable, not an action).
+/// Manufacturer-specific and auto-setup codes are captured and restored only when a quirk lists
+/// them in `snapshot_vcp`: what writing them back does is unknown. Hidden codes never are.
+pub fn is_captured(code: u8, declared: &[u8], quirks: &MonitorQuirks) -> bool {
+    if EXCLUDED.contains(&code) || quirks.is_hidden(code) {
+        return false;
+    }
+    if vcp::is_manufacturer_specific(code) || AUTO_SETUP.contains(&code) {
+        return quirks.in_snapshot(code);
+    }
+    matches!(vcp::kind(code, declared), VcpKind::Continuous | VcpKind::NonContinuous)
+}
+
+impl Snapshot {
+    pub fn matches(&self, manufacturer: &str, product_code: u16) -> bool {
+        self.manufacturer.eq_ignore_ascii_case(manufacturer) && self.product_code == product_code
+    }
+
+    /// Values in restore order: modes and presets first (switching them resets the continuous
+    /// controls on most monitors), then continuous controls.
+    pub fn restore_order(&self) -> Vec<(u8, u16)> {
+        let mut values: Vec<(u8, u16)> = self.vcp.iter().map(|(&c, &v)| (c, v)).collect();
+        values.sort_by_key(|&(code, _)| (vcp::kind(code, &[]) == VcpKind::Continuous, code));
+        values
+    }
+
+    /// File name for a new snapshot: model, serial and capture time.
+    pub fn file_name(&self) -> String {
+        let model: String = self
+            .model
+            .as_deref()
+            .unwrap_or(&self.manufacturer)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        match &self.serial {
+            Some(serial) => format!("{}-{}-{}.json", model, serial, self.created),
+            None => format!("{}-{}.json", model, self.created),
+        }
+    }
+}
+
+pub fn snapshots_dir() -> Result<PathBuf> {
+    let dir = config_dir()?.join("snapshots");
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create snapshots dir {:?}", dir))?;
+    Ok(dir)
+}
+
+/// Write to `path`, or to the snapshots directory under [`Snapshot::file_name`].
+pub fn save_snapshot(snapshot: &Snapshot, path: Option<&Path>) -> Result<PathBuf> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => snapshots_dir()?.join(snapshot.file_name()),
+    };
+    let serialized = serde_json::to_string_pretty(snapshot).context("failed to serialize snapshot")?;
+    fs::write(&path, serialized).with_context(|| format!("failed to write snapshot {:?}", path))?;
+    Ok(path)
+}
+
+pub fn load_snapshot(path: &Path) -> Result<Snapshot> {
+    let content = fs::read_to_string(path).with_context(|| format!("failed to read snapshot {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("invalid snapshot {:?}", path))
+}
+
+/// Snapshot files, oldest first.
+pub fn list_snapshots() -> Vec<PathBuf> {
+    let Ok(dir) = snapshots_dir() else { return Vec::new() };
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut list: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .map(|p| (fs::metadata(&p).and_then(|m| m.modified()).unwrap_or(std::time::UNIX_EPOCH), p))
+        .collect();
+    list.sort();
+    list.into_iter().map(|(_, p)| p).collect()
+}
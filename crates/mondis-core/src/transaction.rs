@@ -0,0 +1,327 @@
+//! Change transactions: every setting changed since the last confirmation, across all monitors,
+//! with its original value and a confirmation deadline. Confirming keeps the changes, cancelling
+//! or letting the deadline pass rolls all of them back together.
+//!
+//! Each setting belongs to a [`Category`] with a [`ConfirmPolicy`]: changes that need no
+//! confirmation (brightness by default) stay in the transaction only until the user stops
+//! adjusting them and are then committed on their own.
+//!
+//! The engine does no I/O: the caller applies the values returned by [`Transaction::rollback`]
+//! its own way. The panel uses it through its write queue, the tray for input and power changes
+//! (confirmed from a desktop notification); the CLI applies changes at once, so its changes are
+//! never confirmed or rolled back, whatever the policies say.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::audio::{VCP_AUDIO_MUTE, VCP_AUDIO_VOLUME};
+use crate::color;
+use crate::inputs::VCP_INPUT_SOURCE;
+use crate::power::VCP_POWER_MODE;
+use crate::settings::UiSettings;
+use crate::vcp::{self, VcpKind};
+
+/// Default time to confirm a change before it is rolled back.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(20);
+/// Quiet time after the last auto-confirmed change before it is committed (a slider drag is one change).
+pub const AUTO_CONFIRM_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Brightness and contrast, DDC or XRandR.
+    Brightness,
+    /// Color preset, gains, black levels and XRandR gamma.
+    Color,
+    Input,
+    Audio,
+    Power,
+    Layout,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 7] = [
+        Category::Brightness,
+        Category::Color,
+        Category::Input,
+        Category::Audio,
+        Category::Power,
+        Category::Layout,
+        Category::Other,
+    ];
+
+    pub fn default_policy(self) -> ConfirmPolicy {
+        match self {
+            Category::Brightness | Category::Audio => ConfirmPolicy::Auto,
+            _ => ConfirmPolicy::Confirm,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmPolicy {
+    /// Committed without asking once the user stops adjusting.
+    Auto,
+    /// Rolled back unless confirmed before the timeout.
+    Confirm,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Setting {
+    /// Output mode and position, restored with `xrandr --output <output> <args>`.
+    Layout { output: String },
+    /// DDC/CI control of the monitor on an I2C bus.
+    Vcp { bus: u8, code: u8 },
+    /// XRandR software brightness of an output.
+    XrandrBrightness { output: String },
+    /// XRandR per-channel gamma of an output.
+    XrandrGamma { output: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    /// Raw VCP value.
+    Raw(u16),
+    Percent(u8),
+    Gamma(f32, f32, f32),
+    /// `xrandr` arguments for the output (`--mode 1920x1080 --pos 0x0 ...`).
+    Args(Vec<String>),
+}
+
+impl Value {
+    pub fn raw(&self) -> Option<u16> {
+        match self {
+            Value::Raw(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub original: Value,
+    /// Last value written, when the caller knows it.
+    pub current: Option<Value>,
+    pub policy: ConfirmPolicy,
+}
+
+/// Changes resolved by [`Transaction::expire`] or [`Transaction::settle`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Resolved {
+    pub committed: Vec<(Setting, Change)>,
+    /// Original values to apply, in restore order.
+    pub rolled_back: Vec<(Setting, Value)>,
+}
+
+impl Resolved {
+    pub fn is_empty(&self) -> bool {
+        self.committed.is_empty() && self.rolled_back.is_empty()
+    }
+}
+
+impl Setting {
+    pub fn category(&self) -> Category {
+        match self {
+            Setting::Layout { .. } => Category::Layout,
+            Setting::XrandrBrightness { .. } => Category::Brightness,
+            Setting::XrandrGamma { .. } => Category::Color,
+            Setting::Vcp { code, .. } => match *code {
+                0x10 | 0x12 => Category::Brightness,
+                VCP_INPUT_SOURCE => Category::Input,
+                VCP_AUDIO_VOLUME | VCP_AUDIO_MUTE => Category::Audio,
+                VCP_POWER_MODE => Category::Power,
+                code if color::CALIBRATION_CODES.contains(&code) => Category::Color,
+                _ => Category::Other,
+            },
+        }
+    }
+
+    // Modes and presets are restored before continuous controls: switching them resets the
+    // continuous controls on most monitors. Layout goes first, XRandR brightness before gamma.
+    fn rollback_rank(&self) -> u8 {
+        match self {
+            Setting::Layout { .. } => 0,
+            Setting::Vcp { code, .. } if vcp::kind(*code, &[]) != VcpKind::Continuous => 1,
+            Setting::Vcp { .. } => 2,
+            Setting::XrandrBrightness { .. } => 3,
+            Setting::XrandrGamma { .. } => 4,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Transaction {
+    timeout: Duration,
+    policies: BTreeMap<Category, ConfirmPolicy>,
+    changes: BTreeMap<Setting, Change>,
+    // Rollback of changes that need confirmation
+    deadline: Option<Instant>,
+    // Commit of auto-confirmed changes
+    auto_deadline: Option<Instant>,
+}
+
+impl Default for Transaction {
+    fn default() -> Self {
+        Self::new(DEFAULT_TIMEOUT)
+    }
+}
+
+impl Transaction {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout, policies: BTreeMap::new(), changes: BTreeMap::new(), deadline: None, auto_deadline: None }
+    }
+
+    /// Timeout and policies from `confirm_timeout_s` and `confirm_policies` in the settings.
+    pub fn from_settings(settings: &UiSettings) -> Self {
+        let mut transaction = Self::new(Duration::from_secs(settings.confirm_timeout_s.max(1) as u64));
+        for (&category, &policy) in &settings.confirm_policies {
+            transaction.set_policy(category, policy);
+        }
+        transaction
+    }
+
+    /// Powering a monitor off always needs confirmation: a monitor turned off by mistake
+    /// may be the one showing the panel.
+    pub fn policy(&self, category: Category) -> ConfirmPolicy {
+        match category {
+            Category::Power => ConfirmPolicy::Confirm,
+            _ => self.policies.get(&category).copied().unwrap_or(category.default_policy()),
+        }
+    }
+
+    /// Applies to changes recorded from now on.
+    pub fn set_policy(&mut self, category: Category, policy: ConfirmPolicy) {
+        self.policies.insert(category, policy);
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Takes effect from the next recorded change.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Record a change and restart its countdown: the confirmation timeout, or the auto-confirm
+    /// delay for categories that need no confirmation. `original` is asked only the first time
+    /// the setting changes in this transaction; without it the change cannot be rolled back and
+    /// is not tracked. A change back to the original value is dropped. Returns whether the
+    /// setting is tracked after the call.
+    pub fn record(&mut self, setting: Setting, original: impl FnOnce() -> Option<Value>, current: Option<Value>, now: Instant) -> bool {
+        if !self.changes.contains_key(&setting) {
+            let Some(original) = original() else { return false };
+            let policy = self.policy(setting.category());
+            self.changes.insert(setting.clone(), Change { original, current: None, policy });
+        }
+        let back_to_original = matches!((&current, self.changes.get(&setting)), (Some(v), Some(c)) if *v == c.original);
+        let policy = self.changes[&setting].policy;
+        if back_to_original {
+            self.changes.remove(&setting);
+        } else if let Some(change) = self.changes.get_mut(&setting) {
+            if current.is_some() {
+                change.current = current;
+            }
+            match policy {
+                ConfirmPolicy::Confirm => self.deadline = Some(now + self.timeout),
+                ConfirmPolicy::Auto => self.auto_deadline = Some(now + AUTO_CONFIRM_DELAY),
+            }
+        }
+        self.clear_idle_deadlines();
+        !back_to_original
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Some pending change waits for the user to confirm it.
+    pub fn needs_confirmation(&self) -> bool {
+        self.changes.values().any(|c| c.policy == ConfirmPolicy::Confirm)
+    }
+
+    pub fn contains(&self, setting: &Setting) -> bool {
+        self.changes.contains_key(setting)
+    }
+
+    pub fn original(&self, setting: &Setting) -> Option<&Value> {
+        self.changes.get(setting).map(|c| &c.original)
+    }
+
+    pub fn changes(&self) -> impl Iterator<Item = (&Setting, &Change)> {
+        self.changes.iter()
+    }
+
+    /// Time left to confirm; `None` when nothing waits for confirmation.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.deadline.map(|d| d.saturating_duration_since(now))
+    }
+
+    /// Resolve the changes whose countdown has run out: auto-confirmed ones are committed,
+    /// the ones that needed confirmation are rolled back.
+    pub fn expire(&mut self, now: Instant) -> Resolved {
+        let commit_auto = self.auto_deadline.is_some_and(|d| now >= d);
+        let rollback_confirm = self.deadline.is_some_and(|d| now >= d);
+        self.resolve(commit_auto, rollback_confirm)
+    }
+
+    /// Resolve everything now, as if both countdowns had run out (e.g. when the panel exits).
+    pub fn settle(&mut self) -> Resolved {
+        self.resolve(true, true)
+    }
+
+    /// Keep all the changes; returns them for the caller to persist.
+    pub fn commit(&mut self) -> Vec<(Setting, Change)> {
+        self.deadline = None;
+        self.auto_deadline = None;
+        std::mem::take(&mut self.changes).into_iter().collect()
+    }
+
+    /// Drop all the changes; returns the original values to apply, in restore order.
+    pub fn rollback(&mut self) -> Vec<(Setting, Value)> {
+        self.deadline = None;
+        self.auto_deadline = None;
+        restore_order(std::mem::take(&mut self.changes).into_iter().map(|(s, c)| (s, c.original)).collect())
+    }
+
+    /// Stop tracking settings that no longer need a rollback (e.g. after a factory reset).
+    pub fn forget(&mut self, mut forget: impl FnMut(&Setting) -> bool) {
+        self.changes.retain(|s, _| !forget(s));
+        self.clear_idle_deadlines();
+    }
+
+    fn resolve(&mut self, commit_auto: bool, rollback_confirm: bool) -> Resolved {
+        let mut resolved = Resolved::default();
+        let mut rolled_back = Vec::new();
+        for (setting, change) in std::mem::take(&mut self.changes) {
+            match change.policy {
+                ConfirmPolicy::Auto if commit_auto => resolved.committed.push((setting, change)),
+                ConfirmPolicy::Confirm if rollback_confirm => rolled_back.push((setting, change.original)),
+                _ => {
+                    self.changes.insert(setting, change);
+                }
+            }
+        }
+        resolved.rolled_back = restore_order(rolled_back);
+        self.clear_idle_deadlines();
+        resolved
+    }
+
+    fn clear_idle_deadlines(&mut self) {
+        if !self.needs_confirmation() {
+            self.deadline = None;
+        }
+        if !self.changes.values().any(|c| c.policy == ConfirmPolicy::Auto) {
+            self.auto_deadline = None;
+        }
+    }
+}
+
+/// Sort values to apply: layout, then modes and presets, continuous controls, XRandR brightness and gamma.
+pub fn restore_order(mut values: Vec<(Setting, Value)>) -> Vec<(Setting, Value)> {
+    values.sort_by_key(|(s, _)| s.rollback_rank());
+    values
+}
@@ -0,0 +1,142 @@
+//! Catalog of MCCS VCP codes: display names and control types, used to build controls
+//! for any code listed in the capabilities string.
+
+/// How a control is read and written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VcpKind {
+    /// Value in 0..=max (slider).
+    Continuous,
+    /// One of a set of values (dropdown of the values listed in the capabilities string).
+    NonContinuous,
+    ReadOnly,
+    /// Action codes (factory resets, degauss); never read.
+    WriteOnly,
+}
+
+use VcpKind::*;
+
+// MCCS 2.2a codes; 0xE0..=0xFF are manufacturer specific
+const KNOWN: &[(u8, &str, VcpKind)] = &[
+    (0x01, "Размагничивание", WriteOnly),
+    (0x02, "Новое значение", NonContinuous),
+    (0x04, "Сброс к заводским настройкам", WriteOnly),
+    (0x05, "Сброс яркости и контраста", WriteOnly),
+    (0x06, "Сброс геометрии", WriteOnly),
+    (0x08, "Сброс цвета", WriteOnly),
+    (0x0A, "Сброс TV", WriteOnly),
+    (0x0B, "Шаг цветовой температуры", ReadOnly),
+    (0x0C, "Цветовая температура", Continuous),
+    (0x0E, "Тактовая частота", Continuous),
+    (0x10, "Яркость", Continuous),
+    (0x11, "Телесные тона", NonContinuous),
+    (0x12, "Контраст", Continuous),
+    (0x13, "Управление подсветкой", Continuous),
+    (0x14, "Цветовой пресет", NonContinuous),
+    (0x16, "Красный канал", Continuous),
+    (0x17, "Коррекция цвета для дальтоников", NonContinuous),
+    (0x18, "Зеленый канал", Continuous),
+    (0x1A, "Синий канал", Continuous),
+    (0x1E, "Автонастройка", NonContinuous),
+    (0x1F, "Автонастройка цвета", NonContinuous),
+    (0x20, "Положение по горизонтали", Continuous),
+    (0x30, "Положение по вертикали", Continuous),
+    (0x3E, "Фаза", Continuous),
+    (0x52, "Активный параметр", ReadOnly),
+    (0x59, "Насыщенность: красный", Continuous),
+    (0x5A, "Насыщенность: желтый", Continuous),
+    (0x5B, "Насыщенность: зеленый", Continuous),
+    (0x5C, "Насыщенность: голубой", Continuous),
+    (0x5D, "Насыщенность: синий", Continuous),
+    (0x5E, "Насыщенность: пурпурный", Continuous),
+    (0x60, "Источник входа", NonContinuous),
+    (0x62, "Громкость", Continuous),
+    (0x6C, "Уровень черного R", Continuous),
+    (0x6E, "Уровень черного G", Continuous),
+    (0x70, "Уровень черного B", Continuous),
+    (0x72, "Гамма", NonContinuous),
+    (0x73, "Размер LUT", ReadOnly),
+    (0x86, "Масштабирование", NonContinuous),
+    (0x87, "Резкость", Continuous),
+    (0x8A, "Насыщенность", Continuous),
+    (0x8D, "Отключение звука", NonContinuous),
+    (0x8F, "Высокие частоты", Continuous),
+    (0x90, "Оттенок", Continuous),
+    (0x91, "Низкие частоты", Continuous),
+    (0x93, "Баланс", Continuous),
+    (0x94, "Стереорежим", NonContinuous),
+    (0x9B, "Оттенок: красный", Continuous),
+    (0x9C, "Оттенок: желтый", Continuous),
+    (0x9D, "Оттенок: зеленый", Continuous),
+    (0x9E, "Оттенок: голубой", Continuous),
+    (0x9F, "Оттенок: синий", Continuous),
+    (0xA0, "Оттенок: пурпурный", Continuous),
+    (0xAA, "Ориентация экрана", ReadOnly),
+    (0xAC, "Частота строк", ReadOnly),
+    (0xAE, "Частота кадров", ReadOnly),
+    (0xB0, "Сохранить/восстановить настройки", WriteOnly),
+    (0xB2, "Расположение субпикселей", ReadOnly),
+    (0xB6, "Технология дисплея", ReadOnly),
+    (0xC0, "Время работы, ч", ReadOnly),
+    (0xC6, "Ключ приложения", ReadOnly),
+    (0xC8, "Тип контроллера", ReadOnly),
+    (0xC9, "Версия прошивки", ReadOnly),
+    (0xCA, "Экранное меню", NonContinuous),
+    (0xCC, "Язык меню", NonContinuous),
+    (0xD6, "Питание", NonContinuous),
+    (0xD7, "Вспомогательный выход питания", NonContinuous),
+    (0xDA, "Режим сканирования", NonContinuous),
+    (0xDB, "Режим изображения", NonContinuous),
+    (0xDC, "Режим отображения", NonContinuous),
+    (0xDF, "Версия VCP", ReadOnly),
+];
+
+/// Manufacturer-specific range of codes (0xE0..=0xFF).
+pub fn is_manufacturer_specific(code: u8) -> bool {
+    code >= 0xE0
+}
+
+/// Display name of a code: the MCCS name, or the raw code.
+pub fn name(code: u8) -> String {
+    match KNOWN.iter().find(|(c, _, _)| *c == code) {
+        Some((_, name, _)) => name.to_string(),
+        None if is_manufacturer_specific(code) => format!("Код производителя 0x{:02X}", code),
+        None => format!("VCP 0x{:02X}", code),
+    }
+}
+
+/// Control type of a code. Codes missing from the catalog are non-continuous when the
+/// capabilities string lists values for them (`declared`), otherwise continuous.
+pub fn kind(code: u8, declared: &[u8]) -> VcpKind {
+    match KNOWN.iter().find(|(c, _, _)| *c == code) {
+        Some((_, _, kind)) => *kind,
+        None if declared.is_empty() => Continuous,
+        None => NonContinuous,
+    }
+}
+
+pub const VCP_RESTORE_FACTORY: u8 = 0x04;
+pub const VCP_RESTORE_LUMINANCE_CONTRAST: u8 = 0x05;
+pub const VCP_RESTORE_GEOMETRY: u8 = 0x06;
+pub const VCP_RESTORE_COLOR: u8 = 0x08;
+
+/// Restore-factory commands, written with any non-zero value.
+pub const FACTORY_RESETS: [u8; 4] = [VCP_RESTORE_FACTORY, VCP_RESTORE_LUMINANCE_CONTRAST, VCP_RESTORE_GEOMETRY, VCP_RESTORE_COLOR];
+
+const GEOMETRY_CODES: &[u8] = &[
+    0x0E, 0x20, 0x22, 0x24, 0x26, 0x28, 0x2A, 0x2C, 0x30, 0x32, 0x34, 0x36, 0x38, 0x3A, 0x3C, 0x3E,
+];
+const COLOR_CODES: &[u8] = &[
+    0x0C, 0x14, 0x16, 0x18, 0x1A, 0x59, 0x5A, 0x5B, 0x5C, 0x5D, 0x5E, 0x6C, 0x6E, 0x70, 0x8A, 0x90, 0x9B, 0x9C, 0x9D,
+    0x9E, 0x9F, 0xA0,
+];
+
+/// Controls a restore-factory command returns to their defaults; `None` for the full reset
+/// (every control may change).
+pub fn reset_scope(code: u8) -> Option<&'static [u8]> {
+    match code {
+        VCP_RESTORE_LUMINANCE_CONTRAST => Some(&[0x10, 0x12]),
+        VCP_RESTORE_GEOMETRY => Some(GEOMETRY_CODES),
+        VCP_RESTORE_COLOR => Some(COLOR_CODES),
+        _ => None,
+    }
+}
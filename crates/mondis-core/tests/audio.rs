@@ -0,0 +1,10 @@
+use mondis_core::audio::{is_muted, mute_value};
+
+#[test]
+fn mute_values_round_trip() {
+    assert_eq!(is_muted(mute_value(true)), Some(true));
+    assert_eq!(is_muted(mute_value(false)), Some(false));
+    // Screen blanking in the high byte on MCCS 3 monitors
+    assert_eq!(is_muted(0x0201), Some(true));
+    assert_eq!(is_muted(0x00), None);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use mondis_core::cache::{parse_displays_cache, validate_displays, CACHE_VERSION};
+use mondis_core::model::DisplayInfo;
+use std::collections::HashMap;
+
+fn display(bus: u8, connector: Option<&str>, edid_hash: Option<&str>) -> DisplayInfo {
+    DisplayInfo { connector: connector.map(str::to_string), ..common::display(bus, edid_hash) }
+}
+
+fn card_json(displays: &[DisplayInfo]) -> serde_json::Value {
+    serde_json::json!([{ "name": "GPU", "displays": displays }])
+}
+
+#[test]
+fn reads_current_version() {
+    let data = serde_json::json!({
+        "version": CACHE_VERSION,
+        "written_at": 1_700_000_000u64,
+        "cards": card_json(&[display(5, Some("card0-DP-1"), Some("0a0b0c0d"))]),
+    });
+    let cards = parse_displays_cache(&data.to_string()).unwrap();
+    assert_eq!(cards.len(), 1);
+    assert_eq!(cards[0].displays[0].i2c_bus, 5);
+}
+
+#[test]
+fn migrates_v1_array_dropping_unverifiable_entries() {
+    let data = card_json(&[
+        display(5, Some("card0-DP-1"), Some("0a0b0c0d")),
+        display(6, None, Some("11111111")),
+        display(7, Some("card0-HDMI-A-1"), None),
+    ]);
+    let cards = parse_displays_cache(&data.to_string()).unwrap();
+    let buses: Vec<u8> = cards.iter().flat_map(|c| &c.displays).map(|d| d.i2c_bus).collect();
+    assert_eq!(buses, vec![5]);
+}
+
+#[test]
+fn discards_unknown_versions_and_garbage() {
+    let future = serde_json::json!({ "version": CACHE_VERSION + 1, "cards": [] });
+    assert!(parse_displays_cache(&future.to_string()).is_none());
+    assert!(parse_displays_cache("{ not json").is_none());
+    assert!(parse_displays_cache("{\"cards\": []}").is_none());
+}
+
+#[test]
+fn validation_against_sysfs() {
+    let connectors: HashMap<String, Option<String>> = [
+        ("card0-DP-1".to_string(), Some("aaaaaaaa".to_string())), // same monitor
+        ("card0-DP-2".to_string(), Some("cccccccc".to_string())), // monitor replaced
+        ("card0-HDMI-A-1".to_string(), None),                     // unplugged
+        ("card0-DP-3".to_string(), Some("dddddddd".to_string())), // newly connected
+    ]
+    .into_iter()
+    .collect();
+    let cached = vec![
+        display(5, Some("card0-DP-1"), Some("aaaaaaaa")),
+        display(6, Some("card0-DP-2"), Some("bbbbbbbb")),
+        display(7, Some("card0-HDMI-A-1"), Some("eeeeeeee")),
+        display(8, Some("card1-DP-1"), Some("ffffffff")), // GPU removed
+        display(9, None, Some("99999999")),               // not verifiable
+    ];
+    let check = validate_displays(cached, &connectors);
+    let buses = |list: &[DisplayInfo]| list.iter().map(|d| d.i2c_bus).collect::<Vec<_>>();
+    assert_eq!(buses(&check.valid), vec![5, 9]);
+    assert_eq!(buses(&check.stale), vec![6, 7, 8]);
+    assert_eq!(check.new_connectors, vec!["card0-DP-2".to_string(), "card0-DP-3".to_string()]);
+    assert!(!check.is_clean());
+}
+
+#[test]
+fn validation_without_drm_sysfs_keeps_cache() {
+    let check = validate_displays(vec![display(5, Some("card0-DP-1"), Some("aaaaaaaa"))], &HashMap::new());
+    assert_eq!(check.valid.len(), 1);
+    assert!(check.is_clean());
+}
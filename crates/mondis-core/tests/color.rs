@@ -0,0 +1,21 @@
+use mondis_core::color::{preset_label, white_point_gains};
+
+#[test]
+fn white_point_gains_relative_to_d65() {
+    for gain in white_point_gains(6500) {
+        assert!((gain - 1.0).abs() < 1e-9);
+    }
+    let [r, g, b] = white_point_gains(5000);
+    assert_eq!(r, 1.0);
+    assert!(r > g && g > b, "warm white: {r} {g} {b}");
+    let [r, g, b] = white_point_gains(9300);
+    assert_eq!(b, 1.0);
+    assert!(b > g && g > r, "cool white: {r} {g} {b}");
+}
+
+#[test]
+fn preset_labels() {
+    assert_eq!(preset_label(0x05), "6500 K");
+    assert_eq!(preset_label(0x0B), "User 1");
+    assert_eq!(preset_label(0x20), "Пресет 0x20");
+}
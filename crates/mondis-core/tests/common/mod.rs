@@ -0,0 +1,15 @@
+use mondis_core::model::DisplayInfo;
+
+/// DDC monitor on `bus`; tests override other fields with struct update syntax.
+pub fn display(bus: u8, edid_hash: Option<&str>) -> DisplayInfo {
+    DisplayInfo {
+        i2c_bus: bus,
+        name: format!("Monitor {}", bus),
+        manufacturer: Some("DEL".into()),
+        model: Some("U2720Q".into()),
+        supports_ddc: true,
+        edid_hash: edid_hash.map(str::to_string),
+        card_name: Some("GPU".into()),
+        ..DisplayInfo::default()
+    }
+}
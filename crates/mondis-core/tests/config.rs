@@ -0,0 +1,31 @@
+use mondis_core::config::{config_dir, write_atomic};
+use mondis_core::settings::{load_settings, update_settings};
+use std::fs;
+
+#[test]
+fn concurrent_updates_keep_every_change() {
+    let home = std::env::temp_dir().join(format!("mondis-config-test-{}", std::process::id()));
+    std::env::set_var("HOME", &home);
+    let dir = config_dir().unwrap();
+
+    let threads: Vec<_> = (0..8)
+        .map(|i| {
+            std::thread::spawn(move || {
+                update_settings(|settings| settings.monitors.entry(format!("i2c-{}", i)).or_default().volume = Some(i)).unwrap();
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    let settings = load_settings().unwrap();
+    assert_eq!(settings.monitors.len(), 8);
+
+    // No temp files are left behind
+    let files: Vec<_> = fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name().into_string().unwrap()).collect();
+    assert!(files.iter().all(|f| f == "settings.json" || f == "settings.lock"), "{:?}", files);
+
+    write_atomic(&dir.join("settings.json"), b"{}").unwrap();
+    assert_eq!(fs::read_to_string(dir.join("settings.json")).unwrap(), "{}");
+    fs::remove_dir_all(&home).unwrap();
+}
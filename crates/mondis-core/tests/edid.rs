@@ -0,0 +1,162 @@
+use mondis_core::edid::cea::DataBlock;
+use mondis_core::edid::{block_checksum_ok, hash_short, Descriptor, DigitalInterface, Edid, EdidError, Extension, Mode, VideoInput};
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/data/edid/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+fn parse(name: &str) -> Edid {
+    Edid::parse(&fixture(name)).unwrap()
+}
+
+#[test]
+fn all_fixtures_have_valid_checksums() {
+    for name in ["card0-DP-3.bin", "card0-LVDS-1.bin", "card0-VGA-1.bin", "card0-eDP-1.bin"] {
+        let edid = parse(name);
+        assert!(edid.checksum_errors.is_empty(), "{}", name);
+        assert_eq!(edid.extensions.len(), edid.extension_count as usize, "{}", name);
+    }
+}
+
+#[test]
+fn rejects_short_and_garbage_input() {
+    assert_eq!(Edid::parse(&[0u8; 64]), Err(EdidError::TooShort(64)));
+    assert_eq!(Edid::parse(&[0x55u8; 128]), Err(EdidError::BadHeader));
+}
+
+#[test]
+fn reports_bad_checksum_but_still_parses() {
+    let mut bytes = fixture("card0-DP-3.bin");
+    bytes[0x90] ^= 0xFF;
+    assert!(!block_checksum_ok(&bytes[128..]));
+    let edid = Edid::parse(&bytes).unwrap();
+    assert_eq!(edid.checksum_errors, vec![1]);
+    assert_eq!(edid.name(), Some("G236HL"));
+}
+
+#[test]
+fn base_block_identity() {
+    let edid = parse("card0-DP-3.bin");
+    assert_eq!(edid.manufacturer, "ACR");
+    assert_eq!(edid.product_code, 0x02EB);
+    assert_eq!((edid.version, edid.revision), (1, 3));
+    assert_eq!(edid.manufacture_week, Some(43));
+    assert_eq!(edid.manufacture_year, Some(2013));
+    assert_eq!(edid.name(), Some("G236HL"));
+    assert_eq!(edid.serial_string(), Some("LVNEE0052482"));
+    assert_eq!(edid.screen_size_cm, Some((51, 29)));
+    assert_eq!(edid.gamma, Some(2.2));
+    assert!(edid.features.dpms_standby && edid.features.dpms_suspend && edid.features.dpms_active_off);
+
+    let samsung = parse("card0-VGA-1.bin");
+    assert_eq!(samsung.manufacturer, "SAM");
+    assert_eq!(samsung.name(), Some("SyncMaster"));
+    assert_eq!(samsung.serial_string(), Some("HS3P701105"));
+    assert!(matches!(samsung.input, VideoInput::Analog { separate_sync: true, .. }));
+}
+
+#[test]
+fn base_block_input_and_chromaticity() {
+    let edp = parse("card0-eDP-1.bin");
+    assert_eq!(edp.manufacturer, "SHP");
+    assert_eq!(edp.input, VideoInput::Digital { bit_depth: Some(8), interface: DigitalInterface::DisplayPort });
+    assert!(edp.features.srgb_default);
+    assert!((edp.chromaticity.white.0 - 0.3125).abs() < 0.001);
+    assert!((edp.chromaticity.red.0 - 0.64).abs() < 0.001);
+
+    let lvds = parse("card0-LVDS-1.bin");
+    assert_eq!(lvds.input, VideoInput::Digital { bit_depth: Some(6), interface: DigitalInterface::Undefined });
+}
+
+#[test]
+fn established_and_standard_timings() {
+    let edid = parse("card0-VGA-1.bin");
+    assert_eq!(edid.established_timings.len(), 15);
+    assert!(edid.established_timings.contains(&Mode::new(1152, 870, 75)));
+    assert_eq!(edid.standard_timings[0], Mode::new(1680, 1050, 60));
+    assert!(edid.standard_timings.contains(&Mode::new(1280, 1024, 60)));
+
+    let acer = parse("card0-DP-3.bin");
+    assert!(acer.standard_timings.contains(&Mode::new(1920, 1080, 60)));
+}
+
+#[test]
+fn detailed_timings_and_descriptors() {
+    let edid = parse("card0-DP-3.bin");
+    let t = edid.preferred_timing().unwrap();
+    assert_eq!((t.h_active, t.v_active), (1920, 1080));
+    assert_eq!(t.pixel_clock_khz, 148_500);
+    assert_eq!((t.h_front_porch, t.h_sync_width, t.v_front_porch, t.v_sync_width), (88, 44, 4, 5));
+    assert_eq!((t.h_image_mm, t.v_image_mm), (509, 286));
+    assert!((t.refresh_hz() - 60.0).abs() < 0.01);
+
+    let range = edid.range_limits().unwrap();
+    assert_eq!((range.min_v_hz, range.max_v_hz, range.min_h_khz, range.max_h_khz), (55, 75, 30, 80));
+    assert_eq!(range.max_pixel_clock_mhz, Some(180));
+
+    // Laptop panel: two detailed timings (normal and low refresh), manufacturer descriptors
+    let lvds = parse("card0-LVDS-1.bin");
+    assert_eq!(lvds.detailed_timings.len(), 2);
+    assert!(lvds.detailed_timings[1].refresh_hz() < lvds.detailed_timings[0].refresh_hz());
+    assert!(lvds.descriptors.iter().all(|d| matches!(d, Descriptor::Manufacturer { .. })));
+    assert_eq!(lvds.name(), None);
+
+    let edp = parse("card0-eDP-1.bin");
+    assert!(edp.descriptors.contains(&Descriptor::Dummy));
+}
+
+#[test]
+fn cea_video_and_hdmi_blocks() {
+    let edid = parse("card0-DP-3.bin");
+    let cea = edid.cea_extensions().next().unwrap();
+    assert_eq!(cea.revision, 3);
+    assert!(cea.underscan && cea.basic_audio && cea.ycbcr444 && cea.ycbcr422);
+    assert_eq!(cea.native_dtds, 2);
+
+    let vics: Vec<u8> = cea.video_descriptors().iter().map(|v| v.vic).collect();
+    assert_eq!(vics, vec![1, 2, 4, 17, 19, 5, 20, 31, 16]);
+    let native: Vec<u8> = cea.video_descriptors().iter().filter(|v| v.native).map(|v| v.vic).collect();
+    assert_eq!(native, vec![31, 16]);
+    assert_eq!(cea.video_descriptors()[8].mode(), Some(Mode::new(1920, 1080, 60)));
+
+    let hdmi = edid.hdmi().unwrap();
+    assert_eq!(hdmi.physical_address, (1, 0, 0, 0));
+    assert_eq!(hdmi.max_tmds_mhz, None);
+    assert_eq!(cea.detailed_timings.len(), 1);
+    assert_eq!(edid.all_detailed_timings().len(), 2);
+}
+
+#[test]
+fn undecoded_blocks_stay_raw() {
+    let edid = parse("card0-DP-3.bin");
+    assert!(edid.cea_data_blocks().all(|b| matches!(b, DataBlock::Video(_) | DataBlock::Hdmi(_))));
+
+    // Extension tags other than CTA-861 (here DisplayID, 0x70) are kept as bytes
+    let mut bytes = fixture("card0-DP-3.bin");
+    bytes[128] = 0x70;
+    let edid = Edid::parse(&bytes).unwrap();
+    assert!(matches!(&edid.extensions[0], Extension::Unknown { tag: 0x70, data } if data.len() == 128));
+    assert!(edid.hdmi().is_none());
+}
+
+#[test]
+fn malformed_extension_does_not_panic() {
+    // Every byte of the extension set to 0x00 and 0xFF in turn
+    let data = fixture("card0-DP-3.bin");
+    for i in 128..data.len() {
+        for byte in [0x00, 0xFF] {
+            let mut broken = data.clone();
+            broken[i] = byte;
+            let _ = Edid::parse(&broken);
+        }
+    }
+}
+
+#[test]
+fn hash_uses_base_block_only() {
+    let bytes = fixture("card0-DP-3.bin");
+    assert_eq!(hash_short(&bytes), hash_short(&bytes[..128]));
+    assert_ne!(hash_short(&bytes), hash_short(&fixture("card0-VGA-1.bin")));
+    assert_eq!(hash_short(&bytes).len(), 8);
+}
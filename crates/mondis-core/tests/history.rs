@@ -0,0 +1,126 @@
+mod common;
+
+use common::display;
+use mondis_core::config::config_dir;
+use mondis_core::history::{load_history, update_history, Entry, History, Step, MAX_STEPS};
+use mondis_core::model::DisplayInfo;
+use mondis_core::transaction::{Setting, Value};
+use std::fs;
+
+fn step(time: u64, changes: &[(u8, u16, u16)]) -> Step {
+    Step {
+        time,
+        entries: changes
+            .iter()
+            .map(|&(code, old, new)| Entry {
+                monitor_key: "edid-abc".into(),
+                monitor: "DELL U2720Q (DP-2)".into(),
+                setting: Setting::Vcp { bus: 5, code },
+                old: Value::Raw(old),
+                new: Value::Raw(new),
+            })
+            .collect(),
+    }
+}
+
+fn vcp(code: u8, value: u16) -> (Setting, Value) {
+    (Setting::Vcp { bus: 5, code }, Value::Raw(value))
+}
+
+fn connected() -> Vec<DisplayInfo> {
+    vec![display(5, Some("abc"))]
+}
+
+#[test]
+fn undo_redo_walk_the_steps() {
+    let displays = connected();
+    let mut history = History::default();
+    assert!(history.undo(&displays).is_none());
+    history.push(step(100, &[(0x10, 50, 60)]));
+    history.push(step(200, &[(0x10, 60, 70), (0x14, 0x05, 0x0B)]));
+
+    assert_eq!(history.undo(&displays), Some(vec![vcp(0x14, 0x05), vcp(0x10, 60)]));
+    assert!(history.can_redo());
+    assert_eq!(history.redo(&displays), Some(vec![vcp(0x14, 0x0B), vcp(0x10, 70)]));
+    assert!(history.redo(&displays).is_none());
+
+    // A new step after an undo drops the undone one
+    history.undo(&displays);
+    history.push(step(300, &[(0x12, 40, 45)]));
+    assert_eq!(history.steps.len(), 2);
+    assert!(!history.can_redo());
+}
+
+#[test]
+fn goes_back_to_state_at_time() {
+    let mut history = History::default();
+    history.push(step(100, &[(0x10, 50, 60)]));
+    history.push(step(200, &[(0x10, 60, 70)]));
+    history.push(step(300, &[(0x12, 40, 45), (0x10, 70, 80)]));
+
+    // State at 150: only the first step in effect, 0x10 gets the old value of the earliest undone step
+    let displays = connected();
+    let cursor = history.cursor_at(150);
+    assert_eq!(cursor, 1);
+    assert_eq!(history.go_to(cursor, &displays), vec![vcp(0x10, 60), vcp(0x12, 40)]);
+    assert_eq!(history.go_to(history.cursor_at(0), &displays), vec![vcp(0x10, 50)]);
+    assert_eq!(history.go_to(history.cursor_at(u64::MAX), &displays), vec![vcp(0x10, 80), vcp(0x12, 45)]);
+}
+
+#[test]
+fn replays_follow_the_monitor_and_skip_absent_ones() {
+    let mut history = History::default();
+    history.push(step(100, &[(0x10, 50, 60)]));
+    let mut xrandr = step(200, &[]);
+    xrandr.entries.push(Entry {
+        monitor_key: "edid-abc".into(),
+        monitor: "DELL U2720Q (DP-2)".into(),
+        setting: Setting::XrandrBrightness { output: "DP-2".into() },
+        old: Value::Percent(100),
+        new: Value::Percent(80),
+    });
+    history.push(xrandr);
+
+    // The monitor moved to bus 7 and output DP-3
+    let moved = vec![DisplayInfo { xrandr_output: Some("DP-3".into()), ..display(7, Some("abc")) }];
+    assert_eq!(
+        history.go_to(0, &moved),
+        vec![(Setting::Vcp { bus: 7, code: 0x10 }, Value::Raw(50)), (Setting::XrandrBrightness { output: "DP-3".into() }, Value::Percent(100))]
+    );
+
+    // Another monitor on the old bus gets nothing
+    assert!(history.go_to(2, &[display(5, Some("def"))]).is_empty());
+    assert_eq!(history.cursor, 2);
+}
+
+#[test]
+fn keeps_the_newest_steps_and_round_trips() {
+    let mut history = History::default();
+    history.push(step(1, &[]));
+    assert!(history.steps.is_empty());
+    for t in 0..MAX_STEPS as u64 + 5 {
+        history.push(step(t, &[(0x10, 0, t as u16)]));
+    }
+    assert_eq!(history.steps.len(), MAX_STEPS);
+    assert_eq!(history.cursor, MAX_STEPS);
+    assert_eq!(history.steps[0].time, 5);
+
+    let json = serde_json::to_string(&history).unwrap();
+    assert_eq!(serde_json::from_str::<History>(&json).unwrap(), history);
+}
+
+#[test]
+fn invalid_file_is_reported_and_kept() {
+    let home = std::env::temp_dir().join(format!("mondis-history-test-{}", std::process::id()));
+    std::env::set_var("HOME", &home);
+    let path = config_dir().unwrap().join("history.json");
+
+    update_history(|h| h.push(step(100, &[(0x10, 50, 60)]))).unwrap();
+    assert_eq!(load_history().unwrap().steps.len(), 1);
+
+    fs::write(&path, "{ not json").unwrap();
+    assert!(load_history().is_err());
+    assert!(update_history(|h| h.push(step(200, &[(0x10, 60, 70)]))).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+    fs::remove_dir_all(&home).unwrap();
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use mondis_core::capabilities::Capabilities;
+use mondis_core::hotkeys::{HotkeyAction, HotkeyBinding, HotkeyTarget};
+use mondis_core::inputs::{input_label, resolve_input, standard_name};
+use mondis_core::settings::UiSettings;
+
+use common::display;
+
+#[test]
+fn parses_capabilities_string() {
+    let caps = Capabilities::parse(
+        "(prot(monitor)type(LCD)model(U2720Q)cmds(01 02 03 07 0C E3 F3)vcp(02 04 10 12 14(01 05 08 0B) 60(0F 11 12 1B) D6(01 04 05) DC(00 03 05))mccs_ver(2.1))",
+    );
+    assert_eq!(caps.model.as_deref(), Some("U2720Q"));
+    assert_eq!(caps.mccs_version.as_deref(), Some("2.1"));
+    assert!(caps.supports(0x10) && caps.supports(0xDC));
+    assert!(caps.values(0x10).is_empty());
+    assert_eq!(caps.values(0x14), &[0x01, 0x05, 0x08, 0x0B]);
+    assert_eq!(caps.values(0x60), &[0x0F, 0x11, 0x12, 0x1B]);
+
+    // No spaces between values, missing outer parentheses
+    let caps = Capabilities::parse("vcp(1012 60(0F1112))");
+    assert_eq!(caps.vcp.keys().copied().collect::<Vec<_>>(), vec![0x10, 0x12, 0x60]);
+    assert_eq!(caps.values(0x60), &[0x0F, 0x11, 0x12]);
+
+    assert_eq!(Capabilities::parse("garbage"), Capabilities::default());
+}
+
+#[test]
+fn resolves_input_names_and_labels() {
+    let (laptop, other) = (display(5, Some("0a0b0c0d")), display(6, Some("01020304")));
+    let mut settings = UiSettings::default();
+    settings.monitor_mut(&laptop).input_labels.insert(0x0F, "Ноутбук".to_string());
+
+    assert_eq!(standard_name(0x11), Some("HDMI-1"));
+    assert_eq!(resolve_input(&settings, &laptop, "hdmi1"), Some(0x11));
+    assert_eq!(resolve_input(&settings, &laptop, "DisplayPort-1"), Some(0x0F));
+    assert_eq!(resolve_input(&settings, &laptop, "usb-c"), Some(0x1B));
+    assert_eq!(resolve_input(&settings, &laptop, "0x12"), Some(0x12));
+    assert_eq!(resolve_input(&settings, &laptop, "17"), Some(0x11));
+    assert_eq!(resolve_input(&settings, &laptop, "ноутбук"), Some(0x0F));
+    assert_eq!(resolve_input(&settings, &other, "Ноутбук"), None);
+
+    // Labels follow the monitor, not the bus
+    assert_eq!(input_label(&settings, &display(7, Some("0a0b0c0d")), 0x0F), "Ноутбук");
+    assert_eq!(input_label(&settings, &other, 0x0F), "DP-1");
+    assert_eq!(input_label(&settings, &other, 0x42), "Вход 0x42");
+}
+
+#[test]
+fn switch_input_binding_round_trips() {
+    let json = r#"{ "keys": "Ctrl+Alt+1", "action": "switch_input", "input": "HDMI-1", "target": "all" }"#;
+    let binding: HotkeyBinding = serde_json::from_str(json).unwrap();
+    assert_eq!(binding, HotkeyBinding::switch_input("Ctrl+Alt+1", "HDMI-1", HotkeyTarget::All));
+    assert_eq!(binding.description(), "Вход HDMI-1 (все мониторы)");
+
+    // Other actions keep their old form
+    let plain = serde_json::to_string(&HotkeyBinding::new("Ctrl+Alt+N", HotkeyAction::ToggleNightMode, HotkeyTarget::All)).unwrap();
+    assert!(!plain.contains("input"));
+}
@@ -0,0 +1,57 @@
+use mondis_core::pci::{short_name, GpuInfo, PciIds};
+
+const PCI_IDS: &str = "\
+# Sample in pci.ids format
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+\t73bf  Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]
+\t\t1da2 e438  Radeon RX 6800 XT Nitro+
+1043  ASUSTeK Computer Inc.
+10de  NVIDIA Corporation
+\t2504  GA106 [GeForce RTX 3060 Lite Hash Rate]
+\t\t1043 881d  GA106 [GeForce RTX 3060 Dual OC]
+8086  Intel Corporation
+\t56a0  DG2 [Arc A770]
+1da2  Sapphire Technology Limited
+
+C 03  Display controller
+\t00  VGA compatible controller
+";
+
+#[test]
+fn parses_vendors_devices_and_subsystems() {
+    let ids = PciIds::parse(PCI_IDS);
+    assert_eq!(ids.vendor_name(0x10de), Some("NVIDIA Corporation"));
+    assert_eq!(ids.device_name(0x10de, 0x2504), Some("GA106 [GeForce RTX 3060 Lite Hash Rate]"));
+    assert_eq!(ids.subsystem_name(0x10de, 0x2504, 0x1043, 0x881d), Some("GA106 [GeForce RTX 3060 Dual OC]"));
+    assert_eq!(ids.device_name(0x8086, 0x56a0), Some("DG2 [Arc A770]"));
+    // Class section is not read as vendors
+    assert!(ids.vendor_name(0x0003).is_none());
+}
+
+#[test]
+fn short_names() {
+    assert_eq!(short_name("NVIDIA Corporation"), "NVIDIA");
+    assert_eq!(short_name("Advanced Micro Devices, Inc. [AMD/ATI]"), "AMD/ATI");
+    assert_eq!(short_name("ASUSTeK Computer Inc."), "ASUSTeK Computer");
+    assert_eq!(short_name("Sapphire Technology Limited"), "Sapphire Technology");
+}
+
+#[test]
+fn gpu_display_name() {
+    let ids = PciIds::parse(PCI_IDS);
+    let mut gpu = GpuInfo {
+        card: "card1".into(),
+        slot: "0000:03:00.0".into(),
+        vendor_id: 0x1002,
+        device_id: 0x73bf,
+        subsystem_vendor_id: Some(0x1da2),
+        subsystem_device_id: Some(0xe438),
+        ..GpuInfo::default()
+    };
+    gpu.resolve_names(&ids);
+    assert_eq!(gpu.display_name(), "AMD/ATI Radeon RX 6800/6800 XT / 6900 XT (Sapphire Technology)");
+    assert_eq!(gpu.subsystem.as_deref(), Some("Radeon RX 6800 XT Nitro+"));
+
+    let unknown = GpuInfo { vendor_id: 0x1234, device_id: 0x1111, ..GpuInfo::default() };
+    assert_eq!(unknown.display_name(), "1234 1111");
+}
@@ -0,0 +1,24 @@
+use mondis_core::edid::Edid;
+use mondis_core::pnp::{self, PNP_IDS};
+
+#[test]
+fn table_is_sorted_and_unique() {
+    assert!(PNP_IDS.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(PNP_IDS.iter().all(|(code, name)| code.len() == 3 && !name.is_empty()));
+}
+
+#[test]
+fn resolves_common_vendors() {
+    assert_eq!(pnp::vendor_name("GSM").as_deref(), Some("LG Electronics"));
+    assert_eq!(pnp::vendor_name("del").as_deref(), Some("Dell Inc."));
+    assert_eq!(pnp::display_name("SAM"), "Samsung Electric Company");
+    // Unknown codes stay as-is
+    assert_eq!(pnp::display_name("ZZZ"), "ZZZ");
+}
+
+#[test]
+fn edid_manufacturer_name() {
+    let bytes = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/edid/card0-eDP-1.bin")).unwrap();
+    let edid = Edid::parse(&bytes).unwrap();
+    assert_eq!(edid.manufacturer_name(), "Sharp Corporation");
+}
@@ -0,0 +1,15 @@
+use mondis_core::power::PowerMode;
+
+#[test]
+fn power_modes_round_trip() {
+    for mode in PowerMode::ALL {
+        assert_eq!(PowerMode::from_vcp(mode.vcp_value()), Some(mode));
+        assert_eq!(PowerMode::parse(mode.name()), Some(mode));
+    }
+    // Hard off (power button) and a junk high byte
+    assert_eq!(PowerMode::from_vcp(0x05), Some(PowerMode::Off));
+    assert_eq!(PowerMode::from_vcp(0x0301), Some(PowerMode::On));
+    assert_eq!(PowerMode::from_vcp(0x00), None);
+    assert_eq!(PowerMode::parse(" OFF "), Some(PowerMode::Off));
+    assert_eq!(PowerMode::parse("sleep"), None);
+}
@@ -0,0 +1,64 @@
+use mondis_core::quirks::QuirkDb;
+
+const SAMPLE: &str = r#"{
+  "quirks": [
+    { "manufacturer": "ABC", "hide_vcp": ["0x62"], "timing": { "reply_delay_ms": 60 } },
+    { "manufacturer": "ABC", "product": "0x1234", "read_only_vcp": [18], "vcp_max": [{ "code": "0x10", "max": 100 }] },
+    { "manufacturer": "ABC", "product": 4660, "firmware": "1.2", "hide_vcp": ["0x10"], "note": "brightness writes ignored on 1.2" },
+    { "manufacturer": "ABC", "timing": { "reply_delay_ms": 90, "retries": 5 } }
+  ]
+}"#;
+
+#[test]
+fn builtin_quirks_parse() {
+    let db = QuirkDb::builtin();
+    assert!(!db.entries.is_empty());
+    assert!(db.lookup("gsm", 0, None).timing.reply_delay_ms.is_some());
+}
+
+#[test]
+fn merges_matching_entries() {
+    let db = QuirkDb::parse(SAMPLE).unwrap();
+
+    let other_product = db.lookup("ABC", 0x9999, None);
+    assert!(other_product.is_hidden(0x62));
+    assert!(!other_product.is_read_only(0x12));
+    // Later entry of the same specificity wins
+    assert_eq!(other_product.timing.reply_delay_ms, Some(90));
+    assert_eq!(other_product.timing.retries, Some(5));
+
+    let product = db.lookup("abc", 0x1234, None);
+    assert!(product.is_read_only(0x12));
+    assert_eq!(product.max_for(0x10), Some(100));
+    // Firmware-specific entry needs a known firmware level
+    assert!(!product.is_hidden(0x10));
+
+    let firmware = db.lookup("ABC", 0x1234, Some("01.02"));
+    assert!(firmware.is_hidden(0x10));
+    assert_eq!(firmware.notes, vec!["brightness writes ignored on 1.2".to_string()]);
+    assert!(!db.lookup("ABC", 0x1234, Some("1.3")).is_hidden(0x10));
+
+    assert_eq!(db.lookup("XYZ", 0x1234, None), Default::default());
+}
+
+#[test]
+fn rejects_invalid_codes() {
+    assert!(QuirkDb::parse(r#"{ "quirks": [{ "manufacturer": "ABC", "hide_vcp": ["0x1FF"] }] }"#).is_err());
+    assert!(QuirkDb::parse(r#"{ "quirks": [{ "manufacturer": "ABC", "product": "oops" }] }"#).is_err());
+}
+
+#[test]
+fn audio_input_from_the_most_specific_entry() {
+    let db = QuirkDb::parse(
+        r#"{ "quirks": [
+            { "manufacturer": "ABC", "audio_input": { "code": "0xE4" } },
+            { "manufacturer": "ABC", "product": 1, "audio_input": { "code": "0xE5", "inputs": [{ "value": "0x01", "name": "Line In" }] } }
+        ] }"#,
+    )
+    .unwrap();
+    let generic = db.lookup("ABC", 2, None).audio_input.unwrap();
+    assert_eq!((generic.code, generic.inputs.len()), (0xE4, 0));
+    let product = db.lookup("ABC", 1, None).audio_input.unwrap();
+    assert_eq!(product.code, 0xE5);
+    assert_eq!((product.inputs[0].value, product.inputs[0].name.as_str()), (1, "Line In"));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use mondis_core::settings::{monitor_key, parse_settings, ControlMethod, UiSettings, SETTINGS_VERSION};
+use mondis_core::transaction::{Category, ConfirmPolicy};
+use std::collections::HashMap;
+
+use common::display;
+
+#[test]
+fn migrates_v1_bus_maps_to_monitors() {
+    let data = r#"{
+        "control_prefs": { "5": "xrandr", "9": "ddc" },
+        "last_values_ddc": { "5": 40 },
+        "last_values_xrandr": { "5": 70 },
+        "last_volume": { "5": 30 },
+        "last_muted": { "5": true },
+        "input_labels": { "5": { "15": "Ноутбук" } },
+        "color_calibrations": { "5": { "20": 5, "22": 90 } },
+        "brightness_step": 5,
+        "confirm_policies": { "color": "auto" }
+    }"#;
+    let main = display(5, Some("0a0b0c0d"));
+    let (settings, migrated) = parse_settings(data, std::slice::from_ref(&main)).unwrap();
+    assert!(migrated);
+    assert_eq!(settings.version, SETTINGS_VERSION);
+    assert_eq!(settings.brightness_step, 5);
+    assert_eq!(settings.confirm_policies.get(&Category::Color), Some(&ConfirmPolicy::Auto));
+
+    let monitor = settings.monitor(&main).unwrap();
+    assert_eq!(monitor.name, "Monitor 5");
+    assert_eq!(monitor.control, Some(ControlMethod::Xrandr));
+    assert_eq!((monitor.brightness_ddc, monitor.brightness_xrandr), (Some(40), Some(70)));
+    assert_eq!((monitor.volume, monitor.muted), (Some(30), Some(true)));
+    assert_eq!(monitor.input_labels.get(&0x0F).map(String::as_str), Some("Ноутбук"));
+    assert_eq!(monitor.color_calibration.get(&0x16), Some(&90));
+
+    // A bus not in the displays cache keeps a bus key
+    assert_eq!(settings.monitors["i2c-9"].control, Some(ControlMethod::Ddc));
+
+    // The same monitor on another bus finds its settings
+    assert_eq!(monitor_key(&display(7, Some("0a0b0c0d"))), monitor_key(&main));
+}
+
+#[test]
+fn current_version_round_trips() {
+    let main = display(5, Some("0a0b0c0d"));
+    let mut settings = UiSettings::default();
+    settings.monitor_mut(&main).profile_brightness = Some(60);
+    settings.import_brightness_profile(&HashMap::from([(5, 80), (6, 20)]), std::slice::from_ref(&main));
+    assert_eq!(settings.monitor(&main).unwrap().profile_brightness, Some(60));
+    assert_eq!(settings.monitors["i2c-6"].profile_brightness, Some(20));
+
+    let json = serde_json::to_string(&settings).unwrap();
+    assert!(!json.contains("input_labels"));
+    let (parsed, migrated) = parse_settings(&json, &[]).unwrap();
+    assert!(!migrated);
+    assert_eq!(parsed.monitors, settings.monitors);
+}
+
+#[test]
+fn rejects_unreadable_and_newer_files() {
+    assert!(parse_settings("{ \"brightness_step\": ", &[]).is_err());
+    assert!(parse_settings("[]", &[]).is_err());
+    let newer = format!("{{ \"version\": {} }}", SETTINGS_VERSION + 1);
+    let error = parse_settings(&newer, &[]).unwrap_err();
+    assert!(format!("{error:#}").contains("newer"));
+    assert!(parse_settings(r#"{ "version": 2, "brightness_step": "high" }"#, &[]).is_err());
+}
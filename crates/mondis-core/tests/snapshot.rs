@@ -0,0 +1,57 @@
+use mondis_core::quirks::QuirkDb;
+use mondis_core::snapshot::{is_captured, Snapshot, XrandrState};
+use std::collections::BTreeMap;
+
+fn sample() -> Snapshot {
+    Snapshot {
+        manufacturer: "GSM".into(),
+        product_code: 0x5B7F,
+        model: Some("LG ULTRAFINE".into()),
+        serial: Some("0001A2B3".into()),
+        created: 1_700_000_000,
+        vcp: BTreeMap::from([(0x10, 70), (0x14, 0x0B), (0x16, 90), (0xE2, 3)]),
+        xrandr: Some(XrandrState { brightness: 100, gamma: (1.0, 0.9, 0.8) }),
+    }
+}
+
+#[test]
+fn restores_modes_before_continuous_controls() {
+    // 0xE2 is not in the catalog and has no declared values: continuous
+    assert_eq!(sample().restore_order(), vec![(0x14, 0x0B), (0x10, 70), (0x16, 90), (0xE2, 3)]);
+}
+
+#[test]
+fn matches_same_model_and_round_trips() {
+    let snapshot = sample();
+    assert!(snapshot.matches("gsm", 0x5B7F));
+    assert!(!snapshot.matches("GSM", 0x5B80));
+    assert_eq!(snapshot.file_name(), "LG_ULTRAFINE-0001A2B3-1700000000.json");
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+}
+
+#[test]
+fn captures_state_codes_only() {
+    let none = Default::default();
+    assert!(is_captured(0x10, &[], &none));
+    assert!(is_captured(0x14, &[0x05, 0x0B], &none));
+    assert!(!is_captured(0x60, &[0x0F, 0x11], &none));
+    assert!(!is_captured(0xD6, &[], &none));
+    assert!(!is_captured(0x04, &[], &none));
+    assert!(!is_captured(0xC9, &[], &none));
+    assert!(!is_captured(0x1E, &[0x00, 0x01], &none));
+    assert!(!is_captured(0x1F, &[0x00, 0x01], &none));
+    assert!(!is_captured(0xE2, &[], &none));
+}
+
+#[test]
+fn quirks_opt_vendor_codes_in_and_hide_codes() {
+    let db = QuirkDb::parse(r#"{ "quirks": [{ "manufacturer": "GSM", "snapshot_vcp": ["0xE2", 30], "hide_vcp": ["0x16"] }] }"#).unwrap();
+    let quirks = db.lookup("GSM", 0x5B7F, None);
+    assert!(is_captured(0xE2, &[], &quirks));
+    assert!(is_captured(0x1E, &[0x00, 0x01], &quirks));
+    assert!(!is_captured(0xE3, &[], &quirks));
+    assert!(!is_captured(0x16, &[], &quirks));
+    assert!(is_captured(0x10, &[], &quirks));
+}
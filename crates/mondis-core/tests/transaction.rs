@@ -0,0 +1,126 @@
+use mondis_core::settings::UiSettings;
+use mondis_core::transaction::{Category, ConfirmPolicy, Setting, Transaction, Value, AUTO_CONFIRM_DELAY};
+use std::time::{Duration, Instant};
+
+fn vcp(bus: u8, code: u8) -> Setting {
+    Setting::Vcp { bus, code }
+}
+
+#[test]
+fn keeps_first_original_and_restarts_countdown() {
+    let start = Instant::now();
+    let mut tx = Transaction::new(Duration::from_secs(20));
+    assert!(tx.record(vcp(3, 0x16), || Some(Value::Raw(50)), Some(Value::Raw(60)), start));
+    // The original is not asked again for a setting already in the transaction
+    assert!(tx.record(vcp(3, 0x16), || panic!("original read twice"), Some(Value::Raw(70)), start + Duration::from_secs(15)));
+    assert_eq!(tx.original(&vcp(3, 0x16)), Some(&Value::Raw(50)));
+    assert_eq!(tx.remaining(start + Duration::from_secs(25)), Some(Duration::from_secs(10)));
+    assert!(tx.expire(start + Duration::from_secs(34)).is_empty());
+    assert_eq!(tx.expire(start + Duration::from_secs(35)).rolled_back, vec![(vcp(3, 0x16), Value::Raw(50))]);
+    assert!(!tx.is_pending());
+}
+
+#[test]
+fn auto_confirms_brightness_and_rolls_back_the_rest() {
+    let start = Instant::now();
+    let mut tx = Transaction::new(Duration::from_secs(20));
+    tx.record(vcp(3, 0x10), || Some(Value::Raw(50)), Some(Value::Raw(60)), start);
+    assert!(tx.is_pending());
+    assert!(!tx.needs_confirmation());
+    assert_eq!(tx.remaining(start), None);
+
+    tx.record(vcp(3, 0x60), || Some(Value::Raw(0x0F)), Some(Value::Raw(0x11)), start);
+    assert!(tx.needs_confirmation());
+    let resolved = tx.expire(start + AUTO_CONFIRM_DELAY);
+    assert_eq!(resolved.committed.len(), 1);
+    assert_eq!(resolved.committed[0].0, vcp(3, 0x10));
+    assert!(resolved.rolled_back.is_empty());
+    assert!(tx.contains(&vcp(3, 0x60)));
+
+    // Leaving the panel resolves what is left right away
+    tx.record(vcp(3, 0x10), || Some(Value::Raw(60)), Some(Value::Raw(65)), start);
+    let resolved = tx.settle();
+    assert_eq!(resolved.committed[0].0, vcp(3, 0x10));
+    assert_eq!(resolved.rolled_back, vec![(vcp(3, 0x60), Value::Raw(0x0F))]);
+    assert!(!tx.is_pending());
+}
+
+#[test]
+fn policies_from_settings_but_power_always_confirms() {
+    let settings = UiSettings {
+        confirm_timeout_s: 45,
+        confirm_policies: [(Category::Brightness, ConfirmPolicy::Confirm), (Category::Power, ConfirmPolicy::Auto)].into(),
+        ..UiSettings::default()
+    };
+    let tx = Transaction::from_settings(&settings);
+    assert_eq!(tx.timeout(), Duration::from_secs(45));
+    assert_eq!(tx.policy(Category::Brightness), ConfirmPolicy::Confirm);
+    assert_eq!(tx.policy(Category::Power), ConfirmPolicy::Confirm);
+    assert_eq!(tx.policy(Category::Audio), ConfirmPolicy::Auto);
+    assert_eq!(tx.policy(Category::Color), ConfirmPolicy::Confirm);
+
+    assert_eq!(vcp(3, 0x12).category(), Category::Brightness);
+    assert_eq!(vcp(3, 0x18).category(), Category::Color);
+    assert_eq!(vcp(3, 0xD6).category(), Category::Power);
+    assert_eq!(Setting::XrandrGamma { output: "DP-1".into() }.category(), Category::Color);
+
+    let json = r#"{"confirm_policies": {"color": "auto"}}"#;
+    let parsed: UiSettings = serde_json::from_str(json).unwrap();
+    assert_eq!(parsed.confirm_timeout_s, 20);
+    assert_eq!(Transaction::from_settings(&parsed).policy(Category::Color), ConfirmPolicy::Auto);
+}
+
+#[test]
+fn rolls_back_all_monitors_modes_first() {
+    let now = Instant::now();
+    let mut tx = Transaction::default();
+    tx.record(vcp(4, 0x16), || Some(Value::Raw(90)), None, now);
+    tx.record(vcp(4, 0x14), || Some(Value::Raw(0x05)), None, now);
+    tx.record(Setting::XrandrBrightness { output: "HDMI-1".into() }, || Some(Value::Percent(100)), Some(Value::Percent(40)), now);
+    tx.record(vcp(3, 0x10), || Some(Value::Raw(50)), Some(Value::Raw(80)), now);
+
+    assert_eq!(
+        tx.rollback(),
+        vec![
+            (vcp(4, 0x14), Value::Raw(0x05)),
+            (vcp(3, 0x10), Value::Raw(50)),
+            (vcp(4, 0x16), Value::Raw(90)),
+            (Setting::XrandrBrightness { output: "HDMI-1".into() }, Value::Percent(100)),
+        ]
+    );
+    assert!(!tx.is_pending());
+    assert_eq!(tx.remaining(now), None);
+}
+
+#[test]
+fn drops_changes_back_to_original_and_untracked_settings() {
+    let now = Instant::now();
+    let mut tx = Transaction::default();
+    assert!(!tx.record(vcp(3, 0x12), || None, Some(Value::Raw(40)), now));
+    assert!(!tx.is_pending());
+
+    tx.record(vcp(3, 0x12), || Some(Value::Raw(50)), Some(Value::Raw(40)), now);
+    assert!(!tx.record(vcp(3, 0x12), || None, Some(Value::Raw(50)), now));
+    assert!(!tx.is_pending());
+    assert_eq!(tx.remaining(now), None);
+}
+
+#[test]
+fn commit_returns_changes_and_forget_clears_deadline() {
+    let now = Instant::now();
+    let mut tx = Transaction::default();
+    tx.record(vcp(3, 0x10), || Some(Value::Raw(50)), Some(Value::Raw(60)), now);
+    tx.record(vcp(5, 0x12), || Some(Value::Raw(30)), None, now);
+
+    tx.forget(|s| matches!(s, Setting::Vcp { bus: 5, .. }));
+    assert!(tx.is_pending());
+    let committed = tx.commit();
+    assert_eq!(committed.len(), 1);
+    assert_eq!(committed[0].0, vcp(3, 0x10));
+    assert_eq!(committed[0].1.current, Some(Value::Raw(60)));
+    assert!(tx.rollback().is_empty());
+
+    tx.record(vcp(5, 0x12), || Some(Value::Raw(30)), None, now);
+    tx.forget(|_| true);
+    assert_eq!(tx.remaining(now), None);
+}
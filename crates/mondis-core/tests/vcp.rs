@@ -0,0 +1,28 @@
+use mondis_core::vcp::{kind, name, VcpKind};
+
+#[test]
+fn known_and_unknown_codes() {
+    assert_eq!(kind(0x87, &[]), VcpKind::Continuous);
+    assert_eq!(kind(0x60, &[]), VcpKind::NonContinuous);
+    assert_eq!(kind(0xC9, &[]), VcpKind::ReadOnly);
+    assert_eq!(kind(0x04, &[]), VcpKind::WriteOnly);
+    // Vendor codes: by whether the capabilities string lists values
+    assert_eq!(kind(0xE2, &[]), VcpKind::Continuous);
+    assert_eq!(kind(0xE2, &[0x01, 0x02]), VcpKind::NonContinuous);
+
+    assert_eq!(name(0x87), "Резкость");
+    assert_eq!(name(0xF0), "Код производителя 0xF0");
+    assert_eq!(name(0x44), "VCP 0x44");
+}
+
+#[test]
+fn reset_scopes() {
+    use mondis_core::vcp::{reset_scope, FACTORY_RESETS};
+    for code in FACTORY_RESETS {
+        assert_eq!(kind(code, &[]), VcpKind::WriteOnly);
+    }
+    assert_eq!(reset_scope(0x04), None);
+    assert_eq!(reset_scope(0x05), Some(&[0x10, 0x12][..]));
+    assert!(reset_scope(0x08).unwrap().contains(&0x16));
+    assert!(!reset_scope(0x06).unwrap().contains(&0x10));
+}
@@ -0,0 +1,123 @@
+//! I2C adapter enumeration via sysfs: which /dev/i2c-N are display (DDC) channels
+//! and which DRM connector each one belongs to.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+const I2C_DEVICES: &str = "/sys/bus/i2c/devices";
+const DRM_CLASS: &str = "/sys/class/drm";
+
+// Adapter names of GPU display channels whose connector link is not exposed (e.g. proprietary NVIDIA)
+const DISPLAY_ADAPTER_PREFIXES: &[&str] = &[
+    "i915 gmbus",
+    "DPDDC-",
+    "AMDGPU DM",
+    "NVIDIA i2c adapter",
+    "nvkm-",
+    "Radeon i2c",
+    "radeon",
+    "DP-AUX",
+    "AUX ",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdapterKind {
+    /// Linked from a DRM connector (`ddc` symlink or DP AUX child node).
+    Connector,
+    /// Belongs to a display controller, connector unknown.
+    Display,
+    /// SMBus, sensors, touchpads and the like — never probed.
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub struct I2cAdapter {
+    pub bus: u8,
+    pub name: String,
+    pub connector: Option<String>, // DRM connector, e.g. card0-DP-2
+    pub kind: AdapterKind,
+}
+
+impl I2cAdapter {
+    pub fn is_display(&self) -> bool {
+        self.kind != AdapterKind::Other
+    }
+}
+
+fn bus_number(name: &str) -> Option<u8> {
+    name.strip_prefix("i2c-")?.parse().ok()
+}
+
+/// Bus -> DRM connector, from /sys/class/drm/cardN-*/ddc and cardN-*/i2c-M.
+pub fn connector_map() -> HashMap<u8, String> {
+    let mut map = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(DRM_CLASS) else { return map };
+    for entry in entries.flatten() {
+        let Ok(connector) = entry.file_name().into_string() else { continue };
+        if !connector.starts_with("card") || !connector.contains('-') {
+            continue;
+        }
+        let path = entry.path();
+        if let Some(bus) = std::fs::read_link(path.join("ddc"))
+            .ok()
+            .and_then(|t| t.file_name().and_then(|f| f.to_str()).and_then(bus_number))
+        {
+            map.insert(bus, connector.clone());
+        }
+        // DP AUX channels are registered as children of the connector
+        if let Ok(children) = std::fs::read_dir(&path) {
+            for child in children.flatten() {
+                if let Some(bus) = child.file_name().to_str().and_then(bus_number) {
+                    map.entry(bus).or_insert_with(|| connector.clone());
+                }
+            }
+        }
+    }
+    map
+}
+
+/// Parent of the adapter is a PCI display controller (class 0x03xxxx).
+fn parent_is_display_controller(adapter_dir: &Path) -> bool {
+    std::fs::read_to_string(adapter_dir.join("device").join("class"))
+        .map(|c| c.trim().starts_with("0x03"))
+        .unwrap_or(false)
+}
+
+fn classify(name: &str, adapter_dir: &Path, connector: Option<&String>) -> AdapterKind {
+    if connector.is_some() {
+        AdapterKind::Connector
+    } else if DISPLAY_ADAPTER_PREFIXES.iter().any(|p| name.starts_with(p)) || parent_is_display_controller(adapter_dir) {
+        AdapterKind::Display
+    } else {
+        AdapterKind::Other
+    }
+}
+
+/// Every I2C adapter in /sys/bus/i2c/devices, sorted by bus number.
+pub fn list_adapters() -> Vec<I2cAdapter> {
+    let connectors = connector_map();
+    let mut adapters = Vec::new();
+    let Ok(entries) = std::fs::read_dir(I2C_DEVICES) else { return adapters };
+    for entry in entries.flatten() {
+        let Some(bus) = entry.file_name().to_str().and_then(bus_number) else { continue };
+        let dir = entry.path();
+        let name = std::fs::read_to_string(dir.join("name"))
+            .map(|n| n.trim().to_string())
+            .unwrap_or_default();
+        let connector = connectors.get(&bus);
+        let kind = classify(&name, &dir, connector);
+        adapters.push(I2cAdapter { bus, name, connector: connector.cloned(), kind });
+    }
+    adapters.sort_by_key(|a| a.bus);
+    adapters
+}
+
+/// Adapters worth probing for DDC/EDID.
+pub fn display_adapters() -> Vec<I2cAdapter> {
+    list_adapters().into_iter().filter(I2cAdapter::is_display).collect()
+}
+
+/// DRM connector (e.g. "card0-DP-1") whose DDC channel is /dev/i2c-N.
+pub fn connector_for_bus(bus: u8) -> Option<String> {
+    connector_map().remove(&bus)
+}
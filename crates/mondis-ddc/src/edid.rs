@@ -0,0 +1,94 @@
+//! Full EDID read over I2C (E-DDC): base block, extension blocks and the
+//! 0x30 segment pointer for blocks past the first 256 bytes.
+
+use crate::adapters::connector_for_bus;
+use anyhow::{anyhow, Context, Result};
+use i2cdev::core::{I2CMessage, I2CTransfer};
+use i2cdev::linux::{LinuxI2CBus, LinuxI2CMessage};
+use mondis_core::edid::block_checksum_ok;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+pub const EDID_ADDR: u16 = 0x50;
+pub const SEGMENT_ADDR: u16 = 0x30;
+pub const BLOCK_SIZE: usize = 128;
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const BLOCK_RETRIES: usize = 3;
+
+/// Read one 128-byte block. Blocks 0-1 live in segment 0; block N is at
+/// segment N/2, offset (N%2)*128. The segment pointer is reset by a STOP,
+/// so it is written in the same I2C_RDWR transaction as the read.
+fn read_block(bus: &mut LinuxI2CBus, index: usize) -> Result<[u8; BLOCK_SIZE]> {
+    let segment = [(index / 2) as u8];
+    let offset = [((index % 2) * BLOCK_SIZE) as u8];
+    let mut block = [0u8; BLOCK_SIZE];
+    {
+        let mut msgs = Vec::with_capacity(3);
+        if segment[0] > 0 {
+            msgs.push(LinuxI2CMessage::write(&segment).with_address(SEGMENT_ADDR));
+        }
+        msgs.push(LinuxI2CMessage::write(&offset).with_address(EDID_ADDR));
+        msgs.push(LinuxI2CMessage::read(&mut block).with_address(EDID_ADDR));
+        bus.transfer(&mut msgs)?;
+    }
+    Ok(block)
+}
+
+fn read_block_checked(bus: &mut LinuxI2CBus, index: usize) -> Result<[u8; BLOCK_SIZE]> {
+    let mut last_err = None;
+    for attempt in 0..BLOCK_RETRIES {
+        if attempt > 0 {
+            thread::sleep(Duration::from_millis(20));
+        }
+        match read_block(bus, index) {
+            Ok(block) if block_checksum_ok(&block) => return Ok(block),
+            Ok(_) => last_err = Some(anyhow!("EDID block {} checksum mismatch", index)),
+            Err(e) => last_err = Some(e.context(format!("failed to read EDID block {}", index))),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow!("failed to read EDID block {}", index)))
+}
+
+/// Read the base block and all extension blocks from /dev/i2c-N.
+pub fn read_edid_i2c(bus_num: u8) -> Result<Vec<u8>> {
+    let path = format!("/dev/i2c-{}", bus_num);
+    let mut bus = LinuxI2CBus::new(&path).with_context(|| format!("failed to open {}", path))?;
+
+    let base = read_block_checked(&mut bus, 0)?;
+    if base[..8] != EDID_HEADER {
+        return Err(anyhow!("invalid EDID header on {}: {:02X?}", path, &base[..8]));
+    }
+
+    let extensions = base[126] as usize;
+    let mut edid = Vec::with_capacity(BLOCK_SIZE * (1 + extensions));
+    edid.extend_from_slice(&base);
+    for index in 1..=extensions {
+        let block = read_block_checked(&mut bus, index)?;
+        edid.extend_from_slice(&block);
+    }
+    Ok(edid)
+}
+
+/// EDID as cached by the kernel in /sys/class/drm/<connector>/edid.
+pub fn read_edid_sysfs(connector: &str) -> Result<Vec<u8>> {
+    let path = Path::new("/sys/class/drm").join(connector).join("edid");
+    let edid = std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    if edid.len() < BLOCK_SIZE || edid[..8] != EDID_HEADER {
+        return Err(anyhow!("no valid EDID in {}", path.display()));
+    }
+    Ok(edid)
+}
+
+/// Full EDID for /dev/i2c-N; falls back to the DRM sysfs copy when the I2C read fails.
+pub fn read_edid(bus_num: u8) -> Result<Vec<u8>> {
+    match read_edid_i2c(bus_num) {
+        Ok(edid) => Ok(edid),
+        Err(i2c_err) => {
+            let connector = connector_for_bus(bus_num)
+                .ok_or_else(|| i2c_err.context("no DRM connector for sysfs fallback"))?;
+            read_edid_sysfs(&connector)
+        }
+    }
+}
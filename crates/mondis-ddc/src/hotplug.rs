@@ -0,0 +1,188 @@
+//! Monitor hotplug: kernel uevents (NETLINK_KOBJECT_UEVENT) for DRM connector
+//! changes and i2c-dev adapters appearing or going away. No udevd required.
+
+use crate::adapters::{connector_map, display_adapters};
+use crate::timing::forget_bus;
+use anyhow::{Context, Result};
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// Kernel (not udevd) multicast group
+const KERNEL_UEVENT_GROUP: u32 = 1;
+const UEVENT_BUFFER: usize = 8192;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HotplugEvent {
+    /// DRM hotplug on `card` ("card0"); `connector` is set when the kernel reports which one changed.
+    Drm { card: String, connector: Option<String> },
+    I2cAdded(u8),
+    I2cRemoved(u8),
+}
+
+/// Parse one kernel uevent datagram: "ACTION@DEVPATH\0KEY=VALUE\0...".
+pub fn parse_uevent(msg: &[u8]) -> Option<(String, Vec<(String, String)>)> {
+    let mut parts = msg.split(|&b| b == 0).filter(|p| !p.is_empty());
+    let header = String::from_utf8_lossy(parts.next()?).to_string();
+    // udevd re-broadcasts start with "libudev"; only kernel messages are handled
+    if !header.contains('@') {
+        return None;
+    }
+    let vars = parts
+        .filter_map(|p| {
+            let s = String::from_utf8_lossy(p);
+            s.split_once('=').map(|(k, v)| (k.to_string(), v.to_string()))
+        })
+        .collect();
+    Some((header, vars))
+}
+
+/// Hotplug event from a parsed uevent, if it is one we care about.
+pub fn classify_uevent(vars: &[(String, String)]) -> Option<HotplugEvent> {
+    let get = |key: &str| vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    let action = get("ACTION")?;
+    match get("SUBSYSTEM")? {
+        "drm" if action == "change" && get("HOTPLUG") == Some("1") => {
+            let card = get("DEVNAME")?.rsplit('/').next()?.to_string();
+            let connector = get("CONNECTOR")
+                .and_then(|id| id.parse::<u32>().ok())
+                .and_then(|id| connector_by_id(&card, id));
+            Some(HotplugEvent::Drm { card, connector })
+        }
+        "i2c-dev" => {
+            let bus = get("DEVNAME")?.rsplit('/').next()?.strip_prefix("i2c-")?.parse().ok()?;
+            match action {
+                "add" => Some(HotplugEvent::I2cAdded(bus)),
+                "remove" => Some(HotplugEvent::I2cRemoved(bus)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Connector name ("card0-DP-1") for a DRM connector object id.
+pub fn connector_by_id(card: &str, id: u32) -> Option<String> {
+    let prefix = format!("{}-", card);
+    std::fs::read_dir("/sys/class/drm").ok()?.flatten().find_map(|e| {
+        let name = e.file_name().into_string().ok()?;
+        let conn_id = std::fs::read_to_string(e.path().join("connector_id")).ok()?;
+        (name.starts_with(&prefix) && conn_id.trim().parse() == Ok(id)).then_some(name)
+    })
+}
+
+/// Connector reports a connected sink (/sys/class/drm/<connector>/status).
+pub fn connector_connected(connector: &str) -> bool {
+    std::fs::read_to_string(Path::new("/sys/class/drm").join(connector).join("status"))
+        .map(|s| s.trim() == "connected")
+        .unwrap_or(false)
+}
+
+/// I2C buses to re-probe for an event. `None` means the bus is unknown (e.g. a DRM
+/// connector without a `ddc` link) and everything should be re-detected.
+pub fn affected_buses(event: &HotplugEvent) -> Option<Vec<u8>> {
+    match event {
+        HotplugEvent::I2cAdded(bus) | HotplugEvent::I2cRemoved(bus) => Some(vec![*bus]),
+        HotplugEvent::Drm { card, connector } => {
+            let map = connector_map();
+            let buses: Vec<u8> = match connector {
+                Some(conn) => map.iter().filter(|(_, c)| *c == conn).map(|(b, _)| *b).collect(),
+                None => map.iter().filter(|(_, c)| c.starts_with(&format!("{}-", card))).map(|(b, _)| *b).collect(),
+            };
+            (!buses.is_empty()).then_some(buses)
+        }
+    }
+}
+
+pub struct UeventSocket {
+    fd: OwnedFd,
+}
+
+impl UeventSocket {
+    pub fn open() -> Result<UeventSocket> {
+        let raw = unsafe {
+            libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_KOBJECT_UEVENT)
+        };
+        if raw < 0 {
+            return Err(io::Error::last_os_error()).context("failed to open uevent netlink socket");
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+        let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = KERNEL_UEVENT_GROUP;
+        let rc = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if rc < 0 {
+            return Err(io::Error::last_os_error()).context("failed to bind uevent netlink socket");
+        }
+        Ok(UeventSocket { fd })
+    }
+
+    /// Block until the next uevent; `Ok(None)` for events that are not monitor hotplug.
+    pub fn recv(&self) -> Result<Option<HotplugEvent>> {
+        let mut buf = vec![0u8; UEVENT_BUFFER];
+        let n = unsafe { libc::recv(self.fd.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                return Ok(None);
+            }
+            return Err(err).context("failed to read uevent");
+        }
+        Ok(parse_uevent(&buf[..n as usize]).and_then(|(_, vars)| classify_uevent(&vars)))
+    }
+}
+
+/// Listen for hotplug in a background thread. Bursts (a DRM change usually comes
+/// with several uevents) are coalesced: `on_batch` runs once events stop for `quiet`.
+pub fn spawn_listener<F>(quiet: Duration, on_batch: F) -> Result<()>
+where
+    F: Fn(Vec<HotplugEvent>) + Send + 'static,
+{
+    let socket = UeventSocket::open()?;
+    let (tx, rx) = mpsc::channel::<HotplugEvent>();
+
+    thread::spawn(move || loop {
+        match socket.recv() {
+            Ok(Some(event)) => {
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!("hotplug listener stopped: {e:#}");
+                break;
+            }
+        }
+    });
+
+    thread::spawn(move || {
+        while let Ok(first) = rx.recv() {
+            let mut batch = vec![first];
+            while let Ok(event) = rx.recv_timeout(quiet) {
+                if !batch.contains(&event) {
+                    batch.push(event);
+                }
+            }
+            // Monitors on these buses may have changed: resolve quirks and timing again on next use
+            for event in &batch {
+                match affected_buses(event) {
+                    Some(buses) => buses.into_iter().for_each(forget_bus),
+                    None => display_adapters().iter().for_each(|a| forget_bus(a.bus)),
+                }
+            }
+            on_batch(batch);
+        }
+    });
+    Ok(())
+}
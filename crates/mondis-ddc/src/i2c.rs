@@ -0,0 +1,254 @@
+//! Raw DDC/CI access over /dev/i2c-N (no ddcutil required): framing, reply
+//! validation and retries. Timing and per-bus pacing live in [`crate::timing`].
+
+use crate::timing::{with_bus, BusGuard};
+use anyhow::{anyhow, Context, Result};
+use mondis_core::audio::{is_muted, mute_value, VCP_AUDIO_MUTE, VCP_AUDIO_VOLUME};
+use mondis_core::capabilities::Capabilities;
+use mondis_core::inputs::VCP_INPUT_SOURCE;
+use mondis_core::power::{PowerMode, VCP_POWER_MODE};
+use i2cdev::core::I2CDevice;
+use i2cdev::linux::LinuxI2CDevice;
+use std::thread;
+use thiserror::Error;
+
+pub const DDC_ADDR: u16 = 0x37;
+pub const VCP_BRIGHTNESS: u8 = 0x10;
+
+// Host source address in requests; checksum seeds (destination address) for requests and replies
+const HOST_ADDR: u8 = 0x51;
+const REQUEST_CHECKSUM_SEED: u8 = 0x6E;
+const REPLY_CHECKSUM_SEED: u8 = 0x50;
+
+const GET_VCP_REQUEST: u8 = 0x01;
+const GET_VCP_REPLY: u8 = 0x02;
+const SET_VCP_REQUEST: u8 = 0x03;
+const CAPABILITIES_REQUEST: u8 = 0xF3;
+const CAPABILITIES_REPLY: u8 = 0xE3;
+
+// Capabilities strings are at most a few KiB; guards against monitors that never send the empty terminator
+const CAPABILITIES_MAX_LEN: usize = 8192;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DdcError {
+    /// Null message: the monitor is busy or did not understand the request.
+    #[error("DDC null response")]
+    NullResponse,
+    #[error("VCP 0x{0:02X} is not supported by the monitor")]
+    UnsupportedVcp(u8),
+    #[error("DDC reply checksum mismatch: {0:02X?}")]
+    Checksum(Vec<u8>),
+    #[error("invalid DDC reply: {0}")]
+    InvalidReply(String),
+}
+
+impl DdcError {
+    /// Worth repeating the transaction (the monitor answered, just not usefully).
+    pub fn is_transient(&self) -> bool {
+        !matches!(self, DdcError::UnsupportedVcp(_))
+    }
+}
+
+/// Request frame: [0x51, 0x80 | len, payload..., checksum].
+pub fn encode_request(payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 3);
+    frame.push(HOST_ADDR);
+    frame.push(0x80 | payload.len() as u8);
+    frame.extend_from_slice(payload);
+    let checksum = frame.iter().fold(REQUEST_CHECKSUM_SEED, |acc, &b| acc ^ b);
+    frame.push(checksum);
+    frame
+}
+
+/// Payload of a reply frame [0x6E, 0x80 | len, payload..., checksum]; checks address, length and checksum.
+pub fn decode_reply(frame: &[u8]) -> Result<&[u8], DdcError> {
+    if frame.len() < 3 || frame[0] != 0x6E || frame[1] & 0x80 == 0 {
+        return Err(DdcError::InvalidReply(format!("bad header {:02X?}", &frame[..frame.len().min(3)])));
+    }
+    let len = (frame[1] & 0x7F) as usize;
+    if frame.len() < len + 3 {
+        return Err(DdcError::InvalidReply(format!("length {} exceeds {} read bytes", len, frame.len())));
+    }
+    let checksum = frame[..len + 2].iter().fold(REPLY_CHECKSUM_SEED, |acc, &b| acc ^ b);
+    if checksum != frame[len + 2] {
+        return Err(DdcError::Checksum(frame[..len + 3].to_vec()));
+    }
+    if len == 0 {
+        return Err(DdcError::NullResponse);
+    }
+    Ok(&frame[2..len + 2])
+}
+
+/// (current, max) from a Get VCP Feature reply payload.
+pub fn parse_vcp_reply(payload: &[u8], vcp_code: u8) -> Result<(u16, u16), DdcError> {
+    // [0x02, result, vcp_code, type, max_hi, max_lo, cur_hi, cur_lo]
+    if payload.len() < 8 || payload[0] != GET_VCP_REPLY {
+        return Err(DdcError::InvalidReply(format!("not a VCP reply: {:02X?}", payload)));
+    }
+    match payload[1] {
+        0x00 => {}
+        0x01 => return Err(DdcError::UnsupportedVcp(vcp_code)),
+        rc => return Err(DdcError::InvalidReply(format!("result code 0x{:02X}", rc))),
+    }
+    if payload[2] != vcp_code {
+        return Err(DdcError::InvalidReply(format!(
+            "expected code 0x{:02X}, got 0x{:02X}",
+            vcp_code, payload[2]
+        )));
+    }
+    let max = u16::from_be_bytes([payload[4], payload[5]]);
+    let cur = u16::from_be_bytes([payload[6], payload[7]]);
+    Ok((cur, max))
+}
+
+fn open(bus: u8) -> Result<LinuxI2CDevice> {
+    let path = format!("/dev/i2c-{}", bus);
+    LinuxI2CDevice::new(&path, DDC_ADDR).with_context(|| format!("failed to open {}", path))
+}
+
+/// Run one DDC exchange with retries. `attempt` performs a single write (and read) on an
+/// open device; I/O errors and transient DDC errors are retried with backoff.
+fn transact<T>(
+    bus: u8,
+    what: &str,
+    mut attempt: impl FnMut(&mut LinuxI2CDevice, &BusGuard) -> Result<T>,
+) -> Result<T> {
+    with_bus(bus, |guard| {
+        let mut dev = open(bus)?;
+        let mut last_err = None;
+        for n in 0..=guard.timing.retries {
+            if n > 0 {
+                thread::sleep(guard.timing.backoff(n));
+            }
+            guard.wait_ready();
+            let result = attempt(&mut dev, guard);
+            guard.done();
+            match result {
+                Ok(v) => return Ok(v),
+                Err(e) => {
+                    if e.downcast_ref::<DdcError>().is_some_and(|d| !d.is_transient()) {
+                        return Err(e);
+                    }
+                    tracing::debug!("i2c-{}: {} attempt {} failed: {e:#}", bus, what, n + 1);
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| anyhow!("{} failed", what)).context(format!("{} on /dev/i2c-{}", what, bus)))
+    })
+}
+
+/// Get VCP Feature: returns (current, max).
+pub fn get_vcp(bus: u8, vcp_code: u8) -> Result<(u16, u16)> {
+    let request = encode_request(&[GET_VCP_REQUEST, vcp_code]);
+    transact(bus, "Get VCP", |dev, guard| {
+        dev.write(&request).context("failed to write DDC get request")?;
+        thread::sleep(guard.timing.reply_delay);
+        let mut reply = [0u8; 11];
+        dev.read(&mut reply).context("failed to read DDC response")?;
+        let (cur, max) = parse_vcp_reply(decode_reply(&reply)?, vcp_code)?;
+        // Monitors known to report a wrong maximum
+        Ok((cur, guard.quirks.max_for(vcp_code).unwrap_or(max)))
+    })
+}
+
+/// Set VCP Feature.
+pub fn set_vcp(bus: u8, vcp_code: u8, value: u16) -> Result<()> {
+    let [hi, lo] = value.to_be_bytes();
+    let request = encode_request(&[SET_VCP_REQUEST, vcp_code, hi, lo]);
+    // No reply to wait for; the command interval before the next command is enforced by the bus guard
+    transact(bus, "Set VCP", |dev, _| dev.write(&request).context("failed to write DDC set request"))
+}
+
+/// Capabilities string, read in fragments (Capabilities Request with increasing offsets).
+pub fn capabilities(bus: u8) -> Result<String> {
+    let mut caps = Vec::new();
+    loop {
+        let offset = caps.len() as u16;
+        let [hi, lo] = offset.to_be_bytes();
+        let request = encode_request(&[CAPABILITIES_REQUEST, hi, lo]);
+        let fragment = transact(bus, "Capabilities Request", |dev, guard| {
+            dev.write(&request).context("failed to write DDC capabilities request")?;
+            thread::sleep(guard.timing.capabilities_delay);
+            // [0x6E, len, 0xE3, off_hi, off_lo, up to 32 data bytes, checksum]
+            let mut reply = [0u8; 38];
+            dev.read(&mut reply).context("failed to read DDC capabilities response")?;
+            let payload = decode_reply(&reply)?;
+            if payload.len() < 3 || payload[0] != CAPABILITIES_REPLY {
+                return Err(DdcError::InvalidReply(format!("not a capabilities reply: {:02X?}", payload)).into());
+            }
+            if u16::from_be_bytes([payload[1], payload[2]]) != offset {
+                return Err(DdcError::InvalidReply("capabilities fragment offset mismatch".into()).into());
+            }
+            Ok(payload[3..].to_vec())
+        })?;
+        if fragment.is_empty() {
+            break;
+        }
+        caps.extend_from_slice(&fragment);
+        if caps.len() > CAPABILITIES_MAX_LEN {
+            return Err(anyhow!("capabilities string on /dev/i2c-{} exceeds {} bytes", bus, CAPABILITIES_MAX_LEN));
+        }
+    }
+    let text: String = caps.iter().take_while(|&&b| b != 0).map(|&b| b as char).collect();
+    Ok(text.trim().to_string())
+}
+
+pub fn get_brightness(bus: u8) -> Result<u8> {
+    let (cur, _max) = get_vcp(bus, VCP_BRIGHTNESS)?;
+    Ok(cur.min(100) as u8)
+}
+
+pub fn set_brightness(bus: u8, value: u8) -> Result<()> {
+    set_vcp(bus, VCP_BRIGHTNESS, value.min(100) as u16)
+}
+
+/// Current input source (VCP 0x60). Only the low byte carries the value; some monitors put junk in the high byte.
+pub fn get_input(bus: u8) -> Result<u8> {
+    let (cur, _max) = get_vcp(bus, VCP_INPUT_SOURCE)?;
+    Ok((cur & 0xFF) as u8)
+}
+
+pub fn set_input(bus: u8, value: u8) -> Result<()> {
+    set_vcp(bus, VCP_INPUT_SOURCE, value as u16)
+}
+
+/// Input sources declared in the capabilities string; the current input when the monitor lists none.
+pub fn supported_inputs(bus: u8) -> Result<Vec<u8>> {
+    let caps = Capabilities::parse(&capabilities(bus)?);
+    let inputs = caps.values(VCP_INPUT_SOURCE).to_vec();
+    if !inputs.is_empty() {
+        return Ok(inputs);
+    }
+    Ok(vec![get_input(bus)?])
+}
+
+/// Power mode (VCP 0xD6).
+pub fn get_power(bus: u8) -> Result<PowerMode> {
+    let (cur, _max) = get_vcp(bus, VCP_POWER_MODE)?;
+    PowerMode::from_vcp(cur).ok_or_else(|| DdcError::InvalidReply(format!("unknown power mode 0x{:02X}", cur)).into())
+}
+
+pub fn set_power(bus: u8, mode: PowerMode) -> Result<()> {
+    set_vcp(bus, VCP_POWER_MODE, mode.vcp_value())
+}
+
+/// Speaker volume (VCP 0x62), 0..100.
+pub fn get_volume(bus: u8) -> Result<u8> {
+    let (cur, _max) = get_vcp(bus, VCP_AUDIO_VOLUME)?;
+    Ok(cur.min(100) as u8)
+}
+
+pub fn set_volume(bus: u8, value: u8) -> Result<()> {
+    set_vcp(bus, VCP_AUDIO_VOLUME, value.min(100) as u16)
+}
+
+/// Speaker mute (VCP 0x8D).
+pub fn get_mute(bus: u8) -> Result<bool> {
+    let (cur, _max) = get_vcp(bus, VCP_AUDIO_MUTE)?;
+    is_muted(cur).ok_or_else(|| DdcError::InvalidReply(format!("unknown mute state 0x{:02X}", cur)).into())
+}
+
+pub fn set_mute(bus: u8, muted: bool) -> Result<()> {
+    set_vcp(bus, VCP_AUDIO_MUTE, mute_value(muted))
+}
@@ -1,3 +1,12 @@
+pub mod adapters;
+pub mod edid;
+pub mod hotplug;
+pub mod i2c;
+pub mod probe;
+pub mod snapshot;
+pub mod timing;
+pub mod writer;
+
 use anyhow::{anyhow, Context, Result};
 use regex::Regex;
 use std::process::Stdio;
@@ -0,0 +1,65 @@
+//! Lightweight identification of the monitor on one display adapter, for
+//! components without the panel's full detection (tray hotplug refresh).
+
+use crate::adapters::connector_for_bus;
+use crate::timing::bus_quirks;
+use crate::{edid, i2c};
+use mondis_core::edid::{hash_short, Edid};
+use mondis_core::model::DisplayInfo;
+use mondis_core::{pci, pnp};
+
+fn port_name(connector: &str) -> Option<String> {
+    // "card0-DP-3" -> "DisplayPort 3", "card0-HDMI-A-1" -> "HDMI Port 1"
+    let (_, rest) = connector.split_once('-')?;
+    let (kind, num) = rest.rsplit_once('-')?;
+    let kind = match kind {
+        "DP" => "DisplayPort",
+        "HDMI-A" | "HDMI-B" | "HDMI" => "HDMI Port",
+        "DVI-D" | "DVI-I" | "DVI" => "DVI Port",
+        "VGA" => "VGA Port",
+        "eDP" => "eDP Port",
+        "LVDS" => "LVDS Port",
+        other => other,
+    };
+    Some(format!("{} {}", kind, num))
+}
+
+fn card_name(connector: &str) -> Option<String> {
+    let (card, _) = connector.split_once('-')?;
+    let gpu = pci::GpuInfo::from_card(card)?;
+    Some(if gpu.slot.is_empty() { gpu.display_name() } else { format!("{} · {}", gpu.display_name(), gpu.slot) })
+}
+
+/// Monitor on /dev/i2c-N with its raw EDID, or `None` if nothing answers there.
+/// `xrandr_output` is left empty for the caller to fill.
+pub fn probe_bus(bus: u8) -> Option<(DisplayInfo, Vec<u8>)> {
+    let edid_bytes = edid::read_edid(bus).ok()?;
+    let parsed = Edid::parse(&edid_bytes).ok()?;
+    let supports_ddc = i2c::get_vcp(bus, i2c::VCP_BRIGHTNESS).is_ok() && !bus_quirks(bus).is_hidden(i2c::VCP_BRIGHTNESS);
+    let connector = connector_for_bus(bus);
+
+    let model = parsed.name().unwrap_or("Monitor").to_string();
+    let connector_info = connector.as_ref().map(|c| format!(" • {}", c)).unwrap_or_default();
+    let name = format!(
+        "{} {}{} ({})",
+        pnp::display_name(&parsed.manufacturer),
+        model,
+        connector_info,
+        if supports_ddc { "DDC" } else { "нет управления" }
+    );
+
+    let display = DisplayInfo {
+        i2c_bus: bus,
+        name,
+        manufacturer: Some(parsed.manufacturer.clone()),
+        model: Some(model),
+        serial: Some(format!("{:08X}", u32::from_be_bytes([edid_bytes[12], edid_bytes[13], edid_bytes[14], edid_bytes[15]]))),
+        card_name: connector.as_deref().and_then(card_name),
+        port_name: connector.as_deref().and_then(port_name),
+        connector,
+        supports_ddc,
+        xrandr_output: None,
+        edid_hash: Some(hash_short(&edid_bytes)),
+    };
+    Some((display, edid_bytes))
+}
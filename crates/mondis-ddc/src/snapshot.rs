@@ -0,0 +1,83 @@
+//! Capture and restore of monitor state snapshots ([`mondis_core::snapshot`]) over DDC/CI.
+
+use crate::timing::bus_quirks;
+use crate::{edid, i2c};
+use anyhow::{bail, Result};
+use mondis_core::capabilities::Capabilities;
+use mondis_core::edid::Edid;
+use mondis_core::snapshot::{is_captured, Snapshot};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// EDID manufacturer and product code of the monitor on `bus`.
+pub fn monitor_model(bus: u8) -> Result<(String, u16)> {
+    let parsed = Edid::parse(&edid::read_edid(bus)?)?;
+    Ok((parsed.manufacturer, parsed.product_code))
+}
+
+/// Read every declared control that belongs in a snapshot; controls that fail to read are skipped.
+/// The XRandR part is left for the caller.
+pub fn capture(bus: u8) -> Result<Snapshot> {
+    let parsed = Edid::parse(&edid::read_edid(bus)?)?;
+    let caps = Capabilities::parse(&i2c::capabilities(bus)?);
+    let quirks = bus_quirks(bus);
+    let mut vcp = BTreeMap::new();
+    for (&code, declared) in &caps.vcp {
+        if !is_captured(code, declared, &quirks) {
+            continue;
+        }
+        match i2c::get_vcp(bus, code) {
+            Ok((cur, _)) => {
+                vcp.insert(code, cur);
+            }
+            Err(e) => tracing::debug!("i2c-{}: snapshot skips 0x{:02X}: {e:#}", bus, code),
+        }
+    }
+    if vcp.is_empty() {
+        bail!("no readable controls on /dev/i2c-{}", bus);
+    }
+    Ok(Snapshot {
+        manufacturer: parsed.manufacturer.clone(),
+        product_code: parsed.product_code,
+        model: parsed.name().map(str::to_string),
+        serial: Some(format!("{:08X}", parsed.serial_number)),
+        created: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default(),
+        vcp,
+        xrandr: None,
+    })
+}
+
+/// Values of `snapshot` that can be written to the monitor on `bus`, in restore order: the
+/// monitor must be the same model, and codes it does not declare, only reads or would not
+/// capture ([`is_captured`]) are dropped.
+pub fn restorable(bus: u8, snapshot: &Snapshot) -> Result<Vec<(u8, u16)>> {
+    let (manufacturer, product_code) = monitor_model(bus)?;
+    if !snapshot.matches(&manufacturer, product_code) {
+        bail!(
+            "snapshot is for {} 0x{:04X}, the monitor on /dev/i2c-{} is {} 0x{:04X}",
+            snapshot.manufacturer,
+            snapshot.product_code,
+            bus,
+            manufacturer,
+            product_code
+        );
+    }
+    let caps = Capabilities::parse(&i2c::capabilities(bus)?);
+    let quirks = bus_quirks(bus);
+    Ok(snapshot
+        .restore_order()
+        .into_iter()
+        .filter(|&(code, _)| caps.supports(code) && is_captured(code, caps.values(code), &quirks) && !quirks.is_read_only(code))
+        .collect())
+}
+
+/// Write the snapshot's controls directly; returns the codes that failed.
+pub fn restore(bus: u8, snapshot: &Snapshot) -> Result<Vec<(u8, anyhow::Error)>> {
+    let mut failed = Vec::new();
+    for (code, value) in restorable(bus, snapshot)? {
+        if let Err(e) = i2c::set_vcp(bus, code, value) {
+            failed.push((code, e));
+        }
+    }
+    Ok(failed)
+}
@@ -0,0 +1,240 @@
+//! DDC/CI timing: MCCS minimum delays, retry policy, per-model overrides from the
+//! quirks database and per-bus pacing so that commands from different threads never overlap.
+
+use crate::adapters::connector_for_bus;
+use crate::edid;
+use mondis_core::edid::Edid;
+use mondis_core::quirks::{MonitorQuirks, QuirkDb, TimingOverride};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DdcTiming {
+    /// Between writing a request and reading its reply (MCCS: 40 ms).
+    pub reply_delay: Duration,
+    /// Minimum gap between the end of one command and the start of the next (MCCS: 50 ms).
+    pub command_interval: Duration,
+    /// Reply delay for Capabilities Request fragments (MCCS: 50 ms).
+    pub capabilities_delay: Duration,
+    /// Extra attempts after a failed transaction.
+    pub retries: u32,
+    /// Wait before the first retry; doubled for each further one.
+    pub retry_backoff: Duration,
+}
+
+impl Default for DdcTiming {
+    fn default() -> Self {
+        DdcTiming {
+            reply_delay: Duration::from_millis(40),
+            command_interval: Duration::from_millis(50),
+            capabilities_delay: Duration::from_millis(50),
+            retries: 2,
+            retry_backoff: Duration::from_millis(30),
+        }
+    }
+}
+
+impl DdcTiming {
+    /// Delay before retry number `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_backoff * 2u32.saturating_pow(attempt.saturating_sub(1))
+    }
+
+    /// Defaults with the fields set in a quirks entry replaced.
+    pub fn with_override(mut self, o: &TimingOverride) -> DdcTiming {
+        let ms = Duration::from_millis;
+        self.reply_delay = o.reply_delay_ms.map(ms).unwrap_or(self.reply_delay);
+        self.command_interval = o.command_interval_ms.map(ms).unwrap_or(self.command_interval);
+        self.capabilities_delay = o.capabilities_delay_ms.map(ms).unwrap_or(self.capabilities_delay);
+        self.retries = o.retries.unwrap_or(self.retries);
+        self.retry_backoff = o.retry_backoff_ms.map(ms).unwrap_or(self.retry_backoff);
+        self
+    }
+}
+
+/// Timing for a monitor from the quirks database.
+pub fn timing_for(manufacturer: &str, product: u16, firmware: Option<&str>) -> DdcTiming {
+    DdcTiming::default().with_override(&QuirkDb::global().lookup(manufacturer, product, firmware).timing)
+}
+
+/// Display Firmware Level (MCCS 0xC9): version in the high byte, revision in the low byte.
+pub const VCP_FIRMWARE_LEVEL: u8 = 0xC9;
+
+/// Firmware level as the quirks database writes it ("major.minor").
+pub fn format_firmware(raw: u16) -> String {
+    format!("{}.{}", raw >> 8, raw & 0xFF)
+}
+
+/// What the quirks database needs to know about the monitor on a bus.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MonitorIdentity {
+    pub manufacturer: String,
+    pub product: u16,
+    pub firmware: Option<String>,
+}
+
+struct BusState {
+    timing: Option<DdcTiming>,
+    // Set with set_bus_timing; survives re-resolution once the firmware level is known
+    timing_override: Option<DdcTiming>,
+    quirks: Option<MonitorQuirks>,
+    identity: Option<MonitorIdentity>,
+    // 0xC9 has been asked for (once per bus)
+    firmware_read: bool,
+    last_command: Option<Instant>,
+}
+
+fn bus_state(bus: u8) -> Arc<Mutex<BusState>> {
+    static BUSES: OnceLock<Mutex<HashMap<u8, Arc<Mutex<BusState>>>>> = OnceLock::new();
+    let mut buses = BUSES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    buses
+        .entry(bus)
+        .or_insert_with(|| {
+            Arc::new(Mutex::new(BusState {
+                timing: None,
+                timing_override: None,
+                quirks: None,
+                identity: None,
+                firmware_read: false,
+                last_command: None,
+            }))
+        })
+        .clone()
+}
+
+// EDID from the kernel copy if there is one (no bus traffic), else over I2C
+fn read_identity(bus: u8) -> Option<MonitorIdentity> {
+    let raw = connector_for_bus(bus)
+        .and_then(|c| edid::read_edid_sysfs(&c).ok())
+        .or_else(|| edid::read_edid_i2c(bus).ok())?;
+    let edid = Edid::parse(&raw).ok()?;
+    Some(MonitorIdentity { manufacturer: edid.manufacturer, product: edid.product_code, firmware: None })
+}
+
+fn ensure_resolved(bus: u8, state: &mut BusState) {
+    if state.identity.is_none() {
+        state.identity = read_identity(bus);
+    }
+    if state.quirks.is_none() {
+        let quirks = state
+            .identity
+            .as_ref()
+            .map(|i| QuirkDb::global().lookup(&i.manufacturer, i.product, i.firmware.as_deref()))
+            .unwrap_or_default();
+        state.quirks = Some(quirks);
+    }
+    if state.timing.is_none() {
+        let quirks = state.quirks.as_ref().map(|q| q.timing.clone()).unwrap_or_default();
+        state.timing = Some(state.timing_override.unwrap_or_else(|| DdcTiming::default().with_override(&quirks)));
+    }
+}
+
+// Firmware-specific quirks need the firmware level, which is itself read over DDC: the first
+// command on a bus reads 0xC9 with the EDID-only quirks, then the quirks are resolved again
+fn ensure_firmware(bus: u8) {
+    let state = bus_state(bus);
+    {
+        let mut locked = state.lock().unwrap_or_else(|e| e.into_inner());
+        if locked.firmware_read {
+            return;
+        }
+        // Also keeps the read below from coming back here
+        locked.firmware_read = true;
+        ensure_resolved(bus, &mut locked);
+        if locked.identity.as_ref().is_none_or(|i| i.firmware.is_some()) {
+            return;
+        }
+    }
+    let Ok((raw, _)) = crate::i2c::get_vcp(bus, VCP_FIRMWARE_LEVEL) else { return };
+    let mut locked = state.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(identity) = locked.identity.as_mut() {
+        identity.firmware = Some(format_firmware(raw));
+        locked.quirks = None;
+        locked.timing = None;
+    }
+}
+
+/// Quirks of the monitor on /dev/i2c-N, by EDID manufacturer/product and firmware level.
+pub fn bus_quirks(bus: u8) -> MonitorQuirks {
+    ensure_firmware(bus);
+    let state = bus_state(bus);
+    let mut locked = state.lock().unwrap_or_else(|e| e.into_inner());
+    ensure_resolved(bus, &mut locked);
+    locked.quirks.clone().unwrap_or_default()
+}
+
+/// Firmware level ("major.minor") of the monitor on /dev/i2c-N, read once from VCP 0xC9.
+pub fn bus_firmware(bus: u8) -> Option<String> {
+    ensure_firmware(bus);
+    let state = bus_state(bus);
+    let locked = state.lock().unwrap_or_else(|e| e.into_inner());
+    locked.identity.as_ref().and_then(|i| i.firmware.clone())
+}
+
+/// Identify the monitor on /dev/i2c-N without reading its EDID or firmware level
+/// (when the caller already knows them); its quirks are resolved again.
+pub fn set_bus_identity(bus: u8, identity: MonitorIdentity) {
+    let state = bus_state(bus);
+    let mut locked = state.lock().unwrap_or_else(|e| e.into_inner());
+    locked.firmware_read = identity.firmware.is_some();
+    locked.identity = Some(identity);
+    locked.quirks = None;
+    locked.timing = None;
+}
+
+/// Override the timing used for /dev/i2c-N (otherwise resolved from its EDID on first use).
+pub fn set_bus_timing(bus: u8, timing: DdcTiming) {
+    let state = bus_state(bus);
+    let mut locked = state.lock().unwrap_or_else(|e| e.into_inner());
+    locked.timing_override = Some(timing);
+    locked.timing = Some(timing);
+}
+
+/// Drop what is known about the monitor on /dev/i2c-N (after hotplug it may be a different one).
+pub fn forget_bus(bus: u8) {
+    let state = bus_state(bus);
+    let mut locked = state.lock().unwrap_or_else(|e| e.into_inner());
+    locked.timing = None;
+    locked.timing_override = None;
+    locked.quirks = None;
+    locked.identity = None;
+    locked.firmware_read = false;
+}
+
+/// Exclusive access to one bus for a whole transaction (including retries).
+pub struct BusGuard<'a> {
+    state: std::sync::MutexGuard<'a, BusState>,
+    pub timing: DdcTiming,
+    pub quirks: MonitorQuirks,
+}
+
+impl BusGuard<'_> {
+    /// Sleep until the command interval since the previous command has passed.
+    pub fn wait_ready(&self) {
+        if let Some(last) = self.state.last_command {
+            let elapsed = last.elapsed();
+            if elapsed < self.timing.command_interval {
+                thread::sleep(self.timing.command_interval - elapsed);
+            }
+        }
+    }
+
+    /// Mark the end of a command.
+    pub fn done(&mut self) {
+        self.state.last_command = Some(Instant::now());
+    }
+}
+
+/// Run `f` with the bus locked and paced.
+pub fn with_bus<T>(bus: u8, f: impl FnOnce(&mut BusGuard) -> T) -> T {
+    ensure_firmware(bus);
+    let state = bus_state(bus);
+    let mut locked = state.lock().unwrap_or_else(|e| e.into_inner());
+    ensure_resolved(bus, &mut locked);
+    let timing = locked.timing.unwrap_or_default();
+    let quirks = locked.quirks.clone().unwrap_or_default();
+    let mut guard = BusGuard { state: locked, timing, quirks };
+    f(&mut guard)
+}
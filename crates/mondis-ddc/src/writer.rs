@@ -0,0 +1,178 @@
+//! Coalescing write queue for slider drags: one worker thread per monitor, only the
+//! newest pending value per control is written. DDC writes are paced by the bus
+//! timing in [`crate::timing`], so a drag never floods the monitor. Optionally each
+//! write is verified by reading the control back.
+
+use crate::i2c;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Applies one value (DDC Set VCP, XRandR, ...).
+pub type WriteFn = Arc<dyn Fn(u16) -> Result<()> + Send + Sync>;
+/// Reads the value back after a write.
+pub type ReadFn = Arc<dyn Fn() -> Result<u16> + Send + Sync>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WriteResult {
+    pub bus: u8,
+    pub code: u8,
+    pub value: u16,
+    pub result: Result<(), String>,
+    /// Value read back after the write (verification on and no newer value queued).
+    pub readback: Option<u16>,
+}
+
+struct Pending {
+    code: u8,
+    value: u16,
+    apply: WriteFn,
+    read: Option<ReadFn>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    // In first-submission order; a newer value for a queued code replaces the old one in place
+    pending: Vec<Pending>,
+    in_flight: Option<u8>,
+    last_write: HashMap<u8, Instant>,
+}
+
+#[derive(Default)]
+struct Queue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+}
+
+type ResultFn = Arc<dyn Fn(WriteResult) + Send + Sync>;
+
+pub struct DdcWriter {
+    queues: Mutex<HashMap<u8, Arc<Queue>>>,
+    on_result: ResultFn,
+    verify: Arc<AtomicBool>,
+}
+
+impl DdcWriter {
+    /// `on_result` runs on the worker thread after every performed write.
+    pub fn new(on_result: impl Fn(WriteResult) + Send + Sync + 'static) -> DdcWriter {
+        DdcWriter {
+            queues: Mutex::new(HashMap::new()),
+            on_result: Arc::new(on_result),
+            verify: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Read each control back after writing it (for writes submitted with a read function).
+    pub fn set_verify(&self, verify: bool) {
+        self.verify.store(verify, Ordering::Relaxed);
+    }
+
+    fn queue(&self, bus: u8) -> Arc<Queue> {
+        let mut queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+        queues
+            .entry(bus)
+            .or_insert_with(|| {
+                let queue = Arc::new(Queue::default());
+                let worker_queue = queue.clone();
+                let on_result = self.on_result.clone();
+                let verify = self.verify.clone();
+                thread::spawn(move || worker(bus, worker_queue, on_result, verify));
+                queue
+            })
+            .clone()
+    }
+
+    /// Queue `value` for control `code` on monitor `bus`, replacing any value not yet written.
+    pub fn submit(&self, bus: u8, code: u8, value: u16, apply: WriteFn, read: Option<ReadFn>) {
+        let queue = self.queue(bus);
+        let mut state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.pending.iter_mut().find(|p| p.code == code) {
+            Some(p) => {
+                p.value = value;
+                p.apply = apply;
+                p.read = read;
+            }
+            None => state.pending.push(Pending { code, value, apply, read }),
+        }
+        queue.changed.notify_all();
+    }
+
+    /// Queue a DDC Set VCP (verified with Get VCP).
+    pub fn set_vcp(&self, bus: u8, code: u8, value: u16) {
+        self.submit(
+            bus,
+            code,
+            value,
+            Arc::new(move |v| i2c::set_vcp(bus, code, v)),
+            Some(Arc::new(move || i2c::get_vcp(bus, code).map(|(cur, _)| cur))),
+        );
+    }
+
+    /// Nothing queued or being written for the control, and the last write is older than `quiet`
+    /// (background polling should not fight a slider that is being dragged).
+    pub fn is_settled(&self, bus: u8, code: u8, quiet: Duration) -> bool {
+        let queue = self.queue(bus);
+        let state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.in_flight != Some(code)
+            && !state.pending.iter().any(|p| p.code == code)
+            && state.last_write.get(&code).is_none_or(|t| t.elapsed() >= quiet)
+    }
+
+    /// Wait until everything queued for `bus` is written; `false` on timeout.
+    pub fn wait_idle(&self, bus: u8, timeout: Duration) -> bool {
+        let queue = self.queue(bus);
+        let deadline = Instant::now() + timeout;
+        let mut state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+        while state.in_flight.is_some() || !state.pending.is_empty() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            state = queue.changed.wait_timeout(state, left).unwrap_or_else(|e| e.into_inner()).0;
+        }
+        true
+    }
+}
+
+fn worker(bus: u8, queue: Arc<Queue>, on_result: ResultFn, verify: Arc<AtomicBool>) {
+    loop {
+        let next = {
+            let mut state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+            while state.pending.is_empty() {
+                state = queue.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            let next = state.pending.remove(0);
+            state.in_flight = Some(next.code);
+            next
+        };
+        let result = (next.apply)(next.value).map_err(|e| format!("{e:#}"));
+        if let Err(e) = &result {
+            tracing::warn!("i2c-{}: write 0x{:02X}={} failed: {}", bus, next.code, next.value, e);
+        }
+
+        // Read back unless a newer value is already queued (it would be overwritten anyway)
+        let superseded = || {
+            let state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.pending.iter().any(|p| p.code == next.code)
+        };
+        let readback = match &next.read {
+            Some(read) if result.is_ok() && verify.load(Ordering::Relaxed) && !superseded() => match read() {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    tracing::warn!("i2c-{}: readback of 0x{:02X} failed: {e:#}", bus, next.code);
+                    None
+                }
+            },
+            _ => None,
+        };
+        on_result(WriteResult { bus, code: next.code, value: next.value, result, readback });
+
+        let mut state = queue.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.in_flight = None;
+        state.last_write.insert(next.code, Instant::now());
+        queue.changed.notify_all();
+    }
+}
@@ -0,0 +1,61 @@
+use mondis_ddc::i2c::{decode_reply, encode_request, parse_vcp_reply, DdcError};
+use mondis_ddc::timing::{timing_for, DdcTiming};
+use std::time::Duration;
+
+// Reply frame with a valid checksum
+fn reply(payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x6E, 0x80 | payload.len() as u8];
+    frame.extend_from_slice(payload);
+    frame.push(frame.iter().fold(0x50, |acc, &b| acc ^ b));
+    frame
+}
+
+#[test]
+fn encodes_get_vcp_request() {
+    assert_eq!(encode_request(&[0x01, 0x10]), vec![0x51, 0x82, 0x01, 0x10, 0xAC]);
+}
+
+#[test]
+fn decodes_vcp_reply() {
+    let frame = reply(&[0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32]);
+    let payload = decode_reply(&frame).unwrap();
+    assert_eq!(parse_vcp_reply(payload, 0x10), Ok((50, 100)));
+    // Trailing bytes after the checksum are ignored
+    let mut padded = frame.clone();
+    padded.push(0xFF);
+    assert!(decode_reply(&padded).is_ok());
+}
+
+#[test]
+fn rejects_bad_checksum_and_null_message() {
+    let mut frame = reply(&[0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32]);
+    frame[9] ^= 0x01;
+    assert!(matches!(decode_reply(&frame), Err(DdcError::Checksum(_))));
+
+    assert_eq!(decode_reply(&[0x6E, 0x80, 0xBE]), Err(DdcError::NullResponse));
+    assert!(DdcError::NullResponse.is_transient());
+}
+
+#[test]
+fn unsupported_vcp_is_not_retried() {
+    let frame = reply(&[0x02, 0x01, 0xD6, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    let err = parse_vcp_reply(decode_reply(&frame).unwrap(), 0xD6).unwrap_err();
+    assert_eq!(err, DdcError::UnsupportedVcp(0xD6));
+    assert!(!err.is_transient());
+}
+
+#[test]
+fn vcp_code_mismatch_is_invalid() {
+    let frame = reply(&[0x02, 0x00, 0x12, 0x00, 0x00, 0x64, 0x00, 0x32]);
+    assert!(matches!(parse_vcp_reply(decode_reply(&frame).unwrap(), 0x10), Err(DdcError::InvalidReply(_))));
+}
+
+#[test]
+fn timing_defaults_and_backoff() {
+    let timing = timing_for("ZZZ", 0x1234, None);
+    assert_eq!(timing, DdcTiming::default());
+    assert_eq!(timing.reply_delay, Duration::from_millis(40));
+    assert_eq!(timing.backoff(1), timing.retry_backoff);
+    assert_eq!(timing.backoff(3), timing.retry_backoff * 4);
+    assert!(timing_for("GSM", 0x5B7F, None).reply_delay > timing.reply_delay);
+}
@@ -0,0 +1,52 @@
+use mondis_ddc::hotplug::{classify_uevent, parse_uevent, HotplugEvent};
+
+fn uevent(header: &str, vars: &[&str]) -> Vec<u8> {
+    let mut msg = header.as_bytes().to_vec();
+    for var in vars {
+        msg.push(0);
+        msg.extend_from_slice(var.as_bytes());
+    }
+    msg.push(0);
+    msg
+}
+
+fn classify(msg: &[u8]) -> Option<HotplugEvent> {
+    parse_uevent(msg).and_then(|(_, vars)| classify_uevent(&vars))
+}
+
+#[test]
+fn i2c_dev_add_and_remove() {
+    let add = uevent(
+        "add@/devices/pci0000:00/0000:00:02.0/drm/card0/card0-DP-1/i2c-7/i2c-dev/i2c-7",
+        &["ACTION=add", "SUBSYSTEM=i2c-dev", "DEVNAME=i2c-7", "MAJOR=89", "MINOR=7"],
+    );
+    assert_eq!(classify(&add), Some(HotplugEvent::I2cAdded(7)));
+
+    let remove = uevent(
+        "remove@/devices/pci0000:00/0000:00:02.0/drm/card0/card0-DP-1/i2c-7/i2c-dev/i2c-7",
+        &["ACTION=remove", "SUBSYSTEM=i2c-dev", "DEVNAME=i2c-7"],
+    );
+    assert_eq!(classify(&remove), Some(HotplugEvent::I2cRemoved(7)));
+}
+
+#[test]
+fn drm_hotplug_without_connector() {
+    let msg = uevent(
+        "change@/devices/pci0000:00/0000:00:02.0/drm/card1",
+        &["ACTION=change", "SUBSYSTEM=drm", "HOTPLUG=1", "DEVNAME=dri/card1"],
+    );
+    assert_eq!(classify(&msg), Some(HotplugEvent::Drm { card: "card1".into(), connector: None }));
+}
+
+#[test]
+fn ignores_unrelated_and_udevd_messages() {
+    // DRM change without HOTPLUG=1 (e.g. lease or content-protection update)
+    let lease = uevent("change@/devices/drm/card0", &["ACTION=change", "SUBSYSTEM=drm", "LEASE=1", "DEVNAME=dri/card0"]);
+    assert_eq!(classify(&lease), None);
+
+    let usb = uevent("add@/devices/usb1/1-1", &["ACTION=add", "SUBSYSTEM=usb", "DEVNAME=bus/usb/001/002"]);
+    assert_eq!(classify(&usb), None);
+
+    // udevd re-broadcasts carry a binary "libudev" header
+    assert!(parse_uevent(b"libudev\0\xfe\xed\xca\xfe").is_none());
+}
@@ -0,0 +1,29 @@
+use mondis_ddc::timing::{bus_quirks, format_firmware, set_bus_identity, MonitorIdentity};
+
+// The quirks database is loaded once per process, so this file holds a single test
+#[test]
+fn firmware_specific_quirks_apply_through_bus_quirks() {
+    let home = std::env::temp_dir().join(format!("mondis-ddc-quirks-{}", std::process::id()));
+    let config = home.join(".config").join("mondis");
+    std::fs::create_dir_all(&config).unwrap();
+    std::fs::write(
+        config.join("quirks.json"),
+        r#"{ "quirks": [{ "manufacturer": "ZZZ", "product": "0x1234", "firmware": "1.2", "hide_vcp": ["0x10"] }] }"#,
+    )
+    .unwrap();
+    std::env::set_var("HOME", &home);
+
+    assert_eq!(format_firmware(0x0102), "1.2");
+    let identity = |firmware: u16| MonitorIdentity {
+        manufacturer: "ZZZ".into(),
+        product: 0x1234,
+        firmware: Some(format_firmware(firmware)),
+    };
+    // Buses that do not exist: nothing is read from them
+    set_bus_identity(250, identity(0x0102));
+    assert!(bus_quirks(250).is_hidden(0x10));
+    set_bus_identity(251, identity(0x0103));
+    assert!(!bus_quirks(251).is_hidden(0x10));
+
+    std::fs::remove_dir_all(&home).ok();
+}
@@ -0,0 +1,88 @@
+use mondis_ddc::writer::{DdcWriter, WriteFn, WriteResult};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+type WriteLog = Arc<Mutex<Vec<(u8, u16)>>>;
+
+// Records applied values, taking `delay` per write like a slow monitor
+fn recorder(delay: Duration) -> (WriteLog, impl Fn(u8) -> WriteFn) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let log_for_fn = log.clone();
+    let make = move |code: u8| -> WriteFn {
+        let log = log_for_fn.clone();
+        Arc::new(move |v| {
+            thread::sleep(delay);
+            log.lock().unwrap().push((code, v));
+            Ok(())
+        })
+    };
+    (log, make)
+}
+
+#[test]
+fn coalesces_to_newest_value() {
+    let results = Arc::new(Mutex::new(Vec::<WriteResult>::new()));
+    let results_cb = results.clone();
+    let writer = DdcWriter::new(move |r| results_cb.lock().unwrap().push(r));
+    let (log, apply) = recorder(Duration::from_millis(20));
+
+    for v in 0..=100u16 {
+        writer.submit(3, 0x10, v, apply(0x10), None);
+    }
+    assert!(writer.wait_idle(3, Duration::from_secs(5)));
+
+    let log = log.lock().unwrap();
+    assert!(log.len() < 10, "expected coalesced writes, got {}", log.len());
+    assert_eq!(log.last(), Some(&(0x10, 100)));
+    assert_eq!(results.lock().unwrap().len(), log.len());
+}
+
+#[test]
+fn keeps_codes_in_submission_order_and_reports_errors() {
+    let results = Arc::new(Mutex::new(Vec::<WriteResult>::new()));
+    let results_cb = results.clone();
+    let writer = DdcWriter::new(move |r| results_cb.lock().unwrap().push(r));
+    let (log, apply) = recorder(Duration::from_millis(30));
+
+    // Occupy the worker so the following submissions queue up
+    writer.submit(4, 0x10, 1, apply(0x10), None);
+    thread::sleep(Duration::from_millis(10));
+    writer.submit(4, 0x12, 50, apply(0x12), None);
+    writer.submit(4, 0x62, 7, Arc::new(|_| Err(anyhow::anyhow!("NACK"))), None);
+    writer.submit(4, 0x12, 60, apply(0x12), None);
+    assert!(writer.wait_idle(4, Duration::from_secs(5)));
+
+    assert_eq!(*log.lock().unwrap(), vec![(0x10, 1), (0x12, 60)]);
+    let results = results.lock().unwrap();
+    let failed: Vec<_> = results.iter().filter(|r| r.result.is_err()).collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!((failed[0].code, failed[0].value), (0x62, 7));
+}
+
+#[test]
+fn verifies_by_reading_back() {
+    let results = Arc::new(Mutex::new(Vec::<WriteResult>::new()));
+    let results_cb = results.clone();
+    let writer = DdcWriter::new(move |r| results_cb.lock().unwrap().push(r));
+    writer.set_verify(true);
+
+    // Monitor clamps to 80
+    let stored = Arc::new(Mutex::new(0u16));
+    let stored_w = stored.clone();
+    let stored_r = stored.clone();
+    writer.submit(
+        5,
+        0x10,
+        95,
+        Arc::new(move |v| {
+            *stored_w.lock().unwrap() = v.min(80);
+            Ok(())
+        }),
+        Some(Arc::new(move || Ok(*stored_r.lock().unwrap()))),
+    );
+    assert!(writer.wait_idle(5, Duration::from_secs(5)));
+    assert_eq!(results.lock().unwrap()[0].readback, Some(80));
+    assert!(!writer.is_settled(5, 0x10, Duration::from_secs(60)));
+    assert!(writer.is_settled(5, 0x10, Duration::ZERO));
+}
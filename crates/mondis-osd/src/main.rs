@@ -0,0 +1,273 @@
+// Mondis OSD — экранный индикатор уровня яркости.
+//
+// Единственный экземпляр (GApplication "com.mondis.Osd"). Показ запускается:
+// - по D-Bus: org.gtk.Actions.Activate("show", [<("DP-1", "Dell U2720Q", 70)>], {})
+//   на com.mondis.Osd, /com/mondis/Osd;
+// - из командной строки: mondis-osd --output DP-1 --label "Dell U2720Q" --value 70
+//   (если OSD уже запущен, аргументы передаются первичному экземпляру).
+
+use anyhow::Result;
+use gtk::gdk::Display as GdkDisplay;
+use gtk::gio;
+use gtk::prelude::*;
+use gtk::{Application, Box as GtkBox, CssProvider, Label, LevelBar, Orientation, Window};
+use glib::translate::ToGlibPtr;
+use mondis_core::settings::read_settings;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+const OSD_WIDTH: i32 = 320;
+const OSD_HEIGHT: i32 = 96;
+const OSD_BOTTOM_MARGIN: i32 = 96;
+const FADE_STEP_MS: u64 = 30;
+// Сколько процесс живет без показов, прежде чем завершиться
+const IDLE_EXIT: Duration = Duration::from_secs(60);
+
+extern "C" {
+    // libgtk-4 (X11 backend)
+    fn gdk_x11_surface_get_xid(surface: *mut gtk::gdk::ffi::GdkSurface) -> std::os::raw::c_ulong;
+}
+
+struct OsdWindow {
+    window: Window,
+    title: Label,
+    bar: LevelBar,
+    value: Label,
+    hide_source: RefCell<Option<glib::SourceId>>,
+    fade_source: RefCell<Option<glib::SourceId>>,
+}
+
+struct OsdState {
+    app: Application,
+    windows: RefCell<HashMap<String, Rc<OsdWindow>>>,
+    hold: RefCell<Option<gio::ApplicationHoldGuard>>,
+    exit_source: RefCell<Option<glib::SourceId>>,
+}
+
+fn setup_styles() {
+    let css = r#"
+    .osd {
+        padding: 14px 18px;
+        border-radius: 14px;
+        background-color: rgba(20, 20, 20, 0.82);
+        color: white;
+    }
+    .osd-title { font-weight: 700; }
+    .osd-value { font-weight: 600; min-width: 44px; }
+    .osd levelbar block.filled { background-color: #4a9eff; }
+    .osd levelbar block.empty { background-color: rgba(255, 255, 255, 0.18); }
+    "#;
+    let provider = CssProvider::new();
+    provider.load_from_data(css);
+    if let Some(display) = GdkDisplay::default() {
+        gtk::style_context_add_provider_for_display(&display, &provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+    }
+}
+
+fn is_x11() -> bool {
+    GdkDisplay::default()
+        .map(|d| d.type_().name() == "GdkX11Display")
+        .unwrap_or(false)
+}
+
+// Монитор по имени коннектора/выхода XRandR (DP-1, HDMI-1...); иначе первый монитор
+fn find_monitor(output: &str) -> Option<gtk::gdk::Monitor> {
+    let display = GdkDisplay::default()?;
+    let monitors = display.monitors();
+    let mut first = None;
+    for i in 0..monitors.n_items() {
+        let Some(monitor) = monitors.item(i).and_then(|o| o.downcast::<gtk::gdk::Monitor>().ok()) else { continue };
+        if !output.is_empty() && monitor.connector().as_deref() == Some(output) {
+            return Some(monitor);
+        }
+        if first.is_none() { first = Some(monitor); }
+    }
+    first
+}
+
+fn create_window() -> Rc<OsdWindow> {
+    let window = Window::builder()
+        .decorated(false)
+        .resizable(false)
+        .focusable(false)
+        .can_target(false)
+        .default_width(OSD_WIDTH)
+        .default_height(OSD_HEIGHT)
+        .title("Mondis OSD")
+        .build();
+
+    let vbox = GtkBox::new(Orientation::Vertical, 8);
+    vbox.add_css_class("osd");
+    let title = Label::new(None);
+    title.add_css_class("osd-title");
+    title.set_xalign(0.0);
+    title.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    vbox.append(&title);
+
+    let row = GtkBox::new(Orientation::Horizontal, 12);
+    let bar = LevelBar::for_interval(0.0, 100.0);
+    bar.set_hexpand(true);
+    bar.set_valign(gtk::Align::Center);
+    let value = Label::new(None);
+    value.add_css_class("osd-value");
+    value.set_xalign(1.0);
+    row.append(&bar);
+    row.append(&value);
+    vbox.append(&row);
+    window.set_child(Some(&vbox));
+
+    Rc::new(OsdWindow {
+        window,
+        title,
+        bar,
+        value,
+        hide_source: RefCell::new(None),
+        fade_source: RefCell::new(None),
+    })
+}
+
+// На X11 окно делаем override-redirect и ставим внизу по центру нужного монитора.
+// На Wayland позиционирование остается за композитором.
+fn place_window(osd: &OsdWindow, output: &str) {
+    if !is_x11() { return; }
+    let Some(monitor) = find_monitor(output) else { return };
+    let geom = monitor.geometry();
+    let scale = monitor.scale_factor();
+    let x = geom.x() + (geom.width() - OSD_WIDTH) / 2;
+    let y = geom.y() + geom.height() - OSD_HEIGHT - OSD_BOTTOM_MARGIN;
+
+    WidgetExt::realize(&osd.window);
+    let surface = osd.window.surface();
+    let xid = unsafe { gdk_x11_surface_get_xid(surface.to_glib_none().0) };
+    if let Err(e) = mondis_x11::make_override_redirect(xid as u32, x * scale, y * scale) {
+        eprintln!("mondis-osd: failed to place window: {e:#}");
+    }
+}
+
+fn start_fade(state: &Rc<OsdState>, osd: &Rc<OsdWindow>) {
+    let state_c = state.clone();
+    let osd_c = osd.clone();
+    let source = glib::timeout_add_local(Duration::from_millis(FADE_STEP_MS), move || {
+        let opacity = osd_c.window.opacity() - 0.1;
+        if opacity > 0.0 {
+            osd_c.window.set_opacity(opacity);
+            return glib::ControlFlow::Continue;
+        }
+        osd_c.window.set_visible(false);
+        osd_c.fade_source.borrow_mut().take();
+        schedule_exit_if_idle(&state_c);
+        glib::ControlFlow::Break
+    });
+    osd.fade_source.replace(Some(source));
+}
+
+fn schedule_exit_if_idle(state: &Rc<OsdState>) {
+    if state.windows.borrow().values().any(|w| w.window.is_visible()) { return; }
+    if let Some(id) = state.exit_source.borrow_mut().take() { id.remove(); }
+    let state_c = state.clone();
+    let source = glib::timeout_add_local_once(IDLE_EXIT, move || {
+        state_c.exit_source.borrow_mut().take();
+        // Освобождаем hold — GApplication завершится сам
+        state_c.hold.borrow_mut().take();
+    });
+    state.exit_source.replace(Some(source));
+}
+
+fn show(state: &Rc<OsdState>, output: &str, label: &str, value: u32) {
+    let settings = read_settings();
+    if !settings.osd_enabled { return; }
+
+    if let Some(id) = state.exit_source.borrow_mut().take() { id.remove(); }
+    if state.hold.borrow().is_none() {
+        state.hold.replace(Some(state.app.hold()));
+    }
+
+    let existing = state.windows.borrow().get(output).cloned();
+    let osd = match existing {
+        Some(osd) => osd,
+        None => {
+            let osd = create_window();
+            place_window(&osd, output);
+            state.windows.borrow_mut().insert(output.to_string(), osd.clone());
+            osd
+        }
+    };
+
+    osd.title.set_text(label);
+    osd.bar.set_value(value.min(100) as f64);
+    osd.value.set_text(&format!("{}%", value.min(100)));
+
+    if let Some(id) = osd.hide_source.borrow_mut().take() { id.remove(); }
+    if let Some(id) = osd.fade_source.borrow_mut().take() { id.remove(); }
+    osd.window.set_opacity(1.0);
+    osd.window.present();
+
+    let state_c = state.clone();
+    let osd_c = osd.clone();
+    let source = glib::timeout_add_local_once(Duration::from_millis(settings.osd_timeout_ms as u64), move || {
+        osd_c.hide_source.borrow_mut().take();
+        start_fade(&state_c, &osd_c);
+    });
+    osd.hide_source.replace(Some(source));
+}
+
+// --output NAME --label TEXT --value N
+fn parse_args(args: &[std::ffi::OsString]) -> Option<(String, String, u32)> {
+    let mut output = String::new();
+    let mut label = String::new();
+    let mut value = None;
+    let mut it = args.iter().skip(1).map(|a| a.to_string_lossy().to_string());
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--output" => output = it.next().unwrap_or_default(),
+            "--label" => label = it.next().unwrap_or_default(),
+            "--value" => value = it.next().and_then(|v| v.parse::<u32>().ok()),
+            _ => eprintln!("mondis-osd: unknown argument '{}'", arg),
+        }
+    }
+    value.map(|v| (output, label, v))
+}
+
+fn main() -> Result<()> {
+    let app = Application::builder()
+        .application_id("com.mondis.Osd")
+        .flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE)
+        .build();
+
+    let state = Rc::new(OsdState {
+        app: app.clone(),
+        windows: RefCell::new(HashMap::new()),
+        hold: RefCell::new(None),
+        exit_source: RefCell::new(None),
+    });
+
+    let state_c = state.clone();
+    app.connect_startup(move |app| {
+        setup_styles();
+        // Действие "show" экспортируется GApplication через org.gtk.Actions
+        let action = gio::SimpleAction::new("show", Some(glib::VariantTy::new("(ssu)").unwrap()));
+        let state_a = state_c.clone();
+        action.connect_activate(move |_, param| {
+            if let Some((output, label, value)) = param.and_then(|p| p.get::<(String, String, u32)>()) {
+                show(&state_a, &output, &label, value);
+            }
+        });
+        app.add_action(&action);
+        // Процесс запущен без показа (или сервисом) — держим его до таймаута бездействия
+        state_c.hold.replace(Some(app.hold()));
+        schedule_exit_if_idle(&state_c);
+    });
+
+    let state_c = state.clone();
+    app.connect_command_line(move |_, cmdline| {
+        if let Some((output, label, value)) = parse_args(&cmdline.arguments()) {
+            show(&state_c, &output, &label, value);
+        }
+        0
+    });
+
+    app.run();
+    Ok(())
+}
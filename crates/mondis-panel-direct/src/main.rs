@@ -8,113 +8,762 @@ use gtk::gdk::Display as GdkDisplay;
 use tracing_subscriber::EnvFilter;
 use glib::clone;
 use std::rc::Rc;
+use std::sync::{Arc, OnceLock};
 use std::cell::{Cell, RefCell};
 use std::thread;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::process::Command;
-use i2cdev::linux::LinuxI2CDevice;
-use i2cdev::core::I2CDevice;
 use std::fs;
 use std::path::PathBuf;
+use mondis_core::cache;
+use mondis_core::capabilities::Capabilities;
+use mondis_core::inputs::{self, VCP_INPUT_SOURCE};
+use mondis_core::power::{PowerMode, VCP_POWER_MODE};
+use mondis_core::audio::{self, VCP_AUDIO_MUTE, VCP_AUDIO_VOLUME};
+use mondis_core::color;
+use mondis_core::vcp;
+use mondis_core::snapshot::{self, Snapshot, XrandrState};
+use mondis_core::transaction::{Change, Resolved, Setting, Transaction, Value};
+use mondis_core::history::{self, read_history, update_history, History};
+use mondis_core::edid::{self, Edid, VideoInput};
+use mondis_core::pci;
+use mondis_core::pnp;
+use mondis_core::model::{DisplayInfo, VideoCard};
+use mondis_core::quirks::{MonitorQuirks, QuirkDb};
+use mondis_core::settings::{load_settings, monitor_key, read_settings, update_settings, ControlMethod, UiSettings};
+use mondis_ddc::adapters;
+use mondis_ddc::i2c as ddc_i2c;
+use mondis_ddc::i2c::VCP_BRIGHTNESS;
+use mondis_ddc::timing;
+use mondis_ddc::writer::{DdcWriter, WriteResult};
+use mondis_ddc::hotplug::{self, HotplugEvent};
 
 // ---------- Config, cache and settings ----------
 
-fn get_config_dir() -> Result<PathBuf, String> {
-    let home = std::env::var("HOME").map_err(|_| "HOME environment variable not set")?;
-    let dir = PathBuf::from(home).join(".config").join("mondis");
-    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
-    Ok(dir)
+// ---------- DDC write queue ----------
+
+// Слушатель результата записи; false — виджет уже удален, слушатель снимается
+type WriteListener = Box<dyn Fn(&WriteResult) -> bool>;
+// Показ значения, прочитанного в фоне после построения строки: (текущее, max) или ошибка
+type DeferredRead = Box<dyn Fn(Result<(u16, u16), String>)>;
+
+thread_local! {
+    // Слушатели по (шина, VCP); вызываются в главном цикле GTK
+    static WRITE_LISTENERS: RefCell<HashMap<(u8, u8), Vec<WriteListener>>> = RefCell::new(HashMap::new());
 }
 
-// Универсальная установка VCP значения по коду
-fn ddc_set_vcp(i2c_bus: u8, vcp_code: u8, value: u8) -> Result<(), String> {
-    let device_path = format!("/dev/i2c-{}", i2c_bus);
-    let mut dev = LinuxI2CDevice::new(&device_path, DDC_ADDR)
-        .map_err(|e| format!("Failed to open {}: {}", device_path, e))?;
+static WRITE_RESULTS: OnceLock<async_channel::Sender<WriteResult>> = OnceLock::new();
+
+// Общая очередь записи: на каждый монитор один поток, из серии значений пишется только последнее
+fn ddc_writer() -> &'static DdcWriter {
+    static WRITER: OnceLock<DdcWriter> = OnceLock::new();
+    WRITER.get_or_init(|| {
+        DdcWriter::new(|result| {
+            if let Some(tx) = WRITE_RESULTS.get() {
+                let _ = tx.send_blocking(result);
+            }
+        })
+    })
+}
 
-    // DDC Set VCP request: [source_addr, length, type, vcp_code, hi_byte, lo_byte]
-    let request = [0x51, 0x04, 0x03, vcp_code, 0x00, value];
-    let checksum = 0x6E ^ request.iter().fold(0u8, |acc, &x| acc ^ x);
-    let full_request = [request[0], request[1], request[2], request[3], request[4], request[5], checksum];
+// Запись VCP через очередь
+fn queue_vcp_write(i2c_bus: u8, vcp_code: u8, value: u16) {
+    ddc_writer().set_vcp(i2c_bus, vcp_code, value);
+}
 
-    dev.write(&full_request)
-        .map_err(|e| format!("Failed to write DDC set request: {}", e))?;
-    Ok(())
+// Запись яркости выбранным методом через очередь (ключ — VCP яркости, чтобы DDC и XRandR не расходились)
+fn queue_brightness_write(display: &DisplayInfo, value: u8, pref: Option<ControlMethodPref>) {
+    let display = display.clone();
+    ddc_writer().submit(
+        display.i2c_bus,
+        VCP_BRIGHTNESS,
+        value as u16,
+        {
+            let display = display.clone();
+            Arc::new(move |v| set_brightness_with_pref(&display, v as u8, pref).map_err(anyhow::Error::msg))
+        },
+        Some(Arc::new(move || get_brightness_with_pref(&display, pref).map(u16::from).map_err(anyhow::Error::msg))),
+    );
+}
+
+fn on_write_result(i2c_bus: u8, vcp_code: u8, listener: impl Fn(&WriteResult) -> bool + 'static) {
+    WRITE_LISTENERS.with(|l| l.borrow_mut().entry((i2c_bus, vcp_code)).or_default().push(Box::new(listener)));
+}
+
+// ---------- Live readback ----------
+
+// Пауза после последней записи, прежде чем опрос снова читает параметр (не мешаем перетаскиванию)
+const POLL_QUIET: Duration = Duration::from_secs(2);
+// Время на применение заводских значений после команды сброса, прежде чем параметры перечитываются
+const RESET_SETTLE: Duration = Duration::from_millis(1500);
+
+// Параметр, значение которого периодически перечитывается, пока виден его виджет
+struct PolledControl {
+    bus: u8,
+    code: u8,
+    widget: glib::WeakRef<gtk::Widget>,
+    apply: Box<dyn Fn(u16)>,
+}
+
+enum PollEvent {
+    Value(u8, u8, u16),
+    RoundDone,
+}
+
+thread_local! {
+    static POLLED_CONTROLS: RefCell<Vec<PolledControl>> = const { RefCell::new(Vec::new()) };
+}
+
+fn poll_control(bus: u8, code: u8, widget: &impl IsA<gtk::Widget>, apply: impl Fn(u16) + 'static) {
+    let widget = widget.as_ref().downgrade();
+    POLLED_CONTROLS.with(|c| c.borrow_mut().push(PolledControl { bus, code, widget, apply: Box::new(apply) }));
+}
+
+// Периодический опрос видимых параметров: изменения кнопками монитора появляются в панели
+fn spawn_control_poller(interval_ms: u32) {
+    if interval_ms == 0 {
+        return;
+    }
+    let (tx, rx) = async_channel::unbounded::<PollEvent>();
+    let round_active = Rc::new(Cell::new(false));
+
+    let round_active_for_timer = round_active.clone();
+    glib::timeout_add_local(Duration::from_millis(interval_ms as u64), move || {
+        if round_active_for_timer.get() {
+            return glib::ControlFlow::Continue;
+        }
+        let mut targets: Vec<(u8, u8)> = POLLED_CONTROLS.with(|c| {
+            let mut controls = c.borrow_mut();
+            controls.retain(|p| p.widget.upgrade().is_some());
+            controls
+                .iter()
+                .filter(|p| p.widget.upgrade().is_some_and(|w| w.is_mapped()))
+                .filter(|p| ddc_writer().is_settled(p.bus, p.code, POLL_QUIET))
+                .map(|p| (p.bus, p.code))
+                .collect()
+        });
+        targets.sort_unstable();
+        targets.dedup();
+        if targets.is_empty() {
+            return glib::ControlFlow::Continue;
+        }
+        round_active_for_timer.set(true);
+        let tx = tx.clone();
+        thread::spawn(move || {
+            for (bus, code) in targets {
+                if let Ok((cur, _max)) = ddc_i2c::get_vcp(bus, code) {
+                    let _ = tx.send_blocking(PollEvent::Value(bus, code, cur));
+                }
+            }
+            let _ = tx.send_blocking(PollEvent::RoundDone);
+        });
+        glib::ControlFlow::Continue
+    });
+
+    glib::spawn_future_local(async move {
+        while let Ok(event) = rx.recv().await {
+            match event {
+                // Пока шло чтение, пользователь мог сдвинуть ползунок — тогда значение устарело
+                PollEvent::Value(bus, code, value) if ddc_writer().is_settled(bus, code, POLL_QUIET) => {
+                    POLLED_CONTROLS.with(|c| {
+                        for p in c.borrow().iter().filter(|p| p.bus == bus && p.code == code) {
+                            (p.apply)(value);
+                        }
+                    });
+                }
+                PollEvent::Value(..) => {}
+                PollEvent::RoundDone => round_active.set(false),
+            }
+        }
+    });
 }
 
-fn edid_hash_short(edid: &[u8]) -> String {
-    // FNV-1a 32-bit over first 128 bytes
-    let mut hash: u32 = 0x811C9DC5;
-    let prime: u32 = 0x01000193;
-    let len = edid.len().min(128);
-    for &b in &edid[..len] {
-        hash ^= b as u32;
-        hash = hash.wrapping_mul(prime);
+// Доставка результатов записи слушателям; запускается один раз из build_ui
+fn spawn_write_results_loop() {
+    let (tx, rx) = async_channel::unbounded::<WriteResult>();
+    if WRITE_RESULTS.set(tx).is_err() {
+        return;
     }
-    format!("{:08x}", hash)
+    glib::spawn_future_local(async move {
+        while let Ok(result) = rx.recv().await {
+            if let Err(e) = &result.result {
+                println!("Bus {}: write VCP 0x{:02X}={} failed: {}", result.bus, result.code, result.value, e);
+            }
+            persist_audio_value(&result);
+            WRITE_LISTENERS.with(|l| {
+                if let Some(listeners) = l.borrow_mut().get_mut(&(result.bus, result.code)) {
+                    listeners.retain(|listener| listener(&result));
+                }
+            });
+        }
+    });
 }
 
-fn get_displays_cache_path() -> Result<PathBuf, String> {
-    Ok(get_config_dir()?.join("displays_cache.json"))
+// ---------- Change transaction ----------
+
+// Панель подтверждения (главное окно или карточка монитора): все показывают один общий отсчет
+struct ConfirmBar {
+    revealer: glib::WeakRef<gtk::Revealer>,
+    label: glib::WeakRef<Label>,
 }
 
+// Показ значения параметра в виджете (при откате ему передаются исходные значения)
+type ShowSetting = Box<dyn Fn(&Setting, &Value)>;
+// Слушатель подтверждения: получает подтвержденные изменения
+type CommitFn = Box<dyn Fn(&[(Setting, Change)])>;
 
-fn read_displays_cache() -> Result<Option<Vec<VideoCard>>, String> {
-    let path = get_displays_cache_path()?;
-    if !path.exists() {
-        return Ok(None);
+// Виджет, показывающий параметры, пока он жив
+struct SettingView {
+    owner: glib::WeakRef<gtk::Widget>,
+    show: ShowSetting,
+}
+
+struct CommitListener {
+    owner: glib::WeakRef<gtk::Widget>,
+    on_commit: CommitFn,
+}
+
+thread_local! {
+    // Одна транзакция на всю панель: изменения во всех окнах и на всех мониторах подтверждаются и откатываются вместе;
+    // время на подтверждение и политики по типам параметров — из settings.json
+    static TRANSACTION: RefCell<Transaction> = RefCell::new(Transaction::from_settings(&read_settings()));
+    static CONFIRM_BARS: RefCell<Vec<ConfirmBar>> = const { RefCell::new(Vec::new()) };
+    static SETTING_VIEWS: RefCell<Vec<SettingView>> = const { RefCell::new(Vec::new()) };
+    static COMMIT_LISTENERS: RefCell<Vec<CommitListener>> = const { RefCell::new(Vec::new()) };
+    static CONFIRM_TICK: RefCell<Option<glib::SourceId>> = const { RefCell::new(None) };
+}
+
+// Панель "Подтвердить/Отменить" с отсчетом общей транзакции
+fn confirm_bar() -> gtk::Revealer {
+    let bar = GtkBox::new(Orientation::Horizontal, 12);
+    bar.add_css_class("confirm-bar");
+    let confirm_btn = Button::with_label("Подтвердить изменения");
+    confirm_btn.add_css_class("confirm-button");
+    let cancel_btn = Button::with_label("Отменить");
+    cancel_btn.add_css_class("cancel-button");
+    // Кнопки слева, текст таймера справа
+    let spacer = GtkBox::new(Orientation::Horizontal, 0);
+    spacer.set_hexpand(true);
+    let timer_lbl = Label::new(None);
+    timer_lbl.add_css_class("timer-label");
+    timer_lbl.set_xalign(1.0);
+    bar.append(&confirm_btn);
+    bar.append(&cancel_btn);
+    bar.append(&spacer);
+    bar.append(&timer_lbl);
+    confirm_btn.connect_clicked(|_| commit_changes());
+    cancel_btn.connect_clicked(|_| rollback_changes());
+
+    // Выпадает сверху поверх контента, без его смещения
+    let revealer = gtk::Revealer::new();
+    revealer.set_transition_type(gtk::RevealerTransitionType::SlideDown);
+    revealer.set_halign(gtk::Align::Fill);
+    revealer.set_valign(gtk::Align::Start);
+    revealer.set_child(Some(&bar));
+    CONFIRM_BARS.with(|b| b.borrow_mut().push(ConfirmBar { revealer: revealer.downgrade(), label: timer_lbl.downgrade() }));
+    refresh_confirm_bars();
+    revealer
+}
+
+fn register_setting_view(owner: &impl IsA<gtk::Widget>, show: impl Fn(&Setting, &Value) + 'static) {
+    let owner = owner.as_ref().downgrade();
+    SETTING_VIEWS.with(|v| v.borrow_mut().push(SettingView { owner, show: Box::new(show) }));
+}
+
+fn on_commit(owner: &impl IsA<gtk::Widget>, on_commit: impl Fn(&[(Setting, Change)]) + 'static) {
+    let owner = owner.as_ref().downgrade();
+    COMMIT_LISTENERS.with(|l| l.borrow_mut().push(CommitListener { owner, on_commit: Box::new(on_commit) }));
+}
+
+// Изменение параметра: исходное значение запоминается при первом изменении, отсчет начинается заново.
+// Параметры без подтверждения (по умолчанию яркость) подтверждаются сами, когда их перестают менять
+fn record_change(setting: Setting, original: impl FnOnce() -> Option<Value>, current: Option<Value>) {
+    TRANSACTION.with(|t| t.borrow_mut().record(setting, original, current, Instant::now()));
+    refresh_confirm_bars();
+    CONFIRM_TICK.with(|tick| {
+        if tick.borrow().is_some() {
+            return;
+        }
+        *tick.borrow_mut() = Some(glib::timeout_add_local(Duration::from_millis(250), || {
+            let (resolved, pending) = TRANSACTION.with(|t| {
+                let mut t = t.borrow_mut();
+                (t.expire(Instant::now()), t.is_pending())
+            });
+            if !resolved.rolled_back.is_empty() {
+                println!("Confirmation timeout - restoring original values");
+            }
+            finish_changes(resolved);
+            if pending {
+                glib::ControlFlow::Continue
+            } else {
+                CONFIRM_TICK.with(|tick| tick.borrow_mut().take());
+                glib::ControlFlow::Break
+            }
+        }));
+    });
+}
+
+// Изменение VCP на карточке монитора: исходное значение читается с монитора до записи,
+// целиком (16 бит): откат должен вернуть именно его
+fn change_vcp(bus: u8, code: u8, value: u16) {
+    record_change(
+        Setting::Vcp { bus, code },
+        || ddc_i2c::get_vcp(bus, code).ok().map(|(cur, _max)| Value::Raw(cur)),
+        Some(Value::Raw(value)),
+    );
+    queue_vcp_write(bus, code, value);
+}
+
+// Выключение и ожидание всегда требуют подтверждения, иначе монитор включается обратно.
+// Включение само по себе не откатывается: оно только снимает неподтвержденное выключение.
+// current — режим, прочитанный заранее; без него читается с монитора
+fn record_power_change(bus: u8, current: Option<u16>, mode: PowerMode) {
+    let setting = Setting::Vcp { bus, code: VCP_POWER_MODE };
+    if mode == PowerMode::On && !TRANSACTION.with(|t| t.borrow().contains(&setting)) {
+        return;
+    }
+    record_change(
+        setting,
+        || current.or_else(|| ddc_i2c::get_vcp(bus, VCP_POWER_MODE).ok().map(|(cur, _)| cur)).map(Value::Raw),
+        Some(Value::Raw(mode.vcp_value())),
+    );
+}
+
+// Параметры, которые больше не нужно откатывать (например, после заводского сброса)
+fn forget_changes(forget: impl FnMut(&Setting) -> bool) {
+    TRANSACTION.with(|t| t.borrow_mut().forget(forget));
+    refresh_confirm_bars();
+}
+
+fn commit_changes() {
+    stop_confirm_tick();
+    let committed = TRANSACTION.with(|t| t.borrow_mut().commit());
+    finish_changes(Resolved { committed, rolled_back: Vec::new() });
+}
+
+fn rollback_changes() {
+    stop_confirm_tick();
+    let rolled_back = TRANSACTION.with(|t| t.borrow_mut().rollback());
+    finish_changes(Resolved { committed: Vec::new(), rolled_back });
+}
+
+// Выход из панели посреди отсчета: неподтвержденные изменения откатываются, остальные подтверждаются.
+// Главного цикла уже нет, поэтому записи дожидаемся здесь
+fn settle_changes_on_exit() {
+    let resolved = TRANSACTION.with(|t| t.borrow_mut().settle());
+    notify_committed(&resolved.committed);
+    if resolved.rolled_back.is_empty() {
+        return;
+    }
+    println!("Panel closed before confirmation - restoring {} original value(s)", resolved.rolled_back.len());
+    let xrandr = apply_settings(&resolved.rolled_back);
+    let buses: HashSet<u8> = resolved.rolled_back.iter()
+        .filter_map(|(s, _)| match s { Setting::Vcp { bus, .. } => Some(*bus), _ => None })
+        .collect();
+    for bus in buses {
+        ddc_writer().wait_idle(bus, Duration::from_secs(5));
+    }
+    if let Some(handle) = xrandr {
+        let _ = handle.join();
     }
-    let data = fs::read_to_string(&path).map_err(|e| format!("Failed to read cache {:?}: {}", path, e))?;
-    let cards: Vec<VideoCard> = serde_json::from_str(&data).map_err(|e| format!("Failed to parse cache JSON: {}", e))?;
-    Ok(Some(cards))
 }
 
-fn write_displays_cache(cards: &[VideoCard]) -> Result<(), String> {
-    let path = get_displays_cache_path()?;
-    let data = serde_json::to_string_pretty(cards).map_err(|e| format!("Failed to serialize cache JSON: {}", e))?;
-    fs::write(&path, data).map_err(|e| format!("Failed to write cache {:?}: {}", path, e))?;
-    Ok(())
+// Применение результата: откат пишется в мониторы и показывается в виджетах, подтвержденное получают слушатели
+fn finish_changes(resolved: Resolved) {
+    refresh_confirm_bars();
+    if !resolved.rolled_back.is_empty() {
+        println!("Rolling back {} change(s)", resolved.rolled_back.len());
+        apply_and_show(&resolved.rolled_back);
+    }
+    notify_committed(&resolved.committed);
 }
 
-#[derive(Default, Serialize, Deserialize)]
-struct UiSettings {
-    control_prefs: HashMap<u8, String>,
-    last_values_ddc: HashMap<u8, u8>,
-    last_values_xrandr: HashMap<u8, u8>,
+// Запись значений в мониторы и их показ в виджетах (откат, переход по истории)
+fn apply_and_show(values: &[(Setting, Value)]) {
+    apply_settings(values);
+    // Виджеты обновляются вне borrow: их обработчики могут регистрировать новые
+    let mut views = SETTING_VIEWS.with(|v| v.take());
+    views.retain(|v| v.owner.upgrade().is_some());
+    for (setting, value) in values {
+        for v in &views {
+            (v.show)(setting, value);
+        }
+    }
+    SETTING_VIEWS.with(|v| {
+        let added = v.replace(views);
+        v.borrow_mut().extend(added);
+    });
+}
+
+fn notify_committed(committed: &[(Setting, Change)]) {
+    if committed.is_empty() {
+        return;
+    }
+    record_history(committed);
+    let mut listeners = COMMIT_LISTENERS.with(|l| l.take());
+    listeners.retain(|l| l.owner.upgrade().is_some());
+    for l in &listeners {
+        (l.on_commit)(committed);
+    }
+    COMMIT_LISTENERS.with(|l| {
+        let added = l.replace(listeners);
+        l.borrow_mut().extend(added);
+    });
+}
+
+fn stop_confirm_tick() {
+    if let Some(id) = CONFIRM_TICK.with(|tick| tick.borrow_mut().take()) {
+        id.remove();
+    }
+}
+
+fn refresh_confirm_bars() {
+    // Панель видна, только пока есть изменения, ждущие подтверждения
+    let remaining = TRANSACTION.with(|t| t.borrow().remaining(Instant::now()));
+    CONFIRM_BARS.with(|bars| {
+        bars.borrow_mut().retain(|bar| {
+            let (Some(revealer), Some(label)) = (bar.revealer.upgrade(), bar.label.upgrade()) else { return false };
+            if let Some(left) = remaining {
+                label.set_text(&format!("Изменения будут отменены через {} сек", left.as_secs_f32().ceil() as u64));
+            }
+            if revealer.reveals_child() != remaining.is_some() {
+                revealer.set_reveal_child(remaining.is_some());
+            }
+            true
+        });
+    });
 }
 
-fn get_settings_path() -> Result<PathBuf, String> { Ok(get_config_dir()?.join("settings.json")) }
+// ---------- History ----------
+
+thread_local! {
+    // Подтвержденные изменения всех сеансов (history.json); меняется только через update_history
+    static HISTORY: RefCell<History> = RefCell::new(read_history());
+    // Кнопки «Отменить/Повторить» в заголовке: доступность зависит от положения в истории
+    static HISTORY_BUTTONS: RefCell<Vec<(glib::WeakRef<Button>, glib::WeakRef<Button>)>> = const { RefCell::new(Vec::new()) };
+}
 
-fn read_settings() -> UiSettings {
-    if let Ok(path) = get_settings_path() {
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(cfg) = serde_json::from_str::<UiSettings>(&content) { return cfg; }
+// Подтвержденная транзакция становится шагом истории
+fn record_history(committed: &[(Setting, Change)]) {
+    let displays = cache::cached_displays();
+    let entries: Vec<history::Entry> = committed
+        .iter()
+        .filter_map(|(setting, change)| {
+            let new = change.current.clone()?;
+            Some(history::Entry {
+                monitor_key: history::display_for(&displays, setting).map(monitor_key).unwrap_or_default(),
+                monitor: monitor_label(&displays, setting),
+                setting: setting.clone(),
+                old: change.original.clone(),
+                new,
+            })
+        })
+        .collect();
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    // Файл истории, который не читается, не перезаписывается: шаг тогда теряется
+    match update_history(|h| {
+        h.push(history::Step { time, entries });
+        h.clone()
+    }) {
+        Ok(h) => {
+            HISTORY.with(|cached| cached.replace(h));
+            refresh_history_buttons();
         }
+        Err(e) => println!("Failed to save history: {e:#}"),
     }
-    UiSettings::default()
 }
 
-fn write_settings(settings: &UiSettings) {
-    if let Ok(path) = get_settings_path() {
-        if let Ok(serialized) = serde_json::to_string_pretty(settings) {
-            let _ = fs::write(path, serialized);
+// Переход по истории. Сначала разрешается текущая транзакция: автоподтверждаемое попадает в историю,
+// неподтвержденное откатывается — тогда отмена на этом и заканчивается.
+// Записи относятся к мониторам, подключенным сейчас (по кэшу дисплеев); отключенные пропускаются
+fn navigate_history(undo: bool, go: impl FnOnce(&mut History, &[DisplayInfo]) -> Option<Vec<(Setting, Value)>>) {
+    stop_confirm_tick();
+    let resolved = TRANSACTION.with(|t| t.borrow_mut().settle());
+    let rolled_back = !resolved.rolled_back.is_empty();
+    finish_changes(resolved);
+    if undo && rolled_back {
+        return;
+    }
+    let displays = cache::cached_displays();
+    let values = match update_history(|h| (go(h, &displays), h.clone())) {
+        Ok((values, h)) => {
+            HISTORY.with(|cached| cached.replace(h));
+            values
+        }
+        Err(e) => {
+            println!("Failed to update history: {e:#}");
+            None
         }
+    };
+    refresh_history_buttons();
+    if let Some(values) = values {
+        println!("History: applying {} value(s)", values.len());
+        apply_and_show(&values);
     }
 }
 
-fn save_settings_from_state(control_prefs: &RefCell<HashMap<u8, ControlMethodPref>>, slider_refs: &RefCell<SliderRefs>) {
-    let prefs_map = control_prefs.borrow();
-    let refs = slider_refs.borrow();
-    let mut settings = UiSettings::default();
-    for (bus, pref) in prefs_map.iter() {
-        settings.control_prefs.insert(*bus, match pref { ControlMethodPref::Ddc => "ddc".into(), ControlMethodPref::Xrandr => "xrandr".into() });
+fn refresh_history_buttons() {
+    let (can_undo, can_redo) = HISTORY.with(|h| (h.borrow().can_undo(), h.borrow().can_redo()));
+    HISTORY_BUTTONS.with(|b| {
+        b.borrow_mut().retain(|(undo, redo)| {
+            let (Some(undo), Some(redo)) = (undo.upgrade(), redo.upgrade()) else { return false };
+            undo.set_sensitive(can_undo);
+            redo.set_sensitive(can_redo);
+            true
+        });
+    });
+}
+
+// Монитор параметра для записи в историю: имя и выход/шина на момент изменения
+fn monitor_label(displays: &[DisplayInfo], setting: &Setting) -> String {
+    let fallback = match setting {
+        Setting::Vcp { bus, .. } => format!("i2c-{}", bus),
+        Setting::XrandrBrightness { output } | Setting::XrandrGamma { output } | Setting::Layout { output } => output.clone(),
+    };
+    match history::display_for(displays, setting) {
+        Some(d) => format!("{} ({})", d.name, d.xrandr_output.clone().unwrap_or(fallback)),
+        None => fallback,
+    }
+}
+
+fn setting_label(setting: &Setting) -> String {
+    match setting {
+        Setting::Vcp { code, .. } => vcp::name(*code),
+        Setting::XrandrBrightness { .. } => "Яркость (XRandR)".to_string(),
+        Setting::XrandrGamma { .. } => "Гамма (XRandR)".to_string(),
+        Setting::Layout { .. } => "Раскладка".to_string(),
+    }
+}
+
+fn setting_value_label(setting: &Setting, value: &Value) -> String {
+    match (setting, value) {
+        (Setting::Vcp { code, .. }, Value::Raw(raw)) if vcp::kind(*code, &[]) != vcp::VcpKind::Continuous => vcp_value_label(*code, *raw as u8),
+        (_, Value::Raw(raw)) => raw.to_string(),
+        (_, Value::Percent(p)) => format!("{}%", p),
+        (_, Value::Gamma(r, g, b)) => format!("{:.2}:{:.2}:{:.2}", r, g, b),
+        (_, Value::Args(args)) => args.join(" "),
+    }
+}
+
+// Кнопки «Отменить», «Повторить» и «История» для заголовка главного окна
+fn create_history_buttons() -> GtkBox {
+    let bar = GtkBox::new(Orientation::Horizontal, 0);
+    bar.add_css_class("linked");
+    let undo_btn = Button::from_icon_name("edit-undo-symbolic");
+    undo_btn.set_tooltip_text(Some("Отменить последнее изменение"));
+    let redo_btn = Button::from_icon_name("edit-redo-symbolic");
+    redo_btn.set_tooltip_text(Some("Повторить отмененное изменение"));
+    undo_btn.connect_clicked(|_| navigate_history(true, History::undo));
+    redo_btn.connect_clicked(|_| navigate_history(false, History::redo));
+    HISTORY_BUTTONS.with(|b| b.borrow_mut().push((undo_btn.downgrade(), redo_btn.downgrade())));
+    refresh_history_buttons();
+
+    let history_btn = gtk::MenuButton::new();
+    history_btn.set_label("История");
+    history_btn.set_tooltip_text(Some("Примененные изменения мониторов"));
+    let popover = gtk::Popover::new();
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled.set_propagate_natural_height(true);
+    scrolled.set_max_content_height(420);
+    scrolled.set_min_content_width(420);
+    let list = GtkBox::new(Orientation::Vertical, 8);
+    scrolled.set_child(Some(&list));
+    popover.set_child(Some(&scrolled));
+    history_btn.set_popover(Some(&popover));
+
+    // Список строится при каждом открытии: новые шаги сверху, отмененные — приглушены
+    popover.connect_show(move |popover| {
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        // С диска: шаги могли добавить трей или другая панель
+        let history = read_history();
+        HISTORY.with(|h| h.replace(history.clone()));
+        refresh_history_buttons();
+        if history.steps.is_empty() {
+            list.append(&Label::new(Some("Изменений пока нет")));
+            return;
+        }
+        let popover = popover.downgrade();
+        let go_to_button = |cursor: usize| {
+            let btn = Button::with_label("Вернуться сюда");
+            btn.add_css_class("flat");
+            btn.set_halign(gtk::Align::End);
+            btn.set_sensitive(cursor != history.cursor);
+            let popover = popover.clone();
+            btn.connect_clicked(move |_| {
+                if let Some(p) = popover.upgrade() { p.popdown(); }
+                navigate_history(false, move |h, displays| Some(h.go_to(cursor, displays)));
+            });
+            btn
+        };
+        for (i, step) in history.steps.iter().enumerate().rev() {
+            let row = GtkBox::new(Orientation::Vertical, 2);
+            if i >= history.cursor {
+                row.add_css_class("dim-label");
+            }
+            let head = GtkBox::new(Orientation::Horizontal, 8);
+            let time = glib::DateTime::from_unix_local(step.time as i64)
+                .and_then(|t| t.format("%d.%m.%Y %H:%M:%S"))
+                .map(|t| t.to_string())
+                .unwrap_or_else(|_| step.time.to_string());
+            let time_lbl = Label::new(None);
+            time_lbl.set_markup(&format!("<b>{}</b>", glib::markup_escape_text(&time)));
+            time_lbl.set_xalign(0.0);
+            time_lbl.set_hexpand(true);
+            head.append(&time_lbl);
+            head.append(&go_to_button(i + 1));
+            row.append(&head);
+            for e in &step.entries {
+                let line = Label::new(Some(&format!(
+                    "{}: {} {} → {}",
+                    e.monitor,
+                    setting_label(&e.setting),
+                    setting_value_label(&e.setting, &e.old),
+                    setting_value_label(&e.setting, &e.new),
+                )));
+                line.set_xalign(0.0);
+                line.set_wrap(true);
+                row.append(&line);
+            }
+            list.append(&row);
+        }
+        let start = GtkBox::new(Orientation::Horizontal, 8);
+        let start_lbl = Label::new(Some("До первого изменения"));
+        start_lbl.set_xalign(0.0);
+        start_lbl.set_hexpand(true);
+        start.append(&start_lbl);
+        start.append(&go_to_button(0));
+        list.append(&start);
+    });
+
+    bar.append(&undo_btn);
+    bar.append(&redo_btn);
+    bar.append(&history_btn);
+    bar
+}
+
+// Запись значений транзакции: VCP — через очередь монитора, XRandR — по порядку в одном потоке
+fn apply_settings(values: &[(Setting, Value)]) -> Option<thread::JoinHandle<()>> {
+    let mut xrandr_values = Vec::new();
+    for (setting, value) in values {
+        match (setting, value) {
+            (Setting::Vcp { bus, code }, Value::Raw(raw)) => queue_vcp_write(*bus, *code, *raw),
+            (Setting::Vcp { .. }, _) => println!("Unexpected value {:?} for {:?}", value, setting),
+            _ => xrandr_values.push((setting.clone(), value.clone())),
+        }
+    }
+    if xrandr_values.is_empty() {
+        return None;
     }
-    settings.last_values_ddc = refs.last_values_ddc.clone();
-    settings.last_values_xrandr = refs.last_values_xrandr.clone();
-    thread::spawn(move || { write_settings(&settings); });
+    Some(thread::spawn(move || {
+        for (setting, value) in xrandr_values {
+            let result = match (&setting, &value) {
+                (Setting::XrandrBrightness { output }, Value::Percent(p)) => xrandr_set_brightness(output, *p),
+                (Setting::XrandrGamma { output }, Value::Gamma(r, g, b)) => {
+                    // xrandr пересчитывает гамму вместе с яркостью: текущую яркость передаем заново
+                    let brightness = xrandr_get_brightness(output).unwrap_or(100);
+                    mondis_x11::xrandr::set_gamma(output, (*r, *g, *b), brightness).map_err(|e| format!("{e:#}"))
+                }
+                (Setting::Layout { output }, Value::Args(args)) => Command::new("xrandr")
+                    .arg("--output").arg(output)
+                    .args(args)
+                    .status()
+                    .map_err(|e| format!("Failed to run xrandr: {}", e))
+                    .and_then(|s| if s.success() { Ok(()) } else { Err(format!("xrandr exited with status: {:?}", s.code())) }),
+                _ => Err(format!("unexpected value {:?}", value)),
+            };
+            if let Err(e) = result {
+                println!("Failed to restore {:?}: {}", setting, e);
+            }
+        }
+    }))
+}
+
+// Последние громкость, отключение звука и аудиовход сохраняются в настройках монитора, как и в трее
+// (после окончания серии записей: при перетаскивании файл не переписывается на каждое значение)
+fn persist_audio_value(result: &WriteResult) {
+    if result.result.is_err() || !ddc_writer().is_settled(result.bus, result.code, Duration::ZERO) {
+        return;
+    }
+    let value = result.readback.unwrap_or(result.value);
+    let (bus, code) = (result.bus, result.code);
+    // Код аудиовхода задается базой особенностей; шина уже разобрана записью, DDC здесь не читается
+    let is_audio_input = || timing::bus_quirks(bus).audio_input.is_some_and(|a| a.code == code);
+    if code != VCP_AUDIO_VOLUME && code != VCP_AUDIO_MUTE && !is_audio_input() {
+        return;
+    }
+    let muted = audio::is_muted(value);
+    if code == VCP_AUDIO_MUTE && muted.is_none() {
+        return;
+    }
+    thread::spawn(move || {
+        let Some(display) = cached_display(bus) else { return };
+        let saved = update_settings(|settings| {
+            let monitor = settings.monitor_mut(&display);
+            match code {
+                VCP_AUDIO_VOLUME => monitor.volume = Some(value.min(100) as u8),
+                VCP_AUDIO_MUTE => monitor.muted = muted,
+                _ => monitor.audio_input = Some(value as u8),
+            }
+        });
+        if let Err(e) = saved { println!("Failed to save settings: {e:#}"); }
+    });
+}
+
+fn read_displays_cache() -> Result<Option<Vec<VideoCard>>, String> {
+    cache::read_displays_cache().map_err(|e| format!("{e:#}"))
+}
+
+fn write_displays_cache(cards: &[VideoCard]) -> Result<(), String> {
+    cache::write_displays_cache(cards).map_err(|e| format!("{e:#}"))
+}
+
+// Ошибка чтения settings.json: панель работает с настройками по умолчанию, файл не трогается
+fn show_settings_error(parent: &ApplicationWindow, error: &str) {
+    let dialog = gtk::MessageDialog::builder()
+        .modal(true)
+        .transient_for(parent)
+        .message_type(gtk::MessageType::Error)
+        .text("Не удалось загрузить настройки")
+        .secondary_text(format!("{}\n\nИспользуются настройки по умолчанию. Изменения не будут сохраняться, пока файл не исправлен.", error))
+        .buttons(gtk::ButtonsType::Close)
+        .build();
+    dialog.connect_response(|dialog, _| dialog.close());
+    // Показываем после появления главного окна
+    glib::idle_add_local_once(move || dialog.present());
+}
+
+// Монитор на шине по кэшу дисплеев: настройки мониторов хранятся по EDID, а не по шине
+fn cached_display(bus: u8) -> Option<DisplayInfo> {
+    cache::cached_displays().into_iter().find(|d| d.i2c_bus == bus)
+}
+
+fn save_settings_from_state(control_prefs: &RefCell<HashMap<u8, ControlMethodPref>>, slider_refs: &RefCell<SliderRefs>) {
+    let prefs = control_prefs.borrow().clone();
+    let (last_ddc, last_xrandr) = {
+        let refs = slider_refs.borrow();
+        (refs.last_values_ddc.clone(), refs.last_values_xrandr.clone())
+    };
+    // Запись сериализуется в mondis-core: поля, которые панель не редактирует (горячие клавиши и т.п.), сохраняются
+    thread::spawn(move || {
+        let saved = update_settings(|settings| {
+            for display in cache::cached_displays() {
+                let bus = display.i2c_bus;
+                let monitor = settings.monitor_mut(&display);
+                if let Some(pref) = prefs.get(&bus) {
+                    monitor.control = Some(match pref { ControlMethodPref::Ddc => ControlMethod::Ddc, ControlMethodPref::Xrandr => ControlMethod::Xrandr });
+                }
+                if let Some(&v) = last_ddc.get(&bus) { monitor.brightness_ddc = Some(v); }
+                if let Some(&v) = last_xrandr.get(&bus) { monitor.brightness_xrandr = Some(v); }
+            }
+        });
+        if let Err(e) = saved { println!("Failed to save settings: {e:#}"); }
+    });
 }
 
 fn setup_styles() {
@@ -291,32 +940,12 @@ fn main() -> Result<()> {
     app.connect_activate(move |app| {
         build_ui(app);
     });
+    app.connect_shutdown(|_| settle_changes_on_exit());
 
     app.run();
     Ok(())
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct DisplayInfo { 
-    i2c_bus: u8,
-    name: String,
-    manufacturer: Option<String>,
-    model: Option<String>,
-    serial: Option<String>,
-    connector: Option<String>,
-    supports_ddc: bool,
-    xrandr_output: Option<String>,
-    edid_hash: Option<String>,
-    card_name: Option<String>,
-    port_name: Option<String>,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct VideoCard {
-    name: String,
-    displays: Vec<DisplayInfo>,
-}
-
 #[derive(Clone, Debug)]
 struct MonitorDetails {
     // EDID информация
@@ -340,45 +969,45 @@ struct MonitorDetails {
     capabilities_string: Option<String>, // полная строка capabilities
     
     // Текущие настройки
-    current_brightness: Option<u8>,
-    current_contrast: Option<u8>,
+    current_brightness: Option<u16>,
+    current_contrast: Option<u16>,
     current_color_temp: Option<String>,
     current_input_source: Option<String>,
-    current_volume: Option<u8>,
+    current_input: Option<u8>,
+    input_values: Vec<u8>, // значения 0x60 из capabilities
+    current_volume: Option<u16>,
     current_power_state: Option<String>,
+    current_power: Option<PowerMode>,
     
     // Дополнительные VCP значения
-    red_gain: Option<u8>,
-    green_gain: Option<u8>,
-    blue_gain: Option<u8>,
-    backlight_control: Option<u8>,
+    red_gain: Option<u16>,
+    green_gain: Option<u16>,
+    blue_gain: Option<u16>,
+    backlight_control: Option<u16>,
     osd_language: Option<String>,
     
     // Технические детали
+    // Монитор, по которому собраны сведения (его настройки хранятся по EDID)
+    display: DisplayInfo,
     i2c_bus: String,
     // Номер I2C шины если известен для управления DDC/CI
     i2c_bus_num: Option<u8>,
     drm_connector: Option<String>,
+    xrandr_output: Option<String>,
     driver: Option<String>,
     pci_path: Option<String>,
     device_path: Option<String>,
     
     // EDID hex dump
     edid_hex: Option<String>,
+    // Разобранный EDID (CEA-861 / DisplayID)
+    edid: Option<Edid>,
     
     // Статистика
     read_errors: u32,
     last_update: Option<String>,
 }
 
-#[derive(Clone, Debug)]
-struct BrightnessState {
-    original_values: HashMap<u8, u8>, // i2c_bus -> brightness value
-    current_values: HashMap<u8, u8>,
-    has_changes: bool,
-    timer_active: bool,
-}
-
 // Структура для хранения ссылок на UI элементы
 struct SliderRefs {
     sliders: HashMap<u8, (Scale, Label)>, // i2c_bus -> (slider, value_label)
@@ -388,56 +1017,6 @@ struct SliderRefs {
     last_values_xrandr: HashMap<u8, u8>,
 }
 
-impl BrightnessState {
-    fn new() -> Self {
-        Self {
-            original_values: HashMap::new(),
-            current_values: HashMap::new(),
-            has_changes: false,
-            timer_active: false,
-        }
-    }
-    
-    fn save_original(&mut self, bus: u8, value: u8) {
-        if !self.original_values.contains_key(&bus) {
-            self.original_values.insert(bus, value);
-        }
-        self.current_values.insert(bus, value);
-    }
-    
-    fn update_current(&mut self, bus: u8, value: u8) {
-        self.current_values.insert(bus, value);
-        if let Some(&original) = self.original_values.get(&bus) {
-            self.has_changes = original != value || self.has_changes_for_other_buses(bus);
-        }
-    }
-    
-    fn has_changes_for_other_buses(&self, exclude_bus: u8) -> bool {
-        for (&bus, &current) in &self.current_values {
-            if bus != exclude_bus {
-                if let Some(&original) = self.original_values.get(&bus) {
-                    if original != current {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
-    }
-    
-    fn reset_to_original(&mut self) {
-        self.current_values = self.original_values.clone();
-        self.has_changes = false;
-        self.timer_active = false;
-    }
-    
-    fn confirm_changes(&mut self) {
-        self.original_values = self.current_values.clone();
-        self.has_changes = false;
-        self.timer_active = false;
-    }
-}
-
 impl SliderRefs {
     fn new() -> Self {
         Self {
@@ -462,12 +1041,6 @@ impl SliderRefs {
         }
     }
     
-    fn restore_all_sliders(&mut self, original_values: &HashMap<u8, u8>) {
-        for (&bus, &value) in original_values {
-            self.update_slider_value(bus, value);
-        }
-    }
-    
     fn is_programmatic_update(&mut self, bus: u8) -> bool {
         if let Some(&is_programmatic) = self.programmatic_update.get(&bus) {
             if is_programmatic {
@@ -494,292 +1067,68 @@ impl SliderRefs {
 }
 
 // DDC/CI constants
-const DDC_ADDR: u16 = 0x37;
-const EDID_ADDR: u16 = 0x50;
-const VCP_BRIGHTNESS: u8 = 0x10;
-
-fn get_profile_path() -> Result<PathBuf, String> {
-    let home = std::env::var("HOME").map_err(|_| "HOME environment variable not set")?;
-    let config_dir = PathBuf::from(home).join(".config").join("mondis");
-    
-    // Создаем директорию если не существует
-    if let Err(e) = fs::create_dir_all(&config_dir) {
-        return Err(format!("Failed to create config directory: {}", e));
-    }
-    
-    Ok(config_dir.join("brightness_profile.xml"))
-}
 
 fn save_brightness_profile(brightness_values: &HashMap<u8, u8>, displays: &[DisplayInfo]) -> Result<(), String> {
-    let profile_path = get_profile_path()?;
-    
-    let mut xml_content = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<mondis_profile>\n");
-    xml_content.push_str("  <brightness_settings>\n");
-    
-    for (&bus, &brightness) in brightness_values {
-        // Найдем информацию о дисплее для этого bus
-        if let Some(display) = displays.iter().find(|d| d.i2c_bus == bus) {
-            xml_content.push_str(&format!(
-                "    <display bus=\"{}\" brightness=\"{}\" name=\"{}\" />\n",
-                bus, brightness, 
-                display.name.replace("&", "&amp;").replace("<", "&lt;").replace(">", "&gt;")
-            ));
-        } else {
-            xml_content.push_str(&format!(
-                "    <display bus=\"{}\" brightness=\"{}\" name=\"Unknown\" />\n",
-                bus, brightness
-            ));
+    update_settings(|settings| {
+        for display in displays {
+            if let Some(&brightness) = brightness_values.get(&display.i2c_bus) {
+                settings.monitor_mut(display).profile_brightness = Some(brightness);
+            }
         }
-    }
-    
-    xml_content.push_str("  </brightness_settings>\n");
-    xml_content.push_str(&format!("  <timestamp>{}</timestamp>\n", 
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs()
-    ));
-    xml_content.push_str("</mondis_profile>\n");
-    
-    fs::write(&profile_path, xml_content)
-        .map_err(|e| format!("Failed to write profile to {:?}: {}", profile_path, e))?;
-    
-    println!("Brightness profile saved to: {:?}", profile_path);
+    })
+    .map_err(|e| format!("{e:#}"))?;
+    println!("Brightness profile saved to settings");
     Ok(())
 }
 
 fn read_edid_directly(i2c_bus: u8) -> Result<Vec<u8>, String> {
-    let device_path = format!("/dev/i2c-{}", i2c_bus);
-    println!("    Opening I2C device: {}", device_path);
-    
-    let mut dev = LinuxI2CDevice::new(&device_path, 0x50)
-        .map_err(|e| format!("Failed to open {} for EDID: {}", device_path, e))?;
-    
-    println!("    Writing EDID offset 0x00...");
-    // Записываем offset 0x00
-    dev.write(&[0x00])
-        .map_err(|e| format!("Failed to write EDID offset: {}", e))?;
-    
-    thread::sleep(Duration::from_millis(10));
-    
-    println!("    Reading 128 bytes of EDID data...");
-    // Читаем EDID (128 байт базовый блок)
-    let mut edid_data = vec![0u8; 128];
-    dev.read(&mut edid_data)
-        .map_err(|e| format!("Failed to read EDID: {}", e))?;
-    
-    println!("    Validating EDID header...");
-    // Проверяем заголовок EDID
-    if edid_data.len() >= 8 && edid_data[0] == 0x00 && edid_data[1] == 0xFF && edid_data[7] == 0x00 {
-        println!("    EDID header is valid");
-        Ok(edid_data)
+    println!("    Reading EDID with extension blocks from /dev/i2c-{}", i2c_bus);
+    let edid_data = mondis_ddc::edid::read_edid(i2c_bus).map_err(|e| format!("Failed to read EDID: {e:#}"))?;
+    println!("    EDID is valid: {} block(s)", edid_data.len() / 128);
+    Ok(edid_data)
+}
+
+fn aspect_ratio_label(ratio: f32) -> String {
+    if (ratio - 16.0/9.0).abs() < 0.1 {
+        "16:9".to_string()
+    } else if (ratio - 16.0/10.0).abs() < 0.1 {
+        "16:10".to_string()
+    } else if (ratio - 4.0/3.0).abs() < 0.1 {
+        "4:3".to_string()
+    } else if (ratio - 21.0/9.0).abs() < 0.1 {
+        "21:9".to_string()
     } else {
-        Err(format!("Invalid EDID header: {:02X} {:02X} {:02X} {:02X} {:02X} {:02X} {:02X} {:02X}", 
-            edid_data[0], edid_data[1], edid_data[2], edid_data[3], 
-            edid_data[4], edid_data[5], edid_data[6], edid_data[7]))
+        format!("{:.2}:1", ratio)
     }
 }
 
-fn parse_edid_detailed(edid_data: &[u8]) -> (Option<String>, Option<String>, Option<u16>, Option<u8>, Option<String>, Option<String>, Option<String>, Option<String>) {
-    if edid_data.len() < 128 {
-        return (None, None, None, None, None, None, None, None);
+// Производитель и модель из EDID для списка дисплеев
+fn edid_manufacturer_model(edid_data: &[u8]) -> (String, String) {
+    match Edid::parse(edid_data) {
+        Ok(edid) => (edid.manufacturer.clone(), edid.name().unwrap_or("Monitor").to_string()),
+        Err(_) => ("Unknown".to_string(), "Unknown Monitor".to_string()),
     }
-    
-    // Manufacturer ID (bytes 8-9)
-    let mfg_id = u16::from_be_bytes([edid_data[8], edid_data[9]]);
-    let c1 = (((mfg_id >> 10) & 0x1F) as u8 + 0x40) as char;
-    let c2 = (((mfg_id >> 5) & 0x1F) as u8 + 0x40) as char;
-    let c3 = ((mfg_id & 0x1F) as u8 + 0x40) as char;
-    let manufacturer = if c1.is_ascii_uppercase() && c2.is_ascii_uppercase() && c3.is_ascii_uppercase() {
-        Some(format!("{}{}{}", c1, c2, c3))
-    } else { 
-        None 
-    };
-    
-    // Год производства (byte 17) + 1990
-    let manufacture_year = if edid_data[17] > 0 {
-        Some(edid_data[17] as u16 + 1990)
-    } else {
-        None
-    };
-    
-    // Неделя производства (byte 16)
-    let manufacture_week = if edid_data[16] > 0 && edid_data[16] <= 54 {
-        Some(edid_data[16])
-    } else {
-        None
-    };
-    
-    // EDID версия (bytes 18-19)
-    let edid_version = Some(format!("{}.{}", edid_data[18], edid_data[19]));
-    
-    // Физический размер (bytes 21-22) в см
-    let physical_size = if edid_data[21] > 0 && edid_data[22] > 0 {
-        let width_cm = edid_data[21];
-        let height_cm = edid_data[22];
-        Some(format!("{} x {} см", width_cm, height_cm))
-    } else {
-        None
-    };
-    
-    // Разрешение из первого detailed timing descriptor (bytes 54-71)
-    let resolution = if edid_data.len() >= 72 {
-        let h_active = ((edid_data[58] as u16) << 4) | ((edid_data[62] as u16 >> 4) & 0x0F);
-        let v_active = ((edid_data[61] as u16) << 4) | (edid_data[62] as u16 & 0x0F);
-        if h_active > 0 && v_active > 0 {
-            Some(format!("{} x {}", h_active, v_active))
-        } else {
-            None
-        }
+}
+
+fn read_ddc_capabilities(i2c_bus: u8) -> Result<String, String> {
+    let caps = ddc_i2c::capabilities(i2c_bus).map_err(|e| format!("{e:#}"))?;
+    println!("    Parsed capabilities string ({} chars): {}", caps.len(), caps);
+    if caps.is_empty() {
+        Err("Empty capabilities string".to_string())
     } else {
-        None
-    };
-    
-    // Соотношение сторон
-    let aspect_ratio = if let Some(ref _size) = physical_size {
-        if edid_data[21] > 0 && edid_data[22] > 0 {
-            let ratio = edid_data[21] as f32 / edid_data[22] as f32;
-            if (ratio - 16.0/9.0).abs() < 0.1 {
-                Some("16:9".to_string())
-            } else if (ratio - 16.0/10.0).abs() < 0.1 {
-                Some("16:10".to_string())
-            } else if (ratio - 4.0/3.0).abs() < 0.1 {
-                Some("4:3".to_string())
-            } else if (ratio - 21.0/9.0).abs() < 0.1 {
-                Some("21:9".to_string())
-            } else {
-                Some(format!("{:.2}:1", ratio))
-            }
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-    
-    // Ищем название модели в дескрипторах (bytes 54-125)
-    let mut model_name = None;
-    for i in (54..126).step_by(18) {
-        if edid_data[i] == 0x00 && edid_data[i+1] == 0x00 && edid_data[i+2] == 0x00 && edid_data[i+3] == 0xFC {
-            // Monitor name descriptor
-            let name_bytes = &edid_data[i+5..i+18];
-            let name = String::from_utf8_lossy(name_bytes)
-                .trim_end_matches('\0')
-                .trim_end_matches('\n')
-                .trim()
-                .to_string();
-            if !name.is_empty() {
-                model_name = Some(name);
-                break;
-            }
-        }
-    }
-    
-    (manufacturer, model_name, manufacture_year, manufacture_week, edid_version, physical_size, resolution, aspect_ratio)
-}
-
-fn read_ddc_capabilities(i2c_bus: u8) -> Result<String, String> {
-    let device_path = format!("/dev/i2c-{}", i2c_bus);
-    println!("    Opening DDC device: {}", device_path);
-    
-    let mut dev = LinuxI2CDevice::new(&device_path, 0x37)
-        .map_err(|e| format!("Failed to open {} for DDC: {}", device_path, e))?;
-    
-    // DDC Get Capabilities request: [source_addr, length, type]
-    let request = [0x51, 0x01, 0xF3]; // F3 = Capabilities Request
-    let checksum = 0x6E ^ request.iter().fold(0u8, |acc, &x| acc ^ x);
-    let full_request = [request[0], request[1], request[2], checksum];
-    
-    println!("    Sending capabilities request: {:02X?}", full_request);
-    dev.write(&full_request)
-        .map_err(|e| format!("Failed to write DDC capabilities request: {}", e))?;
-    
-    println!("    Waiting 200ms for response...");
-    thread::sleep(Duration::from_millis(200)); // Больше времени для capabilities
-    
-    // Читаем ответ (простое чтение, не multi-part)
-    let mut response = vec![0u8; 256];
-    dev.read(&mut response)
-        .map_err(|e| format!("Failed to read DDC capabilities response: {}", e))?;
-    
-    println!("    Received {} bytes: {:02X?}", response.len(), &response[..std::cmp::min(16, response.len())]);
-    
-    // Проверяем заголовок ответа
-    if response.len() < 4 || response[0] != 0x6E {
-        return Err(format!("Invalid DDC response header: 0x{:02X}", response.get(0).unwrap_or(&0)));
-    }
-    
-    let data_len = response[1] as usize;
-    if data_len == 0 || data_len + 3 > response.len() {
-        return Err(format!("Invalid capabilities response length: {} (buffer size: {})", data_len, response.len()));
-    }
-    
-    // Извлекаем данные (пропускаем заголовок и checksum)
-    let data_start = 3;
-    let data_end = std::cmp::min(data_start + data_len - 1, response.len() - 1); // -1 для checksum
-    
-    let mut all_data = Vec::new();
-    if data_end > data_start {
-        all_data.extend_from_slice(&response[data_start..data_end]);
-    }
-    
-    if all_data.is_empty() {
-        return Err("No capabilities data received".to_string());
-    }
-    
-    // Очищаем данные от мусора
-    let mut clean_data = Vec::new();
-    for &byte in &all_data {
-        if byte >= 0x20 && byte <= 0x7E { // Печатные ASCII символы
-            clean_data.push(byte);
-        } else if byte == 0x00 {
-            break; // Конец строки
-        }
-    }
-    
-    let caps_string = String::from_utf8_lossy(&clean_data).trim().to_string();
-    println!("    Parsed capabilities string ({} chars): {}", caps_string.len(), caps_string);
-    
-    if caps_string.is_empty() {
-        Err("Empty capabilities string".to_string())
-    } else {
-        Ok(caps_string)
+        Ok(caps)
     }
 }
 
-fn read_vcp_value(i2c_bus: u8, vcp_code: u8) -> Result<(u8, u8), String> {
-    let device_path = format!("/dev/i2c-{}", i2c_bus);
-    let mut dev = LinuxI2CDevice::new(&device_path, 0x37)
-        .map_err(|e| format!("Failed to open {} for DDC: {}", device_path, e))?;
-    
-    // DDC Get VCP request: [source_addr, length, type, vcp_code]
-    let request = [0x51, 0x02, 0x01, vcp_code];
-    let checksum = 0x6E ^ request.iter().fold(0u8, |acc, &x| acc ^ x);
-    let full_request = [request[0], request[1], request[2], request[3], checksum];
-    
-    dev.write(&full_request)
-        .map_err(|e| format!("Failed to write DDC VCP request: {}", e))?;
-    
-    thread::sleep(Duration::from_millis(50));
-    
-    // Читаем ответ: [dest, length, type, result, vcp_code, type_flag, max_hi, max_lo, cur_hi, cur_lo, checksum]
-    let mut response = [0u8; 12];
-    dev.read(&mut response)
-        .map_err(|e| format!("Failed to read DDC VCP response: {}", e))?;
-    
-    if response.len() >= 10 && response[4] == vcp_code {
-        let current_value = ((response[8] as u16) << 8) | (response[9] as u16);
-        let max_value = ((response[6] as u16) << 8) | (response[7] as u16);
-        Ok((current_value as u8, max_value as u8))
-    } else {
-        Err(format!("Invalid VCP response: expected code 0x{:02X}, got 0x{:02X} (response: {:02X?})", 
-            vcp_code, response[4], &response[..std::cmp::min(12, response.len())]))
-    }
+// Текущее значение и максимум VCP как есть (16 бит): у некоторых параметров максимум больше 255,
+// у списочных значение в младшем байте
+fn read_vcp_value(i2c_bus: u8, vcp_code: u8) -> Result<(u16, u16), String> {
+    ddc_i2c::get_vcp(i2c_bus, vcp_code).map_err(|e| format!("{e:#}"))
 }
 
 fn get_monitor_details(display: &DisplayInfo) -> Result<MonitorDetails, String> {
     let mut details = MonitorDetails {
-        manufacturer: display.manufacturer.clone().unwrap_or_default(),
+        manufacturer: display.manufacturer.as_deref().map(pnp::display_name).unwrap_or_default(),
         model: display.model.clone().unwrap_or_default(),
         serial_number: display.serial.clone(),
         manufacture_year: None,
@@ -799,20 +1148,26 @@ fn get_monitor_details(display: &DisplayInfo) -> Result<MonitorDetails, String>
         current_contrast: None,
         current_color_temp: None,
         current_input_source: None,
+        current_input: None,
+        input_values: Vec::new(),
         current_volume: None,
         current_power_state: None,
+        current_power: None,
         red_gain: None,
         green_gain: None,
         blue_gain: None,
         backlight_control: None,
         osd_language: None,
+        display: display.clone(),
         i2c_bus: format!("/dev/i2c-{}", display.i2c_bus),
         i2c_bus_num: Some(display.i2c_bus),
         drm_connector: display.connector.clone(),
+        xrandr_output: display.xrandr_output.clone(),
         driver: None,
         pci_path: None,
         device_path: None,
         edid_hex: None,
+        edid: None,
         read_errors: 0,
         last_update: Some(chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
     };
@@ -830,72 +1185,54 @@ fn get_monitor_details(display: &DisplayInfo) -> Result<MonitorDetails, String>
         
             // Парсим детальную информацию из EDID
             println!("Step 2: Parsing EDID data...");
-            let (mfg, model, year, week, version, physical_size, resolution, aspect_ratio) = parse_edid_detailed(&edid_data);
-            
-            println!("Parsed EDID info:");
-            if let Some(ref mfg) = mfg {
-                println!("  - Manufacturer: {}", mfg);
-                details.manufacturer = mfg.clone();
-            } else {
-                println!("  - Manufacturer: Not found");
-            }
-            
-            if let Some(ref model) = model {
-                println!("  - Model: {}", model);
-                details.model = model.clone();
-            } else {
-                println!("  - Model: Not found");
-            }
-            
-            if let Some(year) = year {
-                println!("  - Year: {}", year);
-                details.manufacture_year = Some(year);
-            } else {
-                println!("  - Year: Not found");
-            }
-            
-            if let Some(week) = week {
-                println!("  - Week: {}", week);
-                details.manufacture_week = Some(week);
-            } else {
-                println!("  - Week: Not found");
-            }
-            
-            if let Some(ref version) = version {
-                println!("  - EDID Version: {}", version);
-                details.edid_version = Some(version.clone());
-            } else {
-                println!("  - EDID Version: Not found");
-                details.edid_version = version;
-            }
-            
-            if let Some(ref size) = physical_size {
-                println!("  - Physical Size: {}", size);
-                details.physical_size = Some(size.clone());
-            }
-            
-            if let Some(ref res) = resolution {
-                println!("  - Resolution: {}", res);
-                details.resolution = Some(res.clone());
-            }
-            
-            if let Some(ref ratio) = aspect_ratio {
-                println!("  - Aspect Ratio: {}", ratio);
-                details.aspect_ratio = Some(ratio.clone());
-            }
-        
-            // Определяем тип входа из EDID
-            if edid_data.len() > 20 {
-                let video_input = edid_data[20];
-                let input_type = if video_input & 0x80 != 0 {
-                    format!("Digital Input (0x{:02X})", video_input)
-                } else {
-                    format!("Analog Input (0x{:02X})", video_input)
-                };
-                println!("  - Video Input: {}", input_type);
-                details.video_input = Some(input_type);
+            match Edid::parse(&edid_data) {
+                Ok(edid) => {
+                    println!("Parsed EDID info:");
+                    details.manufacturer = format!("{} ({})", edid.manufacturer_name(), edid.manufacturer);
+                    println!("  - Manufacturer: {}", details.manufacturer);
+
+                    if let Some(name) = edid.name() {
+                        println!("  - Model: {}", name);
+                        details.model = name.to_string();
+                    } else {
+                        println!("  - Model: Not found");
+                    }
+
+                    details.manufacture_year = edid.manufacture_year.or(edid.model_year);
+                    details.manufacture_week = edid.manufacture_week;
+                    println!("  - Year: {:?}, Week: {:?}", details.manufacture_year, details.manufacture_week);
+
+                    let version = format!("{}.{}", edid.version, edid.revision);
+                    println!("  - EDID Version: {}", version);
+                    details.edid_version = Some(version);
+
+                    if let Some((w, h)) = edid.screen_size_cm {
+                        details.physical_size = Some(format!("{} x {} см", w, h));
+                        details.aspect_ratio = Some(aspect_ratio_label(w as f32 / h as f32));
+                    }
+
+                    if let Some(t) = edid.preferred_timing() {
+                        details.resolution = Some(format!("{} x {} @ {:.0} Гц", t.h_active, t.v_active, t.refresh_hz()));
+                    }
+
+                    let input_type = match edid.input {
+                        VideoInput::Digital { bit_depth, interface } => match bit_depth {
+                            Some(bits) => format!("Digital, {:?}, {} бит", interface, bits),
+                            None => format!("Digital, {:?}", interface),
+                        },
+                        VideoInput::Analog { .. } => "Analog".to_string(),
+                    };
+                    println!("  - Video Input: {}", input_type);
+                    details.video_input = Some(input_type);
+
+                    if !edid.checksum_errors.is_empty() {
+                        println!("  - Checksum errors in blocks: {:?}", edid.checksum_errors);
+                    }
+                    details.edid = Some(edid);
+                }
+                Err(e) => println!("  - Failed to parse EDID: {}", e),
             }
-        
+
         // Создаем hex dump
         let hex_dump = edid_data.chunks(16)
             .enumerate()
@@ -990,6 +1327,8 @@ fn get_monitor_details(display: &DisplayInfo) -> Result<MonitorDetails, String>
                 
                 println!("  - Found {} VCP codes: {:02X?}", vcp_codes.len(), vcp_codes);
                 details.supported_vcp_codes = vcp_codes;
+                details.input_values = Capabilities::parse(&caps_string).values(VCP_INPUT_SOURCE).to_vec();
+                println!("  - Input sources: {:02X?}", details.input_values);
             }
             Err(e) => {
                 println!("❌ Failed to read DDC capabilities: {}", e);
@@ -1020,20 +1359,24 @@ fn get_monitor_details(display: &DisplayInfo) -> Result<MonitorDetails, String>
             Err(e) => println!("❌ {}", e),
         }
         
+        // Версия прошивки (0xC9): по ней выбираются особенности конкретной прошивки
+        print!("  - Reading firmware level (VCP 0xC9)... ");
+        details.firmware_version = timing::bus_firmware(display.i2c_bus);
+        match details.firmware_version {
+            Some(ref firmware) => println!("✅ {}", firmware),
+            None => println!("❌ not reported"),
+        }
+
         // Источник входа (0x60)
         print!("  - Reading input source (VCP 0x60)... ");
-        match read_vcp_value(display.i2c_bus, 0x60) {
-            Ok((current, _max)) => {
-                let input_name = match current {
-                    0x0F => "DisplayPort-1",
-                    0x11 => "HDMI-1",
-                    0x12 => "HDMI-2",
-                    _ => "Unknown",
-                };
+        match ddc_i2c::get_input(display.i2c_bus) {
+            Ok(current) => {
+                let input_name = inputs::input_label(&read_settings(), display, current);
                 println!("✅ {} (0x{:02X})", input_name, current);
                 details.current_input_source = Some(format!("{} (0x{:02X})", input_name, current));
+                details.current_input = Some(current);
             }
-            Err(e) => println!("❌ {}", e),
+            Err(e) => println!("❌ {e:#}"),
         }
         
         // Громкость (0x62)
@@ -1105,18 +1448,13 @@ fn get_monitor_details(display: &DisplayInfo) -> Result<MonitorDetails, String>
         
         // Состояние питания (0xD6)
         print!("  - Reading power state (VCP 0xD6)... ");
-        match read_vcp_value(display.i2c_bus, 0xD6) {
+        match read_vcp_value(display.i2c_bus, VCP_POWER_MODE) {
             Ok((current, _max)) => {
-                let power_state = match current {
-                    0x01 => "On",
-                    0x02 => "Standby",
-                    0x03 => "Suspend",
-                    0x04 => "Off (Soft)",
-                    0x05 => "Off (Hard)",
-                    _ => "Unknown",
-                };
+                let mode = PowerMode::from_vcp(current);
+                let power_state = mode.map(PowerMode::label).unwrap_or("Неизвестно");
                 println!("✅ {} (0x{:02X})", power_state, current);
                 details.current_power_state = Some(format!("{} (0x{:02X})", power_state, current));
+                details.current_power = mode;
             }
             Err(e) => {
                 println!("❌ {}", e);
@@ -1285,7 +1623,7 @@ fn parse_ddcutil_capabilities(output: &str, details: &mut MonitorDetails) {
 fn get_current_vcp_values(bus: u8, details: &mut MonitorDetails) {
     // Получаем яркость (VCP 0x10)
     if let Ok(brightness) = ddc_get_brightness(bus) {
-        details.current_brightness = Some(brightness);
+        details.current_brightness = Some(brightness.into());
     }
     
     // Получаем другие параметры через ddcutil getvcp
@@ -1316,7 +1654,7 @@ fn parse_vcp_value(output: &str, param_type: &str, details: &mut MonitorDetails)
             match param_type {
                 "contrast" => {
                     if let Some(value) = extract_current_value(line) {
-                        details.current_contrast = Some(value);
+                        details.current_contrast = Some(value.into());
                     }
                 }
                 "color_temp" => {
@@ -1336,7 +1674,7 @@ fn parse_vcp_value(output: &str, param_type: &str, details: &mut MonitorDetails)
                 }
                 "volume" => {
                     if let Some(value) = extract_current_value(line) {
-                        details.current_volume = Some(value);
+                        details.current_volume = Some(value.into());
                     }
                 }
                 _ => {}
@@ -1516,145 +1854,39 @@ fn create_settings_page(details: &MonitorDetails) -> ScrolledWindow {
     vbox.set_margin_start(12);
     vbox.set_margin_end(12);
     
-    // Локальное состояние подтверждения изменений (спойлер)
-    let confirm_revealer = gtk::Revealer::new();
-    confirm_revealer.set_transition_type(gtk::RevealerTransitionType::SlideDown);
-    confirm_revealer.set_reveal_child(false);
-    confirm_revealer.set_halign(gtk::Align::Fill);
-    confirm_revealer.set_valign(gtk::Align::Start);
+    // Панель подтверждения: общая транзакция с главным окном и другими карточками
+    let confirm_revealer = confirm_bar();
 
-    let confirm_bar = GtkBox::new(Orientation::Horizontal, 12);
-    confirm_bar.add_css_class("confirm-bar");
-    let confirm_btn = Button::with_label("Подтвердить изменения");
-    confirm_btn.add_css_class("confirm-button");
-    let cancel_btn = Button::with_label("Отменить");
-    cancel_btn.add_css_class("cancel-button");
-    let spacer = GtkBox::new(Orientation::Horizontal, 0);
-    spacer.set_hexpand(true);
-    let timer_lbl = Label::new(Some("Изменения будут отменены через 20 сек"));
-    timer_lbl.add_css_class("timer-label");
-    confirm_bar.append(&confirm_btn);
-    confirm_bar.append(&cancel_btn);
-    confirm_bar.append(&spacer);
-    confirm_bar.append(&timer_lbl);
-    confirm_revealer.set_child(Some(&confirm_bar));
-
-    // Состояние подтверждения для этой вкладки
-    let originals: Rc<RefCell<HashMap<u8, u16>>> = Rc::new(RefCell::new(HashMap::new())); // vcp -> raw original
-    let sec_left = Rc::new(Cell::new(20));
-    let timer_id: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
-    let revealer_cl = confirm_revealer.clone();
-    let timer_lbl_cl = timer_lbl.clone();
     // Регистрируем сеттеры UI для отката положения ползунков: vcp -> setter(raw)
     let ui_setters: Rc<RefCell<HashMap<u8, Box<dyn Fn(u16)>>>> = Rc::new(RefCell::new(HashMap::new()));
     // Флаг подавления обработчиков при программном откате (чтобы не вызывать value_changed заново)
     let suppress_flag = Rc::new(Cell::new(false));
+    // Строки, построенные при defer_reads, не читают монитор сами: значения читаются одним
+    // проходом в фоне, и строки заполняются по мере ответов
+    let defer_reads = Cell::new(false);
+    let deferred: RefCell<Vec<(u8, DeferredRead)>> = RefCell::new(Vec::new());
     // Вычисляем шину один раз для всех операций отката/подтверждения
     let bus_for_ddc = details
         .i2c_bus_num
         .or_else(|| details.i2c_bus.strip_prefix("/dev/i2c-").and_then(|s| s.parse::<u8>().ok()));
 
-    // Функция запуска/перезапуска таймера
-    let start_or_restart_timer = {
-        let sec_left = sec_left.clone();
-        let timer_id = timer_id.clone();
-        let timer_lbl = timer_lbl_cl.clone();
-        let originals_for_timer = originals.clone();
-        let revealer_for_timer = revealer_cl.clone();
-        let bus_for_timer = bus_for_ddc;
-        let ui_setters_for_timer = ui_setters.clone();
-        let suppress_flag_for_timer = suppress_flag.clone();
-        move || {
-            // Сброс предыдущего таймера
-            if let Some(id) = timer_id.borrow_mut().take() { id.remove(); }
-            sec_left.set(20);
-            timer_lbl.set_text("Изменения будут отменены через 20 сек");
-            *timer_id.borrow_mut() = Some(glib::timeout_add_seconds_local(1, {
-                let sec_left = sec_left.clone();
-                let timer_lbl = timer_lbl.clone();
-                let revealer = revealer_for_timer.clone();
-                let originals = originals_for_timer.clone();
-                let bus_opt = bus_for_timer;
-                let ui_setters = ui_setters_for_timer.clone();
-                let suppress_flag_t = suppress_flag_for_timer.clone();
-                move || {
-                    let left = sec_left.get() - 1;
-                    sec_left.set(left);
-                    if left > 0 {
-                        timer_lbl.set_text(&format!("Изменения будут отменены через {} сек", left));
-                        glib::ControlFlow::Continue
-                    } else {
-                        // автооткат
-                        // Пытаемся восстановить все VCP
-                        if let Some(bus) = bus_opt {
-                            // Собираем пары, чтобы дважды не брать borrow()
-                            let pairs: Vec<(u8,u16)> = originals.borrow().iter().map(|(k,v)| (*k, *v)).collect();
-                            suppress_flag_t.set(true);
-                            for (vcp_code, raw) in pairs {
-                                let v_lo = (raw & 0xFF) as u8;
-                                // Обновляем монитор
-                                thread::spawn(move || { let _ = ddc_set_vcp(bus, vcp_code, v_lo); });
-                                // Обновляем UI (ползунок)
-                                if let Some(setter) = ui_setters.borrow().get(&vcp_code) {
-                                    setter(raw);
-                                }
-                            }
-                            suppress_flag_t.set(false);
-                        }
-                        originals.borrow_mut().clear();
-                        revealer.set_reveal_child(false);
-                        glib::ControlFlow::Break
-                    }
+    // Откат транзакции возвращает виджеты этой вкладки к исходным значениям
+    register_setting_view(&confirm_revealer, {
+        let ui_setters = ui_setters.clone();
+        let suppress_flag = suppress_flag.clone();
+        move |setting, value| {
+            if let (Setting::Vcp { bus, code }, Value::Raw(raw)) = (setting, value) {
+                if Some(*bus) != bus_for_ddc {
+                    return;
                 }
-            }));
-        }
-    };
-
-    // Кнопки подтверждения/отмены
-    {
-        let sec_left = sec_left.clone();
-        let timer_id = timer_id.clone();
-        let originals = originals.clone();
-        let revealer = confirm_revealer.clone();
-        confirm_btn.connect_clicked(move |_| {
-            // Подтверждаем: просто очищаем состояние и скрываем панель
-            if let Some(id) = timer_id.borrow_mut().take() { id.remove(); }
-            originals.borrow_mut().clear();
-            sec_left.set(0);
-            revealer.set_reveal_child(false);
-        });
-    }
-
-    {
-        let sec_left = sec_left.clone();
-        let timer_id = timer_id.clone();
-        let originals = originals.clone();
-        let revealer = confirm_revealer.clone();
-        let details_bus = bus_for_ddc;
-        let ui_setters_cancel = ui_setters.clone();
-        let suppress_flag_cancel = suppress_flag.clone();
-        cancel_btn.connect_clicked(move |_| {
-            // Откат всех VCP
-            if let Some(id) = timer_id.borrow_mut().take() { id.remove(); }
-            if let Some(bus) = details_bus {
-                let pairs: Vec<(u8,u16)> = originals.borrow().iter().map(|(k,v)| (*k, *v)).collect();
-                suppress_flag_cancel.set(true);
-                for (vcp_code, raw) in pairs {
-                    let v_lo = (raw & 0xFF) as u8;
-                    // Обновляем монитор
-                    thread::spawn(move || { let _ = ddc_set_vcp(bus, vcp_code, v_lo); });
-                    // Обновляем UI
-                    if let Some(setter) = ui_setters_cancel.borrow().get(&vcp_code) {
-                        setter(raw);
-                    }
+                if let Some(setter) = ui_setters.borrow().get(code) {
+                    suppress_flag.set(true);
+                    setter(*raw);
+                    suppress_flag.set(false);
                 }
-                suppress_flag_cancel.set(false);
             }
-            originals.borrow_mut().clear();
-            sec_left.set(0);
-            revealer.set_reveal_child(false);
-        });
-    }
+        }
+    });
 
     // Оборачиваем контент в Overlay, чтобы спойлер накладывался поверх без сдвига
     let overlay = gtk::Overlay::new();
@@ -1663,8 +1895,6 @@ fn create_settings_page(details: &MonitorDetails) -> ScrolledWindow {
     vbox.set_hexpand(true);
     vbox.set_vexpand(true);
     overlay.set_child(Some(&vbox));
-    confirm_revealer.set_halign(gtk::Align::Fill);
-    confirm_revealer.set_valign(gtk::Align::Start);
     overlay.add_overlay(&confirm_revealer);
 
     // DDC/CI информация
@@ -1699,9 +1929,22 @@ fn create_settings_page(details: &MonitorDetails) -> ScrolledWindow {
     let bus_opt = details.i2c_bus_num.or_else(|| {
         if let Some(rest) = details.i2c_bus.strip_prefix("/dev/i2c-") { rest.parse::<u8>().ok() } else { None }
     });
+
+    // Известные ошибки прошивки модели: скрытые и только читаемые параметры, неверный max
+    let quirks = match (&details.edid, bus_opt) {
+        (Some(edid), _) => QuirkDb::global().lookup(&edid.manufacturer, edid.product_code, details.firmware_version.as_deref()),
+        (None, Some(bus)) => timing::bus_quirks(bus),
+        (None, None) => MonitorQuirks::default(),
+    };
+    if !quirks.notes.is_empty() {
+        add_info_row(&vbox, "Особенности модели:", &quirks.notes.join("; "));
+    }
     
     // Хелпер для строки слайдера: диапазон 0..100 (%), запись масштабируем по реальному max; отображаем "N% (raw/max)"
     let mut add_slider_row = |container: &GtkBox, label_text: &str, vcp: u8| {
+        if quirks.is_hidden(vcp) {
+            return;
+        }
         if let Some(bus) = bus_opt {
             let row = GtkBox::new(Orientation::Horizontal, 12);
             row.set_hexpand(true);
@@ -1721,12 +1964,27 @@ fn create_settings_page(details: &MonitorDetails) -> ScrolledWindow {
 
             // Локальный max для данного VCP в ячейке, чтобы можно было читать в обработчике
             let max_cell = Rc::new(Cell::new(100u16));
-            if let Ok((cur, max)) = read_vcp_value(bus, vcp) {
-                let max_u16 = max as u16;
-                max_cell.set(if max_u16 == 0 { 100 } else { max_u16 });
-                let percent = ((cur as f64) * 100.0 / (max_cell.get() as f64)).round().clamp(0.0, 100.0) as u8;
-                scale.set_value(percent as f64);
-                val_lbl.set_text(&format!("{}% ({}/{})", percent, cur, max_cell.get()));
+            let show_initial = {
+                let (scale, val_lbl, max_cell, suppress_flag) = (scale.clone(), val_lbl.clone(), max_cell.clone(), suppress_flag.clone());
+                let quirk_max = quirks.max_for(vcp);
+                move |result: Result<(u16, u16), String>| {
+                    let Ok((cur, max)) = result else { return };
+                    max_cell.set(match quirk_max {
+                        Some(m) => m,
+                        None if max == 0 => 100,
+                        None => max,
+                    });
+                    let percent = ((cur as f64) * 100.0 / (max_cell.get() as f64)).round().clamp(0.0, 100.0) as u8;
+                    suppress_flag.set(true);
+                    scale.set_value(percent as f64);
+                    suppress_flag.set(false);
+                    val_lbl.set_text(&format!("{}% ({}/{})", percent, cur, max_cell.get()));
+                }
+            };
+            if defer_reads.get() {
+                deferred.borrow_mut().push((vcp, Box::new(show_initial)));
+            } else {
+                show_initial(read_vcp_value(bus, vcp));
             }
 
             // Регистрируем сеттер UI для отката по этому VCP
@@ -1744,9 +2002,6 @@ fn create_settings_page(details: &MonitorDetails) -> ScrolledWindow {
 
             // on change: считаем raw из процента: raw = round(percent * max / 100)
             let max_cell_cl = max_cell.clone();
-            let originals_cl = originals.clone();
-            let confirm_revealer_cl = confirm_revealer.clone();
-            let start_timer = start_or_restart_timer.clone();
             let suppress_flag_cl = suppress_flag.clone();
             scale.connect_value_changed(clone!(@strong val_lbl => move |s| {
                 if suppress_flag_cl.get() { return; }
@@ -1754,22 +2009,45 @@ fn create_settings_page(details: &MonitorDetails) -> ScrolledWindow {
                 let max_val = max_cell_cl.get().max(1);
                 let raw = (((percent as u32) * (max_val as u32) + 50) / 100) as u16; // округление
                 val_lbl.set_text(&format!("{}% ({}/{})", percent, raw, max_val));
-                // Сохраняем оригинальное значение при первом изменении этого VCP
-                if !originals_cl.borrow().contains_key(&vcp) {
-                    if let Ok((cur, _m)) = read_vcp_value(bus, vcp) {
-                        originals_cl.borrow_mut().insert(vcp, cur as u16);
+                change_vcp(bus, vcp, raw);
+            }));
+
+            // Программная установка реального значения монитора (без повторной записи)
+            let show_actual = {
+                let ui_setters = ui_setters.clone();
+                let suppress_flag = suppress_flag.clone();
+                move |raw: u16| {
+                    if let Some(setter) = ui_setters.borrow().get(&vcp) {
+                        suppress_flag.set(true);
+                        setter(raw);
+                        suppress_flag.set(false);
                     }
                 }
-                // Показываем спойлер и запускаем/перезапускаем таймер
-                if !confirm_revealer_cl.reveals_child() {
-                    confirm_revealer_cl.set_reveal_child(true);
+            };
+
+            // Результат записи: ошибка показывается в подсказке значения, прочитанное значение — на ползунке
+            let val_lbl_weak = val_lbl.downgrade();
+            let show_readback = show_actual.clone();
+            on_write_result(bus, vcp, move |result| {
+                let Some(lbl) = val_lbl_weak.upgrade() else { return false };
+                match &result.result {
+                    Ok(()) => lbl.set_tooltip_text(None),
+                    Err(e) => lbl.set_tooltip_text(Some(&format!("Ошибка записи: {}", e))),
                 }
-                start_timer();
-                let bus_local = bus;
-                let vcp_local = vcp;
-                let raw_lo = (raw & 0xFF) as u8;
-                thread::spawn(move || { let _ = ddc_set_vcp(bus_local, vcp_local, raw_lo); });
-            }));
+                if let Some(actual) = result.readback {
+                    if actual != result.value && ddc_writer().is_settled(bus, vcp, Duration::ZERO) {
+                        println!("Bus {}: VCP 0x{:02X} set to {}, monitor reports {}", bus, vcp, result.value, actual);
+                        show_readback(actual);
+                    }
+                }
+                true
+            });
+            poll_control(bus, vcp, &scale, show_actual);
+
+            if quirks.is_read_only(vcp) {
+                scale.set_sensitive(false);
+                scale.set_tooltip_text(Some("Монитор игнорирует запись этого параметра"));
+            }
 
             row.append(&scale);
             row.append(&val_lbl);
@@ -1785,84 +2063,934 @@ fn create_settings_page(details: &MonitorDetails) -> ScrolledWindow {
     // Контраст как интерактивная строка
     add_slider_row(&vbox, "Контраст:", 0x12);
     
-    if let Some(ref input_source) = details.current_input_source {
-        add_info_row(&vbox, "Источник входа:", input_source);
+    match bus_opt {
+        Some(bus) if !quirks.is_hidden(VCP_INPUT_SOURCE) => append_input_source_row(&vbox, details, bus),
+        _ => {
+            if let Some(ref input_source) = details.current_input_source {
+                add_info_row(&vbox, "Источник входа:", input_source);
+            }
+        }
     }
     
-    // Громкость как интерактивная строка
-    add_slider_row(&vbox, "Громкость:", 0x62);
+    // Программное изменение виджета (откат, прочитанное значение) без повторной записи
+    let show_actual = |vcp: u8| {
+        let ui_setters = ui_setters.clone();
+        let suppress_flag = suppress_flag.clone();
+        move |raw: u16| {
+            if let Some(setter) = ui_setters.borrow().get(&vcp) {
+                suppress_flag.set(true);
+                setter(raw);
+                suppress_flag.set(false);
+            }
+        }
+    };
+
+    // Хелпер для строки выбора из списка значений (аудиовход, цветовой пресет) с подтверждением и откатом
+    let add_choice_row = |container: &GtkBox, label_text: &str, bus: u8, code: u8, options: Vec<(u8, String)>| {
+        if options.is_empty() {
+            return;
+        }
+        let row = GtkBox::new(Orientation::Horizontal, 12);
+        let l = Label::new(Some(label_text));
+        l.set_xalign(0.0);
+        l.set_width_chars(20);
+        row.append(&l);
+        let names: Vec<&str> = options.iter().map(|(_, n)| n.as_str()).collect();
+        let dropdown = gtk::DropDown::from_strings(&names);
+        dropdown.set_hexpand(true);
+        let options = Rc::new(RefCell::new(options));
+        let select = {
+            let dropdown = dropdown.clone();
+            let options = options.clone();
+            move |raw: u16| {
+                if let Some(pos) = options.borrow().iter().position(|(v, _)| *v as u16 == raw & 0xFF) {
+                    dropdown.set_selected(pos as u32);
+                }
+            }
+        };
+        // Значение списка — младший байт, старший у некоторых мониторов занят.
+        // Значение, которого нет в списке, добавляется в конец
+        let show_current = {
+            let (dropdown, options, select, suppress_flag) = (dropdown.clone(), options.clone(), select.clone(), suppress_flag.clone());
+            move |result: Result<(u16, u16), String>| {
+                let Ok((cur, _)) = result else { return };
+                let cur = (cur & 0xFF) as u8;
+                if !options.borrow().iter().any(|(v, _)| *v == cur) {
+                    let name = format!("0x{:02X}", cur);
+                    if let Some(list) = dropdown.model().and_downcast::<gtk::StringList>() {
+                        list.append(&name);
+                    }
+                    options.borrow_mut().push((cur, name));
+                }
+                suppress_flag.set(true);
+                select(cur as u16);
+                suppress_flag.set(false);
+            }
+        };
+        if defer_reads.get() {
+            dropdown.set_selected(gtk::INVALID_LIST_POSITION);
+            deferred.borrow_mut().push((code, Box::new(show_current)));
+        } else {
+            show_current(read_vcp_value(bus, code));
+        }
+        ui_setters.borrow_mut().insert(code, Box::new(select));
+        let suppress_flag_cl = suppress_flag.clone();
+        let options_cl = options.clone();
+        dropdown.connect_selected_notify(move |dd| {
+            if suppress_flag_cl.get() { return; }
+            if let Some(&(value, _)) = options_cl.borrow().get(dd.selected() as usize) {
+                change_vcp(bus, code, value as u16);
+            }
+        });
+        poll_control(bus, code, &dropdown, show_actual(code));
+        if quirks.is_read_only(code) {
+            dropdown.set_sensitive(false);
+            dropdown.set_tooltip_text(Some("Монитор игнорирует запись этого параметра"));
+        }
+        row.append(&dropdown);
+        container.append(&row);
+    };
+
+    // Звук: громкость, отключение звука и (по базе особенностей) аудиовход — с тем же подтверждением и откатом
+    add_slider_row(&vbox, "Громкость:", VCP_AUDIO_VOLUME);
+    if let Some(bus) = bus_opt {
+        let mute_state = if quirks.is_hidden(VCP_AUDIO_MUTE) { None } else { read_vcp_value(bus, VCP_AUDIO_MUTE).ok() };
+        if let Some(muted) = mute_state.and_then(|(cur, _)| audio::is_muted(cur)) {
+            let row = GtkBox::new(Orientation::Horizontal, 12);
+            let l = Label::new(Some("Без звука:"));
+            l.set_xalign(0.0);
+            l.set_width_chars(20);
+            row.append(&l);
+            let switch = gtk::Switch::new();
+            switch.set_active(muted);
+            switch.set_halign(gtk::Align::Start);
+            {
+                let switch = switch.clone();
+                ui_setters.borrow_mut().insert(VCP_AUDIO_MUTE, Box::new(move |raw: u16| {
+                    if let Some(muted) = audio::is_muted(raw) { switch.set_active(muted); }
+                }));
+            }
+            let suppress_flag_cl = suppress_flag.clone();
+            switch.connect_active_notify(move |sw| {
+                if suppress_flag_cl.get() { return; }
+                change_vcp(bus, VCP_AUDIO_MUTE, audio::mute_value(sw.is_active()));
+            });
+            let switch_weak = switch.downgrade();
+            let show_readback = show_actual(VCP_AUDIO_MUTE);
+            on_write_result(bus, VCP_AUDIO_MUTE, move |result| {
+                let Some(sw) = switch_weak.upgrade() else { return false };
+                match &result.result {
+                    Ok(()) => sw.set_tooltip_text(None),
+                    Err(e) => sw.set_tooltip_text(Some(&format!("Ошибка записи: {}", e))),
+                }
+                if let Some(actual) = result.readback {
+                    if audio::is_muted(actual) != audio::is_muted(result.value) && ddc_writer().is_settled(bus, VCP_AUDIO_MUTE, Duration::ZERO) {
+                        show_readback(actual);
+                    }
+                }
+                true
+            });
+            poll_control(bus, VCP_AUDIO_MUTE, &switch, show_actual(VCP_AUDIO_MUTE));
+            if quirks.is_read_only(VCP_AUDIO_MUTE) {
+                switch.set_sensitive(false);
+            }
+            row.append(&switch);
+            vbox.append(&row);
+        }
+
+        // Аудиовход: кода в MCCS нет, он задается в базе особенностей (audio_input)
+        if let Some(audio_input) = quirks.audio_input.clone().filter(|a| !quirks.is_hidden(a.code)) {
+            let code = audio_input.code;
+            let options: Vec<(u8, String)> = if audio_input.inputs.is_empty() {
+                details.capabilities_string.as_deref().map(Capabilities::parse).unwrap_or_default()
+                    .values(code).iter().map(|&v| (v, format!("0x{:02X}", v))).collect()
+            } else {
+                audio_input.inputs.iter().map(|n| (n.value, n.name.clone())).collect()
+            };
+            add_choice_row(&vbox, "Аудиовход:", bus, code, options);
+        }
+    }
     
-    if let Some(ref power_state) = details.current_power_state {
-        add_info_row(&vbox, "Состояние питания:", power_state);
+    if !quirks.is_hidden(VCP_POWER_MODE) {
+        append_power_row(&vbox, details, bus_opt);
     }
 
-    // Цветовые каналы — заменяем статичные значения на ползунки напротив
-    if details.red_gain.is_some() || details.green_gain.is_some() || details.blue_gain.is_some() {
+    // Цвет: пресет, усиление и уровень черного по каналам, точка белого, сохраненная калибровка
+    let color_supported = |code: u8| details.supported_vcp_codes.contains(&code) && !quirks.is_hidden(code);
+    let has_gains = details.red_gain.is_some() || details.green_gain.is_some() || details.blue_gain.is_some();
+    if let Some(bus) = bus_opt.filter(|_| has_gains || color::CALIBRATION_CODES.iter().any(|&c| color_supported(c))) {
         let separator2 = Separator::new(Orientation::Horizontal);
         separator2.set_margin_top(12);
         separator2.set_margin_bottom(12);
         vbox.append(&separator2);
         
-        let color_label = Label::new(Some("Цветовые каналы:"));
+        let color_label = Label::new(Some("Цвет:"));
         color_label.set_xalign(0.0);
-        color_label.set_markup("<b>Цветовые каналы:</b>");
+        color_label.set_markup("<b>Цвет:</b>");
         vbox.append(&color_label);
+
+        // Пресет (0x14) из значений, объявленных в capabilities
+        let preset_values: Vec<u8> = details.capabilities_string.as_deref().map(Capabilities::parse).unwrap_or_default()
+            .values(color::VCP_COLOR_PRESET).to_vec();
+        if color_supported(color::VCP_COLOR_PRESET) {
+            let options = preset_values.iter().map(|&v| (v, color::preset_label(v))).collect();
+            add_choice_row(&vbox, "Цветовой пресет:", bus, color::VCP_COLOR_PRESET, options);
+        }
+
         // Ползунки R/G/B
-        add_slider_row(&vbox, "Красный канал:", 0x16);
-        add_slider_row(&vbox, "Зеленый канал:", 0x18);
-        add_slider_row(&vbox, "Синий канал:", 0x1A);
+        add_slider_row(&vbox, "Красный канал:", color::VCP_RED_GAIN);
+        add_slider_row(&vbox, "Зеленый канал:", color::VCP_GREEN_GAIN);
+        add_slider_row(&vbox, "Синий канал:", color::VCP_BLUE_GAIN);
+        let black_labels = ["Уровень черного R:", "Уровень черного G:", "Уровень черного B:"];
+        for (code, label) in color::BLACK_LEVEL_CODES.into_iter().zip(black_labels) {
+            if color_supported(code) {
+                add_slider_row(&vbox, label, code);
+            }
+        }
+
+        // Точка белого: усиления каналов вычисляются из цветовой температуры относительно D65
+        // (максимум — полный 16-битный, у 10-битных панелей он больше 255)
+        let gains: Vec<(u8, u16)> = color::GAIN_CODES.iter()
+            .filter(|&&code| !quirks.is_hidden(code) && !quirks.is_read_only(code) && ui_setters.borrow().contains_key(&code))
+            .filter_map(|&code| {
+                let (_, max) = ddc_i2c::get_vcp(bus, code).ok()?;
+                Some((code, quirks.max_for(code).unwrap_or(if max == 0 { 100 } else { max })))
+            })
+            .collect();
+        if gains.len() == color::GAIN_CODES.len() {
+            let row = GtkBox::new(Orientation::Horizontal, 12);
+            let l = Label::new(Some("Точка белого:"));
+            l.set_xalign(0.0);
+            l.set_width_chars(20);
+            row.append(&l);
+            let kelvin = gtk::SpinButton::with_range(3000.0, 10000.0, 100.0);
+            kelvin.set_value(6500.0);
+            row.append(&kelvin);
+            row.append(&Label::new(Some("K")));
+            let apply_btn = Button::with_label("Установить");
+            // Усиления действуют только в пользовательском пресете
+            let user_preset = (color_supported(color::VCP_COLOR_PRESET) && preset_values.contains(&color::USER_PRESET))
+                .then_some(color::USER_PRESET);
+            let show_preset = show_actual(color::VCP_COLOR_PRESET);
+            let show_gains: Vec<_> = gains.iter().map(|&(code, _)| show_actual(code)).collect();
+            apply_btn.connect_clicked(clone!(@strong kelvin => move |_| {
+                if let Some(preset) = user_preset {
+                    change_vcp(bus, color::VCP_COLOR_PRESET, preset as u16);
+                    show_preset(preset as u16);
+                }
+                let factors = color::white_point_gains(kelvin.value() as u32);
+                for ((&(code, max), factor), show) in gains.iter().zip(factors).zip(&show_gains) {
+                    let raw = (factor * max as f64).round() as u16;
+                    change_vcp(bus, code, raw);
+                    show(raw);
+                }
+            }));
+            row.append(&apply_btn);
+            vbox.append(&row);
+        }
+
+        // Калибровка монитора: сохраняются значения показанных цветовых параметров, как есть (16 бит)
+        let row = GtkBox::new(Orientation::Horizontal, 12);
+        let l = Label::new(Some("Калибровка:"));
+        l.set_xalign(0.0);
+        l.set_width_chars(20);
+        row.append(&l);
+        let save_btn = Button::with_label("Сохранить");
+        let restore_btn = Button::with_label("Восстановить");
+        restore_btn.set_sensitive(read_settings().monitor(&details.display).is_some_and(|m| !m.color_calibration.is_empty()));
+        let codes: Vec<u8> = color::CALIBRATION_CODES.into_iter().filter(|c| ui_setters.borrow().contains_key(c)).collect();
+        {
+            let codes = codes.clone();
+            let restore_btn = restore_btn.clone();
+            let display = details.display.clone();
+            save_btn.connect_clicked(move |btn| {
+                let values: BTreeMap<u8, u16> = codes.iter()
+                    .filter_map(|&code| ddc_i2c::get_vcp(bus, code).ok().map(|(cur, _)| (code, cur)))
+                    .collect();
+                if values.is_empty() {
+                    btn.set_tooltip_text(Some("Не удалось прочитать цветовые параметры"));
+                    return;
+                }
+                match update_settings(|settings| settings.monitor_mut(&display).color_calibration = values) {
+                    Ok(()) => {
+                        btn.set_tooltip_text(None);
+                        restore_btn.set_sensitive(true);
+                    }
+                    Err(e) => btn.set_tooltip_text(Some(&format!("Ошибка сохранения: {e:#}"))),
+                }
+            });
+        }
+        {
+            let shows: HashMap<u8, _> = codes.iter().map(|&code| (code, show_actual(code))).collect();
+            let display = details.display.clone();
+            restore_btn.connect_clicked(move |_| {
+                let Some(values) = read_settings().monitor(&display).map(|m| m.color_calibration.clone()) else { return };
+                // Порядок CALIBRATION_CODES: сначала пресет, затем усиления
+                for (code, raw) in values {
+                    let Some(show) = shows.get(&code) else { continue };
+                    change_vcp(bus, code, raw);
+                    show(raw);
+                }
+            });
+        }
+        row.append(&save_btn);
+        row.append(&restore_btn);
+        vbox.append(&row);
     }
     // Подсветка как интерактивная строка
     add_slider_row(&vbox, "Управление подсветкой:", 0x13);
-    
-    if let Some(ref osd_lang) = details.osd_language {
-        add_info_row(&vbox, "Язык OSD:", osd_lang);
-    }
-    
-    // Поддерживаемые VCP коды
-    if !details.supported_vcp_codes.is_empty() {
-        let separator = Separator::new(Orientation::Horizontal);
-        separator.set_margin_top(12);
-        separator.set_margin_bottom(12);
-        vbox.append(&separator);
-        
-        let vcp_label = Label::new(Some("Поддерживаемые VCP коды:"));
-        vcp_label.set_xalign(0.0);
-        vcp_label.set_markup("<b>Поддерживаемые VCP коды:</b>");
-        vbox.append(&vcp_label);
-        
-        let mut vcp_codes = details.supported_vcp_codes.clone();
-        vcp_codes.sort();
-        let vcp_text = vcp_codes.iter()
-            .map(|code| format!("0x{:02X}", code))
-            .collect::<Vec<_>>()
-            .join(", ");
-        
-        let vcp_value_label = Label::new(Some(&vcp_text));
-        vcp_value_label.set_xalign(0.0);
-        vcp_value_label.set_wrap(true);
-        vcp_value_label.set_selectable(true);
-        vbox.append(&vcp_value_label);
-    }
-    
 
-    scrolled.set_child(Some(&overlay));
-    scrolled
-}
+    // Остальные параметры из capabilities: непрерывные — ползунки, со списком значений — выбор, только чтение — текст
+    if let Some(bus) = bus_opt {
+        let mut codes = details.capabilities_string.as_deref().map(Capabilities::parse).unwrap_or_default().vcp;
+        for &code in &details.supported_vcp_codes {
+            codes.entry(code).or_default();
+        }
+        let declared: HashSet<u8> = codes.keys().copied().collect();
+        // Источник входа и питание уже выведены своими строками, 0x02/0x52 — служебные коды протокола
+        let own_rows = [VCP_INPUT_SOURCE, VCP_POWER_MODE, 0x02, 0x52];
+        let rest: Vec<(u8, Vec<u8>)> = codes
+            .into_iter()
+            .filter(|(code, values)| {
+                !own_rows.contains(code)
+                    && !ui_setters.borrow().contains_key(code)
+                    && !quirks.is_hidden(*code)
+                    && vcp::kind(*code, values) != vcp::VcpKind::WriteOnly
+            })
+            .collect();
+        if !rest.is_empty() {
+            let separator3 = Separator::new(Orientation::Horizontal);
+            separator3.set_margin_top(12);
+            separator3.set_margin_bottom(12);
+            vbox.append(&separator3);
+
+            let all_label = Label::new(Some("Все параметры:"));
+            all_label.set_xalign(0.0);
+            all_label.set_markup("<b>Все параметры:</b>");
+            vbox.append(&all_label);
+
+            // Десятки кодов с задержками MCCS: читаются в фоне, окно не ждет
+            defer_reads.set(true);
+            for (code, values) in rest {
+                let label = format!("{}:", vcp::name(code));
+                match vcp::kind(code, &values) {
+                    vcp::VcpKind::Continuous => add_slider_row(&vbox, &label, code),
+                    vcp::VcpKind::NonContinuous if !values.is_empty() => {
+                        let options = values.iter().map(|&v| (v, vcp_value_label(code, v))).collect();
+                        add_choice_row(&vbox, &label, bus, code, options);
+                    }
+                    _ => {
+                        let value_lbl = add_info_row(&vbox, &label, "…");
+                        deferred.borrow_mut().push((code, Box::new(move |result| {
+                            value_lbl.set_text(&match result {
+                                Ok((cur, max)) => format!("{} (0x{:04X}), max {}", cur, cur, max),
+                                Err(e) => format!("Не читается: {}", e),
+                            });
+                        })));
+                    }
+                }
+            }
+            defer_reads.set(false);
+            read_deferred(bus, &vbox, deferred.take());
+        }
 
-fn create_capabilities_page(details: &MonitorDetails) -> ScrolledWindow {
-    let scrolled = ScrolledWindow::new();
-    scrolled.set_policy(PolicyType::Never, PolicyType::Automatic);
-    scrolled.set_vexpand(true);
-    scrolled.set_hexpand(true);
-    
-    let vbox = GtkBox::new(Orientation::Vertical, 12);
-    vbox.set_margin_top(12);
-    vbox.set_margin_bottom(12);
-    vbox.set_margin_start(12);
-    vbox.set_margin_end(12);
-    
-    if let Some(ref caps_string) = details.capabilities_string {
+        // Запись произвольного кода — только после явного согласия; откат возможен, если код читается
+        let raw_expander = gtk::Expander::new(Some("Расширенный режим: запись любого кода VCP"));
+        raw_expander.set_margin_top(12);
+        let raw_box = GtkBox::new(Orientation::Vertical, 8);
+        let warning = Label::new(Some(
+            "Запись неизвестных кодов может изменить скрытые настройки монитора, перевести его в сервисный режим \
+             или сделать неуправляемым до сброса кнопками. Откатить изменение можно, только если код читается.",
+        ));
+        warning.set_wrap(true);
+        warning.set_xalign(0.0);
+        warning.add_css_class("warning");
+        raw_box.append(&warning);
+        let agree = gtk::CheckButton::with_label("Я понимаю риск");
+        raw_box.append(&agree);
+
+        let row = GtkBox::new(Orientation::Horizontal, 8);
+        let code_entry = gtk::Entry::new();
+        code_entry.set_placeholder_text(Some("Код, например E0"));
+        code_entry.set_width_chars(12);
+        let value_spin = gtk::SpinButton::with_range(0.0, 65535.0, 1.0);
+        let read_btn = Button::with_label("Прочитать");
+        let write_btn = Button::with_label("Записать");
+        write_btn.set_sensitive(false);
+        let status = Label::new(None);
+        status.set_xalign(0.0);
+        status.set_hexpand(true);
+        row.append(&code_entry);
+        row.append(&value_spin);
+        row.append(&read_btn);
+        row.append(&write_btn);
+        row.append(&status);
+        raw_box.append(&row);
+
+        agree.connect_toggled(clone!(@weak write_btn => move |cb| write_btn.set_sensitive(cb.is_active())));
+        read_btn.connect_clicked(clone!(@weak code_entry, @weak value_spin, @weak status => move |_| {
+            let Some(code) = parse_vcp_code(&code_entry.text()) else {
+                status.set_text("Неверный код");
+                return;
+            };
+            match ddc_i2c::get_vcp(bus, code) {
+                Ok((cur, max)) => {
+                    value_spin.set_value(cur as f64);
+                    status.set_text(&format!("0x{:02X} = {} (max {})", code, cur, max));
+                }
+                Err(e) => status.set_text(&format!("0x{:02X}: {e:#}", code)),
+            }
+        }));
+        {
+            let setters = ui_setters.clone();
+            let suppress_flag = suppress_flag.clone();
+            write_btn.connect_clicked(clone!(@weak code_entry, @weak value_spin, @weak status => move |_| {
+                let Some(code) = parse_vcp_code(&code_entry.text()) else {
+                    status.set_text("Неверный код");
+                    return;
+                };
+                let value = value_spin.value() as u16;
+                change_vcp(bus, code, value);
+                if let Some(setter) = setters.borrow().get(&code) {
+                    suppress_flag.set(true);
+                    setter(value);
+                    suppress_flag.set(false);
+                }
+                status.set_text(&format!("Записано: 0x{:02X} = {}", code, value));
+            }));
+        }
+        raw_expander.set_child(Some(&raw_box));
+        vbox.append(&raw_expander);
+
+        // Заводские настройки: только объявленные монитором команды, каждая — после подтверждения
+        let resets: Vec<u8> = vcp::FACTORY_RESETS.into_iter().filter(|c| declared.contains(c) && !quirks.is_hidden(*c)).collect();
+        if !resets.is_empty() {
+            let apply_reset: Rc<dyn Fn(u8)> = Rc::new({
+                let setters = ui_setters.clone();
+                let suppress_flag = suppress_flag.clone();
+                move |code: u8| {
+                    println!("Bus {}: restore factory defaults (VCP 0x{:02X})", bus, code);
+                    // Команда сброса не читается, поэтому без проверки записи
+                    ddc_writer().submit(bus, code, 1, Arc::new(move |v| ddc_i2c::set_vcp(bus, code, v)), None);
+                    // Показанные на странице параметры, которые вернет сброс
+                    let shown: Vec<u8> = setters.borrow().keys().copied()
+                        .filter(|c| vcp::reset_scope(code).is_none_or(|scope| scope.contains(c)))
+                        .collect();
+                    // Откатывать их по таймеру больше нечего
+                    forget_changes(|s| matches!(s, Setting::Vcp { bus: b, code: c }
+                        if *b == bus && vcp::reset_scope(code).is_none_or(|scope| scope.contains(c))));
+                    let (tx, rx) = async_channel::bounded::<Vec<(u8, u16)>>(1);
+                    thread::spawn(move || {
+                        ddc_writer().wait_idle(bus, Duration::from_secs(5));
+                        // Монитору нужно время, чтобы применить заводские значения
+                        thread::sleep(RESET_SETTLE);
+                        let values = shown.iter().filter_map(|&c| ddc_i2c::get_vcp(bus, c).ok().map(|(cur, _)| (c, cur))).collect();
+                        let _ = tx.send_blocking(values);
+                    });
+                    let setters = setters.clone();
+                    let suppress_flag = suppress_flag.clone();
+                    glib::spawn_future_local(async move {
+                        let Ok(values) = rx.recv().await else { return };
+                        suppress_flag.set(true);
+                        for (c, value) in values {
+                            if let Some(setter) = setters.borrow().get(&c) {
+                                setter(value);
+                            }
+                        }
+                        suppress_flag.set(false);
+                    });
+                }
+            });
+
+            let row = GtkBox::new(Orientation::Horizontal, 12);
+            row.set_margin_top(12);
+            let l = Label::new(Some("Заводские настройки:"));
+            l.set_xalign(0.0);
+            l.set_width_chars(20);
+            row.append(&l);
+            let reset_btn = gtk::MenuButton::new();
+            reset_btn.set_label("Сброс");
+            let popover = gtk::Popover::new();
+            let list = GtkBox::new(Orientation::Vertical, 4);
+            for code in resets {
+                let name = vcp::name(code);
+                let item = Button::with_label(&name);
+                item.add_css_class("flat");
+                let popover_weak = popover.downgrade();
+                let apply_reset = apply_reset.clone();
+                item.connect_clicked(move |btn| {
+                    if let Some(p) = popover_weak.upgrade() { p.popdown(); }
+                    let dialog = gtk::MessageDialog::builder()
+                        .modal(true)
+                        .message_type(gtk::MessageType::Warning)
+                        .text(format!("{}?", name))
+                        .secondary_text("Монитор вернет заводские значения этих параметров. Отменить сброс нельзя.")
+                        .build();
+                    if let Some(window) = btn.root().and_then(|r| r.downcast::<gtk::Window>().ok()) {
+                        dialog.set_transient_for(Some(&window));
+                    }
+                    dialog.add_button("Отмена", gtk::ResponseType::Cancel);
+                    if let Some(ok) = dialog.add_button("Сбросить", gtk::ResponseType::Accept).downcast_ref::<Button>() {
+                        ok.add_css_class("destructive-action");
+                    }
+                    let apply_reset = apply_reset.clone();
+                    dialog.connect_response(move |dialog, response| {
+                        dialog.close();
+                        if response == gtk::ResponseType::Accept {
+                            apply_reset(code);
+                        }
+                    });
+                    dialog.present();
+                });
+                list.append(&item);
+            }
+            popover.set_child(Some(&list));
+            reset_btn.set_popover(Some(&popover));
+            row.append(&reset_btn);
+            vbox.append(&row);
+        }
+
+        // Снимок состояния: все читаемые параметры из capabilities и яркость/гамма XRandR в файл
+        let row = GtkBox::new(Orientation::Horizontal, 12);
+        row.set_margin_top(12);
+        let l = Label::new(Some("Снимок состояния:"));
+        l.set_xalign(0.0);
+        l.set_width_chars(20);
+        row.append(&l);
+        let save_snap = Button::with_label("Сохранить");
+        let restore_snap = Button::with_label("Восстановить…");
+        let snap_status = Label::new(None);
+        snap_status.set_xalign(0.0);
+        snap_status.set_hexpand(true);
+        snap_status.set_wrap(true);
+        snap_status.set_selectable(true);
+        row.append(&save_snap);
+        row.append(&restore_snap);
+        row.append(&snap_status);
+        vbox.append(&row);
+
+        let xrandr_output = details.xrandr_output.clone();
+        save_snap.connect_clicked(clone!(@weak snap_status, @strong xrandr_output => move |btn| {
+            btn.set_sensitive(false);
+            snap_status.set_text("Чтение параметров...");
+            let (tx, rx) = async_channel::bounded::<Result<(PathBuf, usize), String>>(1);
+            let output = xrandr_output.clone();
+            thread::spawn(move || {
+                let result = mondis_ddc::snapshot::capture(bus).and_then(|mut snap| {
+                    if let Some(out) = output.as_deref() {
+                        if let (Ok(brightness), Ok(gamma)) = (mondis_x11::xrandr::get_brightness(out), mondis_x11::xrandr::get_gamma(out)) {
+                            snap.xrandr = Some(XrandrState { brightness, gamma });
+                        }
+                    }
+                    snapshot::save_snapshot(&snap, None).map(|path| (path, snap.vcp.len()))
+                });
+                let _ = tx.send_blocking(result.map_err(|e| format!("{e:#}")));
+            });
+            let btn = btn.clone();
+            glib::spawn_future_local(async move {
+                let Ok(result) = rx.recv().await else { return };
+                btn.set_sensitive(true);
+                match result {
+                    Ok((path, count)) => snap_status.set_text(&format!("Сохранено параметров: {} — {}", count, path.display())),
+                    Err(e) => snap_status.set_text(&format!("Ошибка снимка: {}", e)),
+                }
+            });
+        }));
+
+        // Восстановление идет через панель подтверждения: текущие значения становятся исходными в транзакции
+        let apply_snapshot = {
+            let setters = ui_setters.clone();
+            let suppress_flag = suppress_flag.clone();
+            let snap_status = snap_status.clone();
+            let xrandr_output = xrandr_output.clone();
+            move |snap: Snapshot| {
+                snap_status.set_text("Чтение текущих значений...");
+                type Current = (Vec<(u8, u16, u16)>, Option<XrandrState>);
+                let (tx, rx) = async_channel::bounded::<Result<Current, String>>(1);
+                let snap_for_thread = snap.clone();
+                let out_for_thread = xrandr_output.clone().filter(|_| snap.xrandr.is_some());
+                thread::spawn(move || {
+                    // (код, значение из снимка, текущее значение для отката)
+                    let result = mondis_ddc::snapshot::restorable(bus, &snap_for_thread).map(|values| {
+                        let vcp_values = values
+                            .into_iter()
+                            .filter_map(|(code, value)| ddc_i2c::get_vcp(bus, code).ok().map(|(cur, _)| (code, value, cur)))
+                            .collect();
+                        let xrandr_current = out_for_thread.and_then(|out| {
+                            let brightness = mondis_x11::xrandr::get_brightness(&out).ok()?;
+                            let gamma = mondis_x11::xrandr::get_gamma(&out).ok()?;
+                            Some(XrandrState { brightness, gamma })
+                        });
+                        (vcp_values, xrandr_current)
+                    });
+                    let _ = tx.send_blocking(result.map_err(|e| format!("{e:#}")));
+                });
+                let setters = setters.clone();
+                let suppress_flag = suppress_flag.clone();
+                let snap_status = snap_status.clone();
+                let xrandr_output = xrandr_output.clone();
+                glib::spawn_future_local(async move {
+                    let (values, xrandr_current) = match rx.recv().await {
+                        Ok(Ok(current)) => current,
+                        Ok(Err(e)) => { snap_status.set_text(&format!("Снимок не подходит: {}", e)); return; }
+                        Err(_) => return,
+                    };
+                    for &(code, value, current) in &values {
+                        record_change(Setting::Vcp { bus, code }, || Some(Value::Raw(current)), Some(Value::Raw(value)));
+                        queue_vcp_write(bus, code, value);
+                        if let Some(setter) = setters.borrow().get(&code) {
+                            suppress_flag.set(true);
+                            setter(value);
+                            suppress_flag.set(false);
+                        }
+                    }
+                    if let (Some(state), Some(output)) = (snap.xrandr.clone(), xrandr_output.clone()) {
+                        let (r, g, b) = state.gamma;
+                        let restored = vec![
+                            (Setting::XrandrBrightness { output: output.clone() }, Value::Percent(state.brightness)),
+                            (Setting::XrandrGamma { output }, Value::Gamma(r, g, b)),
+                        ];
+                        for (setting, value) in &restored {
+                            let original = xrandr_current.as_ref().map(|cur| match setting {
+                                Setting::XrandrGamma { .. } => Value::Gamma(cur.gamma.0, cur.gamma.1, cur.gamma.2),
+                                _ => Value::Percent(cur.brightness),
+                            });
+                            record_change(setting.clone(), || original, Some(value.clone()));
+                        }
+                        apply_settings(&restored);
+                    }
+                    snap_status.set_text(&format!("Восстановлено параметров: {}", values.len()));
+                });
+            }
+        };
+        restore_snap.connect_clicked(clone!(@weak snap_status => move |btn| {
+            let parent = btn.root().and_then(|r| r.downcast::<gtk::Window>().ok());
+            let chooser = gtk::FileChooserDialog::new(
+                Some("Восстановить снимок"),
+                parent.as_ref(),
+                gtk::FileChooserAction::Open,
+                &[("Отмена", gtk::ResponseType::Cancel), ("Открыть", gtk::ResponseType::Accept)],
+            );
+            chooser.set_modal(true);
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some("Снимки Mondis (*.json)"));
+            filter.add_pattern("*.json");
+            chooser.add_filter(&filter);
+            if let Ok(dir) = snapshot::snapshots_dir() {
+                let _ = chooser.set_current_folder(Some(&gtk::gio::File::for_path(dir)));
+            }
+            let apply_snapshot = apply_snapshot.clone();
+            chooser.connect_response(clone!(@weak snap_status => move |chooser, response| {
+                let path = chooser.file().and_then(|f| f.path());
+                chooser.close();
+                if response != gtk::ResponseType::Accept {
+                    return;
+                }
+                let Some(path) = path else { return };
+                match snapshot::load_snapshot(&path) {
+                    Ok(snap) => apply_snapshot(snap),
+                    Err(e) => snap_status.set_text(&format!("Ошибка чтения снимка: {e:#}")),
+                }
+            }));
+            chooser.present();
+        }));
+    }
+    
+    if let Some(ref osd_lang) = details.osd_language {
+        add_info_row(&vbox, "Язык OSD:", osd_lang);
+    }
+    
+    // Поддерживаемые VCP коды
+    if !details.supported_vcp_codes.is_empty() {
+        let separator = Separator::new(Orientation::Horizontal);
+        separator.set_margin_top(12);
+        separator.set_margin_bottom(12);
+        vbox.append(&separator);
+        
+        let vcp_label = Label::new(Some("Поддерживаемые VCP коды:"));
+        vcp_label.set_xalign(0.0);
+        vcp_label.set_markup("<b>Поддерживаемые VCP коды:</b>");
+        vbox.append(&vcp_label);
+        
+        let mut vcp_codes = details.supported_vcp_codes.clone();
+        vcp_codes.sort();
+        let vcp_text = vcp_codes.iter()
+            .map(|code| format!("0x{:02X}", code))
+            .collect::<Vec<_>>()
+            .join(", ");
+        
+        let vcp_value_label = Label::new(Some(&vcp_text));
+        vcp_value_label.set_xalign(0.0);
+        vcp_value_label.set_wrap(true);
+        vcp_value_label.set_selectable(true);
+        vbox.append(&vcp_value_label);
+    }
+    
+
+    scrolled.set_child(Some(&overlay));
+    scrolled
+}
+
+// Название значения для списка выбора в разделе «Все параметры»
+fn vcp_value_label(code: u8, value: u8) -> String {
+    let name = match code {
+        color::VCP_COLOR_PRESET => color::preset_name(value),
+        VCP_INPUT_SOURCE => inputs::standard_name(value),
+        VCP_POWER_MODE => PowerMode::from_vcp(value as u16).map(PowerMode::label),
+        VCP_AUDIO_MUTE => audio::is_muted(value as u16).map(|muted| if muted { "Звук выключен" } else { "Звук включен" }),
+        _ => None,
+    };
+    match name {
+        Some(name) => format!("{} (0x{:02X})", name, value),
+        None => format!("0x{:02X}", value),
+    }
+}
+
+// Код VCP из поля ввода: шестнадцатеричный, с префиксом 0x или без
+fn parse_vcp_code(text: &str) -> Option<u8> {
+    let text = text.trim();
+    let hex = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+    u8::from_str_radix(hex, 16).ok()
+}
+
+// Источник входа (0x60): список входов из capabilities, переключение сразу (без отката по таймеру —
+// после переключения на другой компьютер подтвердить изменение некому) и метки пользователя
+fn append_input_source_row(container: &GtkBox, details: &MonitorDetails, bus: u8) {
+    let mut values = details.input_values.clone();
+    if let Some(current) = details.current_input {
+        if !values.contains(&current) {
+            values.push(current);
+        }
+    }
+    if values.is_empty() {
+        if let Some(ref input_source) = details.current_input_source {
+            add_info_row(container, "Источник входа:", input_source);
+        }
+        return;
+    }
+    let values = Rc::new(values);
+    let settings = read_settings();
+    let display = &details.display;
+    let labels: Vec<String> = values.iter().map(|&v| inputs::input_label(&settings, display, v)).collect();
+
+    let row = GtkBox::new(Orientation::Horizontal, 12);
+    let l = Label::new(Some("Источник входа:"));
+    l.set_xalign(0.0);
+    l.set_width_chars(20);
+    row.append(&l);
+
+    let model = gtk::StringList::new(&labels.iter().map(String::as_str).collect::<Vec<_>>());
+    let dropdown = gtk::DropDown::new(Some(model.clone()), gtk::Expression::NONE);
+    dropdown.set_hexpand(true);
+    if let Some(pos) = details.current_input.and_then(|c| values.iter().position(|&v| v == c)) {
+        dropdown.set_selected(pos as u32);
+    }
+    // Программная смена выбора (опрос, переименование, откат) не должна переключать вход
+    let suppress = Rc::new(Cell::new(false));
+    // Вход, выбранный сейчас: исходное значение для отката переключения
+    let current = Rc::new(Cell::new(details.current_input));
+    {
+        let values = values.clone();
+        let suppress = suppress.clone();
+        let current = current.clone();
+        dropdown.connect_selected_notify(move |dd| {
+            if suppress.get() { return; }
+            if let Some(&value) = values.get(dd.selected() as usize) {
+                println!("Bus {}: switching input to 0x{:02X}", bus, value);
+                let original = current.replace(Some(value));
+                record_change(
+                    Setting::Vcp { bus, code: VCP_INPUT_SOURCE },
+                    || original.map(|v| Value::Raw(v as u16)),
+                    Some(Value::Raw(value as u16)),
+                );
+                queue_vcp_write(bus, VCP_INPUT_SOURCE, value as u16);
+            }
+        });
+    }
+    // Откат переключения (в том числе из заголовка «Вход») возвращает выбор
+    {
+        let values = values.clone();
+        let suppress = suppress.clone();
+        let current = current.clone();
+        let dropdown_weak = dropdown.downgrade();
+        register_setting_view(&dropdown, move |setting, value| {
+            let (Setting::Vcp { bus: b, code: VCP_INPUT_SOURCE }, Value::Raw(raw)) = (setting, value) else { return };
+            let Some(dd) = dropdown_weak.upgrade().filter(|_| *b == bus) else { return };
+            if let Some(pos) = values.iter().position(|&v| v as u16 == raw & 0xFF) {
+                current.set(Some(values[pos]));
+                suppress.set(true);
+                dd.set_selected(pos as u32);
+                suppress.set(false);
+            }
+        });
+    }
+    let dropdown_weak = dropdown.downgrade();
+    on_write_result(bus, VCP_INPUT_SOURCE, move |result| {
+        let Some(dd) = dropdown_weak.upgrade() else { return false };
+        match &result.result {
+            Ok(()) => dd.set_tooltip_text(None),
+            Err(e) => dd.set_tooltip_text(Some(&format!("Ошибка записи: {}", e))),
+        }
+        true
+    });
+    // Вход, переключенный кнопками монитора или другим компьютером
+    {
+        let values = values.clone();
+        let suppress = suppress.clone();
+        let dropdown_weak = dropdown.downgrade();
+        poll_control(bus, VCP_INPUT_SOURCE, &dropdown, move |raw| {
+            let Some(dd) = dropdown_weak.upgrade() else { return };
+            if let Some(pos) = values.iter().position(|&v| v as u16 == raw & 0xFF) {
+                current.set(Some(values[pos]));
+                if dd.selected() != pos as u32 {
+                    suppress.set(true);
+                    dd.set_selected(pos as u32);
+                    suppress.set(false);
+                }
+            }
+        });
+    }
+    row.append(&dropdown);
+    container.append(&row);
+
+    // Метки входов: сохраняются в settings.json и используются треем и CLI
+    let expander = gtk::Expander::new(Some("Названия входов"));
+    let labels_box = GtkBox::new(Orientation::Vertical, 6);
+    labels_box.set_margin_top(6);
+    for (pos, &value) in values.iter().enumerate() {
+        let label_row = GtkBox::new(Orientation::Horizontal, 12);
+        let standard = inputs::standard_name(value).map(str::to_string).unwrap_or_else(|| format!("0x{:02X}", value));
+        let name_lbl = Label::new(Some(&standard));
+        name_lbl.set_xalign(0.0);
+        name_lbl.set_width_chars(20);
+        label_row.append(&name_lbl);
+
+        let entry = gtk::Entry::new();
+        entry.set_hexpand(true);
+        entry.set_placeholder_text(Some(&standard));
+        if let Some(custom) = settings.monitor(display).and_then(|m| m.input_labels.get(&value)) {
+            entry.set_text(custom);
+        }
+        let save = {
+            let model = model.clone();
+            let dropdown = dropdown.clone();
+            let suppress = suppress.clone();
+            let display = display.clone();
+            move |entry: &gtk::Entry| {
+                let text = entry.text().trim().to_string();
+                let saved = update_settings(|settings| {
+                    let labels = &mut settings.monitor_mut(&display).input_labels;
+                    if labels.get(&value).map(String::as_str).unwrap_or_default() == text { return None; }
+                    if text.is_empty() { labels.remove(&value); } else { labels.insert(value, text); }
+                    Some(inputs::input_label(settings, &display, value))
+                });
+                let label = match saved {
+                    Ok(Some(label)) => label,
+                    Ok(None) => return,
+                    Err(e) => {
+                        println!("Failed to save input labels: {e:#}");
+                        return;
+                    }
+                };
+                // Обновляем строку в списке, сохраняя выбор
+                let selected = dropdown.selected();
+                suppress.set(true);
+                model.splice(pos as u32, 1, &[label.as_str()]);
+                dropdown.set_selected(selected);
+                suppress.set(false);
+            }
+        };
+        entry.connect_activate(save.clone());
+        let focus = gtk::EventControllerFocus::new();
+        let entry_weak = entry.downgrade();
+        focus.connect_leave(move |_| {
+            if let Some(entry) = entry_weak.upgrade() { save(&entry); }
+        });
+        entry.add_controller(focus);
+        label_row.append(&entry);
+        labels_box.append(&label_row);
+    }
+    expander.set_child(Some(&labels_box));
+    container.append(&expander);
+}
+
+// DPMS в отдельном потоке: действует на все мониторы экрана X11
+fn force_dpms(mode: PowerMode) {
+    thread::spawn(move || match mondis_x11::dpms::force_level(mode) {
+        Ok(()) => println!("DPMS: {} (all monitors)", mode.name()),
+        Err(e) => println!("DPMS {} failed: {e:#}", mode.name()),
+    });
+}
+
+// Питание (0xD6): кнопки режимов; без DDC — DPMS. Ошибка записи DDC только показывается:
+// DPMS вместо нее выключил бы и остальные мониторы
+fn append_power_row(container: &GtkBox, details: &MonitorDetails, bus: Option<u8>) {
+    let ddc_bus = bus.filter(|_| details.supported_vcp_codes.is_empty() || details.supported_vcp_codes.contains(&VCP_POWER_MODE));
+    let row = GtkBox::new(Orientation::Horizontal, 12);
+    let l = Label::new(Some("Питание:"));
+    l.set_xalign(0.0);
+    l.set_width_chars(20);
+    row.append(&l);
+
+    let state_text = |mode: Option<PowerMode>| mode.map(PowerMode::label).unwrap_or("--").to_string();
+    let state_lbl = Label::new(Some(&details.current_power.map(|m| m.label().to_string())
+        .or_else(|| details.current_power_state.clone())
+        .unwrap_or_else(|| "--".to_string())));
+    state_lbl.set_xalign(0.0);
+    state_lbl.set_hexpand(true);
+    row.append(&state_lbl);
+
+    for (mode, text) in [(PowerMode::On, "Вкл"), (PowerMode::Standby, "Ожидание"), (PowerMode::Off, "Выкл")] {
+        let btn = Button::with_label(text);
+        if ddc_bus.is_none() {
+            btn.set_tooltip_text(Some("Нет DDC/CI: DPMS действует на все мониторы"));
+        }
+        btn.connect_clicked(move |_| match ddc_bus {
+            Some(bus) => {
+                println!("Bus {}: power {}", bus, mode.name());
+                record_power_change(bus, None, mode);
+                queue_vcp_write(bus, VCP_POWER_MODE, mode.vcp_value());
+            }
+            None => force_dpms(mode),
+        });
+        row.append(&btn);
+    }
+    container.append(&row);
+
+    let Some(bus) = ddc_bus else { return };
+    let state_weak = state_lbl.downgrade();
+    on_write_result(bus, VCP_POWER_MODE, move |result| {
+        let Some(lbl) = state_weak.upgrade() else { return false };
+        match &result.result {
+            Ok(()) => {
+                lbl.set_tooltip_text(None);
+                lbl.set_text(&state_text(PowerMode::from_vcp(result.readback.unwrap_or(result.value))));
+            }
+            Err(e) => lbl.set_tooltip_text(Some(&format!("Ошибка DDC: {}", e))),
+        }
+        true
+    });
+    let state_weak = state_lbl.downgrade();
+    poll_control(bus, VCP_POWER_MODE, &state_lbl, move |raw| {
+        if let Some(lbl) = state_weak.upgrade() {
+            lbl.set_text(&state_text(PowerMode::from_vcp(raw)));
+        }
+    });
+}
+
+fn create_capabilities_page(details: &MonitorDetails) -> ScrolledWindow {
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    scrolled.set_hexpand(true);
+    
+    let vbox = GtkBox::new(Orientation::Vertical, 12);
+    vbox.set_margin_top(12);
+    vbox.set_margin_bottom(12);
+    vbox.set_margin_start(12);
+    vbox.set_margin_end(12);
+    
+    if let Some(ref caps_string) = details.capabilities_string {
         // Заголовок
         let title_label = Label::new(Some("DDC/CI Capabilities String:"));
         title_label.set_xalign(0.0);
@@ -1950,19 +3078,7 @@ fn create_capabilities_page(details: &MonitorDetails) -> ScrolledWindow {
             
             let codes_text = vcp_codes.iter()
                 .map(|code| {
-                    let description = match *code {
-                        0x10 => "Яркость",
-                        0x12 => "Контраст",
-                        0x13 => "Управление подсветкой",
-                        0x14 => "Цветовая температура",
-                        0x16 => "Красный канал",
-                        0x18 => "Зеленый канал",
-                        0x1A => "Синий канал",
-                        0x60 => "Источник входа",
-                        0x62 => "Громкость",
-                        0xD6 => "Состояние питания",
-                        _ => "Неизвестно",
-                    };
+                    let description = vcp::name(*code);
                     format!("0x{:02X} - {}", code, description)
                 })
                 .collect::<Vec<_>>()
@@ -2077,6 +3193,14 @@ fn create_edid_page(details: &MonitorDetails) -> ScrolledWindow {
     vbox.set_margin_start(12);
     vbox.set_margin_end(12);
     
+    if let Some(ref edid) = details.edid {
+        let caps_label = Label::new(None);
+        caps_label.set_xalign(0.0);
+        caps_label.set_markup("<b>Возможности (EDID):</b>");
+        vbox.append(&caps_label);
+        append_edid_capabilities(&vbox, edid);
+    }
+
     let label = Label::new(Some("EDID Hex Dump:"));
     label.set_xalign(0.0);
     label.set_markup("<b>EDID Hex Dump:</b>");
@@ -2115,7 +3239,32 @@ fn create_edid_page(details: &MonitorDetails) -> ScrolledWindow {
     scrolled
 }
 
-fn add_info_row(container: &GtkBox, label: &str, value: &str) {
+// Сводка по расширениям EDID: диапазон частот и HDMI из CEA-861
+fn append_edid_capabilities(vbox: &GtkBox, edid: &Edid) {
+    add_info_row(vbox, "Блоков расширения:", &edid.extension_count.to_string());
+
+    if let Some(limits) = edid.range_limits() {
+        add_info_row(vbox, "Диапазон частот:", &format!(
+            "{}-{} Гц (V), {}-{} кГц (H)",
+            limits.min_v_hz, limits.max_v_hz, limits.min_h_khz, limits.max_h_khz
+        ));
+    }
+
+    if let Some(hdmi) = edid.hdmi() {
+        let (a, b, c, d) = hdmi.physical_address;
+        let mut value = format!("адрес {}.{}.{}.{}", a, b, c, d);
+        if let Some(tmds) = hdmi.max_tmds_mhz {
+            value.push_str(&format!(", TMDS до {} МГц", tmds));
+        }
+        add_info_row(vbox, "HDMI:", &value);
+    }
+
+    if !edid.checksum_errors.is_empty() {
+        add_info_row(vbox, "Ошибки контрольной суммы:", &format!("блоки {:?}", edid.checksum_errors));
+    }
+}
+
+fn add_info_row(container: &GtkBox, label: &str, value: &str) -> Label {
     let hbox = GtkBox::new(Orientation::Horizontal, 12);
     
     let label_widget = Label::new(Some(label));
@@ -2132,125 +3281,41 @@ fn add_info_row(container: &GtkBox, label: &str, value: &str) {
     hbox.append(&label_widget);
     hbox.append(&value_widget);
     container.append(&hbox);
+    value_widget
 }
 
-fn load_brightness_profile() -> Result<HashMap<u8, u8>, String> {
-    let profile_path = get_profile_path()?;
-    
-    if !profile_path.exists() {
-        return Ok(HashMap::new()); // Пустой профиль если файл не существует
-    }
-    
-    let content = fs::read_to_string(&profile_path)
-        .map_err(|e| format!("Failed to read profile: {}", e))?;
-    
-    let mut brightness_values = HashMap::new();
-    
-    // Простой парсинг XML (можно заменить на полноценный XML парсер)
-    for line in content.lines() {
-        if line.trim().starts_with("<display") {
-            if let (Some(bus_start), Some(brightness_start)) = (
-                line.find("bus=\"").map(|i| i + 5),
-                line.find("brightness=\"").map(|i| i + 12)
-            ) {
-                if let (Some(bus_end), Some(brightness_end)) = (
-                    line[bus_start..].find('"').map(|i| i + bus_start),
-                    line[brightness_start..].find('"').map(|i| i + brightness_start)
-                ) {
-                    if let (Ok(bus), Ok(brightness)) = (
-                        line[bus_start..bus_end].parse::<u8>(),
-                        line[brightness_start..brightness_end].parse::<u8>()
-                    ) {
-                        brightness_values.insert(bus, brightness);
-                    }
-                }
+// Чтение значений строк в рабочем потоке (через ту же блокировку шины, что и очередь записи);
+// каждое значение показывается по приходу. Когда owner закрыт, чтение останавливается
+fn read_deferred(bus: u8, owner: &impl IsA<gtk::Widget>, rows: Vec<(u8, DeferredRead)>) {
+    if rows.is_empty() {
+        return;
+    }
+    let owner = owner.as_ref().downgrade();
+    let codes: Vec<u8> = rows.iter().map(|(code, _)| *code).collect();
+    let (tx, rx) = async_channel::unbounded::<(u8, Result<(u16, u16), String>)>();
+    thread::spawn(move || {
+        for code in codes {
+            if tx.send_blocking((code, read_vcp_value(bus, code))).is_err() {
+                break;
             }
         }
-    }
-    
-    println!("Loaded brightness profile: {:?}", brightness_values);
-    Ok(brightness_values)
+    });
+    glib::spawn_future_local(async move {
+        let mut rows: HashMap<u8, DeferredRead> = rows.into_iter().collect();
+        while let Ok((code, result)) = rx.recv().await {
+            if owner.upgrade().is_none() {
+                break;
+            }
+            if let Some(show) = rows.remove(&code) {
+                show(result);
+            }
+        }
+    });
 }
 
 fn ddc_get_brightness(i2c_bus: u8) -> Result<u8, String> {
-    let device_path = format!("/dev/i2c-{}", i2c_bus);
-    let mut dev = LinuxI2CDevice::new(&device_path, DDC_ADDR)
-        .map_err(|e| format!("Failed to open {}: {}", device_path, e))?;
-    
-    // DDC Get VCP request: [source_addr, length, type, vcp_code, checksum]
-    let request = [0x51, 0x02, 0x01, VCP_BRIGHTNESS];
-    let checksum = 0x6E ^ request.iter().fold(0u8, |acc, &x| acc ^ x);
-    let full_request = [request[0], request[1], request[2], request[3], checksum];
-    
-    dev.write(&full_request)
-        .map_err(|e| format!("Failed to write DDC request: {}", e))?;
-    
-    // Small delay for DDC response
-    thread::sleep(Duration::from_millis(50));
-    
-    // Read response: [dest, length, type, result, vcp_code, type_flag, max_hi, max_lo, cur_hi, cur_lo, checksum]
-    let mut response = [0u8; 12];
-    dev.read(&mut response)
-        .map_err(|e| format!("Failed to read DDC response: {}", e))?;
-    
-    // Parse current brightness value (bytes 8-9)
-    if response.len() >= 10 && response[4] == VCP_BRIGHTNESS {
-        let current_value = ((response[8] as u16) << 8) | (response[9] as u16);
-        Ok((current_value & 0xFF) as u8)
-    } else {
-        Err("Invalid DDC response".to_string())
-    }
-}
-
-fn parse_edid_manufacturer(edid_data: &[u8]) -> String {
-    if edid_data.len() < 10 {
-        return "Unknown".to_string();
-    }
-    
-    // Manufacturer ID is at bytes 8-9 (big endian)
-    let mfg_id = ((edid_data[8] as u16) << 8) | (edid_data[9] as u16);
-    
-    // Extract 3 letters (5 bits each)
-    let letter1 = ((mfg_id >> 10) & 0x1F) as u8;
-    let letter2 = ((mfg_id >> 5) & 0x1F) as u8;
-    let letter3 = (mfg_id & 0x1F) as u8;
-    
-    // Convert to ASCII (A=1, B=2, etc.)
-    if letter1 > 0 && letter2 > 0 && letter3 > 0 {
-        format!("{}{}{}", 
-            (letter1 + b'A' - 1) as char,
-            (letter2 + b'A' - 1) as char,
-            (letter3 + b'A' - 1) as char
-        )
-    } else {
-        "Unknown".to_string()
-    }
-}
-
-fn parse_edid_model(edid_data: &[u8]) -> String {
-    if edid_data.len() < 128 {
-        return "Unknown Monitor".to_string();
-    }
-    
-    // Look for descriptor blocks starting at byte 54
-    for i in (54..126).step_by(18) {
-        if edid_data[i] == 0x00 && edid_data[i+1] == 0x00 && edid_data[i+2] == 0x00 {
-            // This is a display descriptor
-            if edid_data[i+3] == 0xFC { // Monitor name descriptor
-                let name_bytes = &edid_data[i+5..i+18];
-                let name = String::from_utf8_lossy(name_bytes)
-                    .trim_end_matches('\0')
-                    .trim_end_matches('\n')
-                    .trim()
-                    .to_string();
-                if !name.is_empty() {
-                    return name;
-                }
-            }
-        }
-    }
-    
-    "Monitor".to_string()
+    let (cur, _max) = ddc_i2c::get_vcp(i2c_bus, VCP_BRIGHTNESS).map_err(|e| format!("{e:#}"))?;
+    Ok(cur.min(u8::MAX as u16) as u8)
 }
 
 fn get_all_edids_from_sysfs() -> Vec<(String, Vec<u8>, String, String)> {
@@ -2265,8 +3330,7 @@ fn get_all_edids_from_sysfs() -> Vec<(String, Vec<u8>, String, String)> {
                     let edid_path = connector_path.join("edid");
                     if let Ok(edid_data) = std::fs::read(&edid_path) {
                         if edid_data.len() >= 128 && edid_data[0] == 0x00 && edid_data[1] == 0xFF {
-                            let manufacturer = parse_edid_manufacturer(&edid_data);
-                            let model = parse_edid_model(&edid_data);
+                            let (manufacturer, model) = edid_manufacturer_model(&edid_data);
                             println!("Found EDID in {}: {} {}", name, manufacturer, model);
                             list.push((name.to_string(), edid_data, manufacturer, model));
                         }
@@ -2279,54 +3343,12 @@ fn get_all_edids_from_sysfs() -> Vec<(String, Vec<u8>, String, String)> {
 }
 
 fn read_edid_raw(i2c_bus: u8) -> Result<Vec<u8>, String> {
-    let device_path = format!("/dev/i2c-{}", i2c_bus);
-    let mut dev = LinuxI2CDevice::new(&device_path, EDID_ADDR)
-        .map_err(|e| format!("Failed to open {} for EDID: {}", device_path, e))?;
-    
-    // Write EDID offset (0x00) first
-    dev.write(&[0x00])
-        .map_err(|e| format!("Failed to write EDID offset: {}", e))?;
-    
-    thread::sleep(Duration::from_millis(10));
-    
-    // Read EDID (128 bytes minimum)
-    let mut edid_data = vec![0u8; 128];
-    dev.read(&mut edid_data)
-        .map_err(|e| format!("Failed to read EDID: {}", e))?;
-    
-    Ok(edid_data)
+    mondis_ddc::edid::read_edid(i2c_bus).map_err(|e| format!("Failed to read EDID: {e:#}"))
 }
 
-fn read_edid(i2c_bus: u8) -> Result<(String, String, String), String> {
-    let device_path = format!("/dev/i2c-{}", i2c_bus);
-    let mut dev = LinuxI2CDevice::new(&device_path, EDID_ADDR)
-        .map_err(|e| format!("Failed to open {} for EDID: {}", device_path, e))?;
-    
-    // Write EDID offset (0x00) first, like ddcutil does
-    dev.write(&[0x00])
-        .map_err(|e| format!("Failed to write EDID offset: {}", e))?;
-    
-    // Small delay
-    thread::sleep(Duration::from_millis(10));
-    
-    // Read EDID (128 bytes minimum)
-    let mut edid_data = [0u8; 128];
-    dev.read(&mut edid_data)
-        .map_err(|e| format!("Failed to read EDID: {}", e))?;
-    
-    // Debug: print first few bytes
-    println!("Bus {}: EDID header: {:02X} {:02X} {:02X} {:02X} {:02X} {:02X} {:02X} {:02X}", 
-        i2c_bus, edid_data[0], edid_data[1], edid_data[2], edid_data[3], 
-        edid_data[4], edid_data[5], edid_data[6], edid_data[7]);
-    
-    // Check EDID header (should start with 00 FF FF FF FF FF FF 00)
-    if edid_data[0] != 0x00 || edid_data[1] != 0xFF || edid_data[7] != 0x00 {
-        return Err(format!("Invalid EDID header: {:02X} {:02X} ... {:02X}", 
-            edid_data[0], edid_data[1], edid_data[7]));
-    }
-    
-    let manufacturer = parse_edid_manufacturer(&edid_data);
-    let model = parse_edid_model(&edid_data);
+// Производитель, модель и серийный номер из уже прочитанного EDID
+fn edid_identity(i2c_bus: u8, edid_data: &[u8]) -> (String, String, String) {
+    let (manufacturer, model) = edid_manufacturer_model(edid_data);
     let serial = format!("{:08X}", 
         ((edid_data[12] as u32) << 24) | 
         ((edid_data[13] as u32) << 16) | 
@@ -2337,166 +3359,26 @@ fn read_edid(i2c_bus: u8) -> Result<(String, String, String), String> {
     println!("Bus {}: Parsed - Manufacturer: {}, Model: {}, Serial: {}", 
         i2c_bus, manufacturer, model, serial);
     
-    Ok((manufacturer, model, serial))
-}
-
-fn get_gpu_model_name(vendor_id: &str, device_id: &str) -> Option<String> {
-    // Mapping common GPU device IDs to model names
-    match (vendor_id, device_id) {
-        // NVIDIA RTX 40 series
-        ("0x10de", "0x2684") => Some("RTX 4090".to_string()),
-        ("0x10de", "0x2782") => Some("RTX 4070 Ti".to_string()),
-        ("0x10de", "0x2786") => Some("RTX 4070".to_string()),
-        ("0x10de", "0x2788") => Some("RTX 4060 Ti".to_string()),
-        ("0x10de", "0x28e0") => Some("RTX 4060".to_string()),
-        
-        // NVIDIA RTX 30 series
-        ("0x10de", "0x2204") => Some("RTX 3090".to_string()),
-        ("0x10de", "0x2206") => Some("RTX 3080".to_string()),
-        ("0x10de", "0x2484") => Some("RTX 3070".to_string()),
-        ("0x10de", "0x2504") => Some("RTX 3060".to_string()),
-        ("0x10de", "0x2487") => Some("RTX 3060 Ti".to_string()),
-        
-        // NVIDIA RTX 20 series
-        ("0x10de", "0x1e04") => Some("RTX 2080 Ti".to_string()),
-        ("0x10de", "0x1e07") => Some("RTX 2080".to_string()),
-        ("0x10de", "0x1f02") => Some("RTX 2070".to_string()),
-        ("0x10de", "0x1f06") => Some("RTX 2060".to_string()),
-        
-        // NVIDIA GTX 16 series
-        ("0x10de", "0x2182") => Some("GTX 1660 Ti".to_string()),
-        ("0x10de", "0x21c4") => Some("GTX 1660".to_string()),
-        ("0x10de", "0x1f82") => Some("GTX 1650".to_string()),
-        
-        // AMD RX 7000 series
-        ("0x1002", "0x744c") => Some("RX 7900 XTX".to_string()),
-        ("0x1002", "0x7448") => Some("RX 7900 XT".to_string()),
-        ("0x1002", "0x747e") => Some("RX 7800 XT".to_string()),
-        ("0x1002", "0x7479") => Some("RX 7700 XT".to_string()),
-        
-        // AMD RX 6000 series
-        ("0x1002", "0x73bf") => Some("RX 6900 XT".to_string()),
-        ("0x1002", "0x73df") => Some("RX 6800 XT".to_string()),
-        ("0x1002", "0x73ef") => Some("RX 6800".to_string()),
-        ("0x1002", "0x73ff") => Some("RX 6700 XT".to_string()),
-        ("0x1002", "0x7421") => Some("RX 6600 XT".to_string()),
-        ("0x1002", "0x73e3") => Some("RX 6600".to_string()),
-        
-        // Intel Arc
-        ("0x8086", "0x56a0") => Some("Arc A770".to_string()),
-        ("0x8086", "0x56a1") => Some("Arc A750".to_string()),
-        ("0x8086", "0x56a5") => Some("Arc A380".to_string()),
-        
-        _ => None,
-    }
-}
-
-fn get_gpu_name_from_lspci() -> Option<String> {
-    // Try to get GPU name from lspci command
-    let output = Command::new("lspci")
-        .arg("-nn")
-        .output()
-        .ok()?;
-    
-    if !output.status.success() {
-        return None;
-    }
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    
-    // Parse lspci output to find VGA/Display controller
-    for line in stdout.lines() {
-        if line.contains("VGA compatible controller") || line.contains("Display controller") {
-            // Extract GPU name from line like:
-            // "01:00.0 VGA compatible controller [0300]: NVIDIA Corporation GA106 [GeForce RTX 3060 Lite Hash Rate] [10de:2504] (rev a1)"
-            if let Some(colon_pos) = line.find(": ") {
-                let gpu_part = &line[colon_pos + 2..];
-                // Remove the final PCI ID part [10de:2504] and revision
-                let gpu_name = gpu_part
-                    .split(" [")
-                    .last()
-                    .unwrap_or(gpu_part)
-                    .split(']')
-                    .next()
-                    .unwrap_or("")
-                    .trim();
-                
-                if !gpu_name.is_empty() {
-                    // Extract just the model name from full string
-                    // "NVIDIA Corporation GA106 [GeForce RTX 3060 Lite Hash Rate]" -> "GeForce RTX 3060 Lite Hash Rate"
-                    if let Some(bracket_start) = gpu_part.find('[') {
-                        if let Some(bracket_end) = gpu_part.find(']') {
-                            let model_name = &gpu_part[bracket_start + 1..bracket_end];
-                            if !model_name.is_empty() {
-                                // Get vendor from the beginning
-                                let vendor_part = &gpu_part[..bracket_start].trim();
-                                let vendor = if vendor_part.contains("NVIDIA") {
-                                    "NVIDIA"
-                                } else if vendor_part.contains("AMD") || vendor_part.contains("ATI") {
-                                    "AMD"
-                                } else if vendor_part.contains("Intel") {
-                                    "Intel"
-                                } else {
-                                    "Unknown"
-                                };
-                                return Some(format!("{} {}", vendor, model_name));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    None
+    (manufacturer, model, serial)
 }
 
 fn get_gpu_name_from_card(card_num: u8) -> String {
-    // First try to get name from lspci (most accurate)
-    if let Some(lspci_name) = get_gpu_name_from_lspci() {
-        println!("GPU info for card{}: {} (from lspci)", card_num, lspci_name);
-        return lspci_name;
-    }
-    
-    // Fallback to sysfs PCI ID lookup
-    let device_path = format!("/sys/class/drm/card{}/device", card_num);
-    
-    // Try to read vendor and device IDs
-    if let (Ok(vendor), Ok(device)) = (
-        std::fs::read_to_string(format!("{}/vendor", device_path)),
-        std::fs::read_to_string(format!("{}/device", device_path))
-    ) {
-        let vendor_id = vendor.trim();
-        let device_id = device.trim();
-        
-        // Try to get specific model name from our table
-        if let Some(model) = get_gpu_model_name(vendor_id, device_id) {
-            let vendor_name = match vendor_id {
-                "0x10de" => "NVIDIA",
-                "0x1002" => "AMD",
-                "0x8086" => "Intel",
-                _ => "Unknown",
+    // Имя по pci.ids (вендор, модель, производитель платы) и PCI-слот из sysfs
+    match pci::GpuInfo::from_card(&format!("card{}", card_num)) {
+        Some(gpu) => {
+            let result = if gpu.slot.is_empty() {
+                gpu.display_name()
+            } else {
+                format!("{} · {}", gpu.display_name(), gpu.slot)
             };
-            let result = format!("{} {}", vendor_name, model);
-            println!("GPU info for card{}: {} (from PCI ID table)", card_num, result);
-            return result;
+            println!("GPU info for card{}: {} [{:04x}:{:04x}]", card_num, result, gpu.vendor_id, gpu.device_id);
+            result
+        }
+        None => {
+            let result = format!("Card {}", card_num);
+            println!("GPU info for card{}: {} (no vendor/device info)", card_num, result);
+            result
         }
-        
-        // Fallback to generic vendor name
-        let vendor_name = match vendor_id {
-            "0x10de" => "NVIDIA",
-            "0x1002" => "AMD", 
-            "0x8086" => "Intel",
-            _ => "Unknown GPU",
-        };
-        
-        let result = format!("{} Card {}", vendor_name, card_num);
-        println!("GPU info for card{}: {} (generic fallback)", card_num, result);
-        result
-    } else {
-        let result = format!("Card {}", card_num);
-        println!("GPU info for card{}: {} (no vendor/device info)", card_num, result);
-        result
     }
 }
 
@@ -2791,144 +3673,245 @@ fn get_brightness_with_pref(display: &DisplayInfo, pref: Option<ControlMethodPre
         _ => get_brightness_any_method(display),
     }
 }
+// Параметр транзакции, которым меняется яркость монитора при выбранном методе (как в set_brightness_with_pref)
+fn brightness_setting(display: &DisplayInfo, pref: Option<ControlMethodPref>) -> Option<Setting> {
+    if display.supports_ddc && pref != Some(ControlMethodPref::Xrandr) {
+        Some(Setting::Vcp { bus: display.i2c_bus, code: VCP_BRIGHTNESS })
+    } else {
+        display.xrandr_output.clone().map(|output| Setting::XrandrBrightness { output })
+    }
+}
+
+// Яркость в процентах как значение параметра: по DDC процент пишется как есть
+fn brightness_value(setting: &Setting, percent: u8) -> Value {
+    match setting {
+        Setting::Vcp { .. } => Value::Raw(percent as u16),
+        _ => Value::Percent(percent),
+    }
+}
+
 fn ddc_set_brightness(i2c_bus: u8, value: u8) -> Result<(), String> {
-    let device_path = format!("/dev/i2c-{}", i2c_bus);
-    let mut dev = LinuxI2CDevice::new(&device_path, DDC_ADDR)
-        .map_err(|e| format!("Failed to open {}: {}", device_path, e))?;
-    
-    // DDC Set VCP request: [source_addr, length, type, vcp_code, hi_byte, lo_byte]
-    let request = [0x51, 0x04, 0x03, VCP_BRIGHTNESS, 0x00, value];
-    let checksum = 0x6E ^ request.iter().fold(0u8, |acc, &x| acc ^ x);
-    let full_request = [request[0], request[1], request[2], request[3], request[4], request[5], checksum];
-    
-    dev.write(&full_request)
-        .map_err(|e| format!("Failed to write DDC set request: {}", e))?;
-    
-    Ok(())
+    ddc_i2c::set_vcp(i2c_bus, VCP_BRIGHTNESS, value as u16).map_err(|e| format!("{e:#}"))
 }
 
-fn detect_i2c_displays() -> Result<Vec<DisplayInfo>, String> {
-    let mut displays = Vec::new();
-    // Build sysfs EDID map: connector -> edid bytes and parsed info
-    let sysfs = get_all_edids_from_sysfs();
-
-    // Scan common I2C buses (typically 0-10)
-    for bus in 0..=10 {
-        let device_path = format!("/dev/i2c-{}", bus);
-        if !std::path::Path::new(&device_path).exists() { continue; }
-
-        // Probe DDC capability
-        let supports_ddc = ddc_get_brightness(bus).is_ok();
-
-        // Try read EDID via I2C for identification
-        let (manufacturer, model, serial, edid_hash_opt, connector_opt) = match read_edid(bus) {
-            Ok((mfg, mdl, ser)) => {
-                // we need the raw bytes to compute hash and match connector; re-read from device
-                let dev_path = format!("/dev/i2c-{}", bus);
-                let mut dev = LinuxI2CDevice::new(&dev_path, EDID_ADDR)
-                    .map_err(|e| format!("Failed to open {} for EDID hash: {}", dev_path, e))?;
-                let _ = dev.write(&[0x00]);
-                thread::sleep(Duration::from_millis(5));
-                let mut edid_bytes = [0u8; 128];
-                if let Err(e) = dev.read(&mut edid_bytes) {
-                    println!("Warn: failed to reread EDID for hash on bus {}: {}", bus, e);
-                }
-                let hash = edid_hash_short(&edid_bytes);
-                // Match to sysfs connector by EDID equality
-                let mut connector: Option<String> = None;
-                for (conn_name, sys_edid, _smfg, _smdl) in &sysfs {
-                    if edid_matches(&edid_bytes, sys_edid) {
-                        connector = Some(conn_name.clone());
-                        break;
-                    }
-                }
-                (Some(mfg), Some(mdl), Some(ser), Some(hash), connector)
-            },
-            Err(e) => {
-                println!("Bus {}: EDID read failed: {}", bus, e);
-                (None, None, None, None, None)
-            }
-        };
+// Отрисовка строки монитора в контейнере секции видеокарты
+type DisplayRowRenderer = Rc<dyn Fn(&GtkBox, DisplayInfo)>;
+
+// Событие потокового обнаружения мониторов
+enum DetectEvent {
+    // Начат опрос шин: (шина, коннектор)
+    Probing(Vec<(u8, Option<String>)>),
+    Found(DisplayInfo),
+    // На шине нет монитора (строка монитора, если была, удаляется)
+    Finished(u8),
+    // Шина не ответила вовремя
+    TimedOut(u8),
+    Done,
+}
 
-        // Determine xrandr output by connector mapping (port-first strategy)
-        let xrandr_output = connector_opt.as_ref().and_then(|c| get_xrandr_output_for_connector(c));
-        
-        // Fallback: if no connector found in sysfs, try to match by EDID with active xrandr outputs
-        let xrandr_output = if xrandr_output.is_none() && edid_hash_opt.is_some() {
-            let mut matched_output = None;
-            // Get active xrandr outputs and try to match by manufacturer/model
-            let xrandr_outputs = get_xrandr_outputs();
-            for (output_name, xrandr_edid) in &xrandr_outputs {
-                // Try to match by EDID
-                if let Ok(bus_edid) = read_edid_raw(bus) {
-                    if edid_matches(&bus_edid, xrandr_edid) {
-                        println!("Bus {}: Matched to xrandr output {} by EDID", bus, output_name);
-                        matched_output = Some(output_name.clone());
-                        break;
-                    }
+// Пауза после последнего hotplug-события перед повторным опросом
+const HOTPLUG_QUIET: Duration = Duration::from_millis(1500);
+
+// Шины для повторного опроса после проверки кэша; None — какой-то коннектор без шины, нужен полный детект
+fn stale_cache_buses(check: &cache::CacheValidation) -> Option<Vec<u8>> {
+    let connectors = adapters::connector_map();
+    let mut buses: Vec<u8> = check.stale.iter().map(|d| d.i2c_bus).collect();
+    for conn in &check.new_connectors {
+        buses.push(*connectors.iter().find(|(_, c)| *c == conn)?.0);
+    }
+    buses.sort_unstable();
+    buses.dedup();
+    Some(buses)
+}
+
+// Максимальное время опроса одной шины
+const BUS_PROBE_TIMEOUT: Duration = Duration::from_secs(4);
+
+// Опрос одного адаптера: DDC, EDID, коннектор и вывод XRandR
+fn probe_display_adapter(adapter: &adapters::I2cAdapter, sysfs: &[(String, Vec<u8>, String, String)]) -> Option<DisplayInfo> {
+    let bus = adapter.bus;
+    // Probe DDC capability (кроме мониторов, чья DDC-яркость известна как нерабочая)
+    let supports_ddc = ddc_get_brightness(bus).is_ok() && !timing::bus_quirks(bus).is_hidden(VCP_BRIGHTNESS);
+
+    // Try read EDID via I2C for identification
+    let edid_read = read_edid_raw(bus);
+    let (manufacturer, model, serial, edid_hash_opt, connector_opt) = match edid_read {
+        Ok(ref edid_bytes) => {
+            let (mfg, mdl, ser) = edid_identity(bus, edid_bytes);
+            let hash = edid::hash_short(edid_bytes);
+            // Коннектор по ссылке ddc из sysfs, иначе по совпадению EDID
+            let connector = adapter.connector.clone().or_else(|| {
+                sysfs.iter()
+                    .find(|(_, sys_edid, _, _)| edid_matches(edid_bytes, sys_edid))
+                    .map(|(conn_name, _, _, _)| conn_name.clone())
+            });
+            (Some(mfg), Some(mdl), Some(ser), Some(hash), connector)
+        },
+        Err(ref e) => {
+            println!("Bus {}: EDID read failed: {}", bus, e);
+            (None, None, None, None, None)
+        }
+    };
+
+    // Determine xrandr output by connector mapping (port-first strategy)
+    let xrandr_output = connector_opt.as_ref().and_then(|c| get_xrandr_output_for_connector(c));
+    
+    // Fallback: if no connector found in sysfs, try to match by EDID with active xrandr outputs
+    let xrandr_output = if xrandr_output.is_none() && edid_hash_opt.is_some() {
+        let mut matched_output = None;
+        // Get active xrandr outputs and try to match by manufacturer/model
+        let xrandr_outputs = get_xrandr_outputs();
+        for (output_name, xrandr_edid) in &xrandr_outputs {
+            // Try to match by EDID (already read above)
+            if let Ok(ref bus_edid) = edid_read {
+                if edid_matches(bus_edid, xrandr_edid) {
+                    println!("Bus {}: Matched to xrandr output {} by EDID", bus, output_name);
+                    matched_output = Some(output_name.clone());
+                    break;
                 }
             }
-            matched_output
-        } else {
-            xrandr_output
-        };
-        
-        if let Some(ref xrandr_out) = xrandr_output { println!("Bus {}: xrandr output = {}", bus, xrandr_out); }
+        }
+        matched_output
+    } else {
+        xrandr_output
+    };
+    
+    if let Some(ref xrandr_out) = xrandr_output { println!("Bus {}: xrandr output = {}", bus, xrandr_out); }
 
-        // Parse connector info for card and port names
-        let (card_name, port_name, _card_num) = if let Some(ref conn) = connector_opt {
-            parse_connector_info(conn)
-        } else {
-            (Some("Unknown GPU".to_string()), Some("Unknown Port".to_string()), 0)
-        };
+    // Parse connector info for card and port names
+    let (card_name, port_name, _card_num) = if let Some(ref conn) = connector_opt {
+        parse_connector_info(conn)
+    } else {
+        (Some("Unknown GPU".to_string()), Some("Unknown Port".to_string()), 0)
+    };
 
-        let name = match (&manufacturer, &model) {
-            (Some(mfg), Some(mdl)) => {
-                let expanded_mfg = match mfg.as_str() {
-                    "ACR" => "Acer",
-                    "GSM" => "LG",
-                    "SAM" => "Samsung",
-                    "DEL" => "Dell",
-                    "AUS" => "ASUS",
-                    "BNQ" => "BenQ",
-                    "AOC" => "AOC",
-                    "HPN" => "HP",
-                    "LEN" => "Lenovo",
-                    "MSI" => "MSI",
-                    _ => mfg,
-                };
-                let connector_info = connector_opt.as_ref().map(|c| format!(" • {}", c)).unwrap_or_default();
-                let control_method = if supports_ddc { "DDC" } else if xrandr_output.is_some() { "xrandr" } else { "нет управления" };
-                format!("{} {}{} ({})", expanded_mfg, mdl, connector_info, control_method)
-            }
-            _ => {
-                let control_method = if supports_ddc { "DDC" } else if xrandr_output.is_some() { "xrandr" } else { "нет управления" };
-                format!("I2C Device (bus {}) ({})", bus, control_method)
-            }
-        };
+    let name = match (&manufacturer, &model) {
+        (Some(mfg), Some(mdl)) => {
+            let expanded_mfg = pnp::display_name(mfg);
+            let connector_info = connector_opt.as_ref().map(|c| format!(" • {}", c)).unwrap_or_default();
+            let control_method = if supports_ddc { "DDC" } else if xrandr_output.is_some() { "xrandr" } else { "нет управления" };
+            format!("{} {}{} ({})", expanded_mfg, mdl, connector_info, control_method)
+        }
+        _ => {
+            let control_method = if supports_ddc { "DDC" } else if xrandr_output.is_some() { "xrandr" } else { "нет управления" };
+            format!("I2C Device (bus {}) ({})", bus, control_method)
+        }
+    };
 
-        // Include only if we have EDID identification OR xrandr mapping.
-        // Skip pure DDC-only devices (to avoid phantom monitors without EDID/connector binding).
-        if manufacturer.is_some() || xrandr_output.is_some() {
-            displays.push(DisplayInfo {
-                i2c_bus: bus,
-                name,
-                manufacturer,
-                model,
-                serial,
-                connector: connector_opt,
-                supports_ddc,
-                xrandr_output,
-                edid_hash: edid_hash_opt,
-                card_name,
-                port_name,
-            });
-        } else if supports_ddc {
+    // Include only if we have EDID identification OR xrandr mapping.
+    // Skip pure DDC-only devices (to avoid phantom monitors without EDID/connector binding).
+    if manufacturer.is_some() || xrandr_output.is_some() {
+        Some(DisplayInfo {
+            i2c_bus: bus,
+            name,
+            manufacturer,
+            model,
+            serial,
+            connector: connector_opt,
+            supports_ddc,
+            xrandr_output,
+            edid_hash: edid_hash_opt,
+            card_name,
+            port_name,
+        })
+    } else {
+        if supports_ddc {
             println!("Skipping bus {}: DDC responsive but no EDID and no xrandr output (likely non-display or unsupported EDID)", bus);
         }
+        None
+    }
+}
+
+// Все шины (или только `only`) опрашиваются параллельно; результаты отправляются по мере готовности.
+// Шины, не ответившие за BUS_PROBE_TIMEOUT, пропускаются.
+fn detect_i2c_displays_streaming(tx: async_channel::Sender<DetectEvent>, only: Option<Vec<u8>>) {
+    // Build sysfs EDID map: connector -> edid bytes and parsed info
+    let sysfs = std::sync::Arc::new(get_all_edids_from_sysfs());
+
+    // Только адаптеры видеокарт (по sysfs); SMBus, датчики и т.п. не опрашиваем
+    let adapters: Vec<adapters::I2cAdapter> = adapters::display_adapters()
+        .into_iter()
+        .filter(|a| std::path::Path::new(&format!("/dev/i2c-{}", a.bus)).exists())
+        .filter(|a| only.as_ref().is_none_or(|buses| buses.contains(&a.bus)))
+        .collect();
+    // Запрошенные шины, которых больше нет (адаптер удален вместе с монитором)
+    for bus in only.iter().flatten().filter(|b| !adapters.iter().any(|a| a.bus == **b)) {
+        let _ = tx.send_blocking(DetectEvent::Finished(*bus));
+    }
+    let _ = tx.send_blocking(DetectEvent::Probing(adapters.iter().map(|a| (a.bus, a.connector.clone())).collect()));
+
+    let (res_tx, res_rx) = std::sync::mpsc::channel::<(u8, Option<DisplayInfo>)>();
+    for adapter in &adapters {
+        println!("Bus {}: adapter '{}' ({:?}{})", adapter.bus, adapter.name, adapter.kind,
+            adapter.connector.as_ref().map(|c| format!(", {}", c)).unwrap_or_default());
+        let adapter = adapter.clone();
+        let sysfs = sysfs.clone();
+        let res_tx = res_tx.clone();
+        thread::spawn(move || {
+            let display = probe_display_adapter(&adapter, &sysfs);
+            let _ = res_tx.send((adapter.bus, display));
+        });
+    }
+    drop(res_tx);
+
+    let deadline = std::time::Instant::now() + BUS_PROBE_TIMEOUT;
+    let mut pending: HashSet<u8> = adapters.iter().map(|a| a.bus).collect();
+    while !pending.is_empty() {
+        let left = deadline.saturating_duration_since(std::time::Instant::now());
+        match res_rx.recv_timeout(left) {
+            Ok((bus, display)) => {
+                pending.remove(&bus);
+                let event = match display {
+                    Some(d) => DetectEvent::Found(d),
+                    None => DetectEvent::Finished(bus),
+                };
+                if tx.send_blocking(event).is_err() { return; }
+            }
+            Err(_) => break,
+        }
+    }
+    // Зависшие шины: поток опроса продолжит работу, но результат уже не нужен
+    for bus in pending {
+        println!("Bus {}: probe timed out after {:?}", bus, BUS_PROBE_TIMEOUT);
+        let _ = tx.send_blocking(DetectEvent::TimedOut(bus));
     }
+    let _ = tx.send_blocking(DetectEvent::Done);
+}
+
+// Секция видеокарты: заголовок, контейнер строк мониторов и разделитель
+fn create_card_section(card_name: &str) -> (GtkBox, GtkBox) {
+    let section = GtkBox::new(Orientation::Vertical, 12);
+    let card_header = Label::new(Some(card_name));
+    card_header.set_xalign(0.0);
+    card_header.set_markup(&format!("<b>{}</b>", glib::markup_escape_text(card_name)));
+    section.append(&card_header);
 
-    Ok(displays)
+    let rows = GtkBox::new(Orientation::Vertical, 12);
+    section.append(&rows);
+
+    let separator = Separator::new(Orientation::Horizontal);
+    separator.set_margin_top(8);
+    separator.set_margin_bottom(8);
+    section.append(&separator);
+    (section, rows)
+}
+
+// Строка-заглушка на время опроса шины
+fn create_probe_placeholder(bus: u8, connector: Option<&str>) -> GtkBox {
+    let row = GtkBox::new(Orientation::Horizontal, 8);
+    row.set_margin_start(20);
+    let spinner = gtk::Spinner::new();
+    spinner.start();
+    row.append(&spinner);
+    let text = match connector {
+        Some(c) => format!("Опрос /dev/i2c-{} ({})…", bus, c),
+        None => format!("Опрос /dev/i2c-{}…", bus),
+    };
+    let label = Label::new(Some(&text));
+    label.set_xalign(0.0);
+    label.add_css_class("dim-label");
+    row.append(&label);
+    row
 }
 
 fn group_displays_by_card(displays: Vec<DisplayInfo>) -> Vec<VideoCard> {
@@ -2958,6 +3941,141 @@ fn group_displays_by_card(displays: Vec<DisplayInfo>) -> Vec<VideoCard> {
     result
 }
 
+// Кнопка «Вход» в заголовке: переключение всех мониторов на один вход. Входы мониторов
+// читаются из capabilities при каждом открытии (после hotplug набор мониторов другой)
+fn create_all_inputs_button() -> gtk::MenuButton {
+    let button = gtk::MenuButton::new();
+    button.set_label("Вход");
+    button.set_tooltip_text(Some("Переключить все мониторы на вход"));
+    let popover = gtk::Popover::new();
+    let list = GtkBox::new(Orientation::Vertical, 4);
+    popover.set_child(Some(&list));
+    button.set_popover(Some(&popover));
+
+    let list_for_show = list.clone();
+    popover.connect_show(move |popover| {
+        while let Some(child) = list_for_show.first_child() {
+            list_for_show.remove(&child);
+        }
+        list_for_show.append(&Label::new(Some("Чтение входов мониторов...")));
+
+        // (шина, входы монитора, текущий вход для отката)
+        let (tx, rx) = async_channel::bounded::<Vec<(u8, Vec<u8>, Option<u16>)>>(1);
+        thread::spawn(move || {
+            let found = cache::cached_displays()
+                .into_iter()
+                .filter(|d| d.supports_ddc)
+                .filter_map(|d| {
+                    let inputs = ddc_i2c::supported_inputs(d.i2c_bus).ok()?;
+                    let current = ddc_i2c::get_vcp(d.i2c_bus, VCP_INPUT_SOURCE).ok().map(|(cur, _)| cur & 0xFF);
+                    Some((d.i2c_bus, inputs, current))
+                })
+                .collect();
+            let _ = tx.send_blocking(found);
+        });
+        let list = list_for_show.clone();
+        let popover = popover.downgrade();
+        glib::spawn_future_local(async move {
+            let Ok(found) = rx.recv().await else { return };
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
+            }
+            let mut values: Vec<u8> = found.iter().flat_map(|(_, inputs, _)| inputs.iter().copied()).collect();
+            values.sort_unstable();
+            values.dedup();
+            if values.is_empty() {
+                list.append(&Label::new(Some("Нет мониторов с переключением входа")));
+                return;
+            }
+            for value in values {
+                let buses: Vec<(u8, Option<u16>)> = found.iter()
+                    .filter(|(_, inputs, _)| inputs.contains(&value))
+                    .map(|(bus, _, current)| (*bus, *current))
+                    .collect();
+                let name = inputs::standard_name(value).map(str::to_string).unwrap_or_else(|| format!("0x{:02X}", value));
+                let item = Button::with_label(&format!("{} ({} мон.)", name, buses.len()));
+                item.add_css_class("flat");
+                let popover = popover.clone();
+                item.connect_clicked(move |_| {
+                    for &(bus, current) in &buses {
+                        println!("Bus {}: switching input to 0x{:02X}", bus, value);
+                        record_change(
+                            Setting::Vcp { bus, code: VCP_INPUT_SOURCE },
+                            || current.map(Value::Raw),
+                            Some(Value::Raw(value as u16)),
+                        );
+                        queue_vcp_write(bus, VCP_INPUT_SOURCE, value as u16);
+                    }
+                    if let Some(p) = popover.upgrade() { p.popdown(); }
+                });
+                list.append(&item);
+            }
+        });
+    });
+    button
+}
+
+// Кнопка «Питание» в заголовке: выключить все мониторы, кроме основного, и включить все
+fn create_power_button() -> gtk::MenuButton {
+    let button = gtk::MenuButton::new();
+    button.set_label("Питание");
+    let popover = gtk::Popover::new();
+    let list = GtkBox::new(Orientation::Vertical, 4);
+    let off_btn = Button::with_label("Выключить все, кроме основного");
+    let on_btn = Button::with_label("Включить все мониторы");
+    for btn in [&off_btn, &on_btn] {
+        btn.add_css_class("flat");
+        list.append(btn);
+    }
+    popover.set_child(Some(&list));
+    button.set_popover(Some(&popover));
+
+    let popover_weak = popover.downgrade();
+    off_btn.connect_clicked(move |_| {
+        if let Some(p) = popover_weak.upgrade() { p.popdown(); }
+        // Основной выход и (шина, текущий режим питания) остальных мониторов — для отката
+        type Targets = (String, Vec<(u8, Option<u16>)>);
+        let (tx, rx) = async_channel::bounded::<Result<Option<Targets>, String>>(1);
+        thread::spawn(move || {
+            let targets = mondis_x11::primary_output().map_err(|e| format!("{e:#}")).map(|primary| {
+                primary.map(|primary| {
+                    // Только DDC: DPMS выключил бы и основной монитор
+                    let buses = cache::cached_displays()
+                        .iter()
+                        .filter(|d| d.supports_ddc && d.xrandr_output.as_deref() != Some(primary.as_str()))
+                        .map(|d| (d.i2c_bus, ddc_i2c::get_vcp(d.i2c_bus, VCP_POWER_MODE).ok().map(|(cur, _)| cur)))
+                        .collect();
+                    (primary, buses)
+                })
+            });
+            let _ = tx.send_blocking(targets);
+        });
+        glib::spawn_future_local(async move {
+            let (primary, buses) = match rx.recv().await {
+                Ok(Ok(Some(targets))) => targets,
+                Ok(Ok(None)) => { println!("No primary output set; not powering off"); return; }
+                Ok(Err(e)) => { println!("Failed to get primary output: {}", e); return; }
+                Err(_) => return,
+            };
+            for (bus, current) in buses {
+                println!("Bus {}: power off ({} is primary)", bus, primary);
+                record_power_change(bus, current, PowerMode::Off);
+                queue_vcp_write(bus, VCP_POWER_MODE, PowerMode::Off.vcp_value());
+            }
+        });
+    });
+    let popover_weak = popover.downgrade();
+    on_btn.connect_clicked(move |_| {
+        if let Some(p) = popover_weak.upgrade() { p.popdown(); }
+        for d in cache::cached_displays().iter().filter(|d| d.supports_ddc) {
+            record_power_change(d.i2c_bus, None, PowerMode::On);
+            queue_vcp_write(d.i2c_bus, VCP_POWER_MODE, PowerMode::On.vcp_value());
+        }
+        force_dpms(PowerMode::On);
+    });
+    button
+}
+
 fn build_ui(app: &Application) {
     let win = ApplicationWindow::builder()
         .application(app)
@@ -2966,6 +4084,20 @@ fn build_ui(app: &Application) {
     // Пусть окно подстраивается под естественный размер контента
     win.set_resizable(true);
 
+    // Результаты фоновой записи DDC/XRandR возвращаются в UI; проверка записи и опрос — по настройкам
+    spawn_write_results_loop();
+    let live_settings = match load_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            // Файл не перезаписывается: все сохранения идут через update_settings и тоже завершатся ошибкой
+            println!("Failed to load settings: {e:#}");
+            show_settings_error(&win, &format!("{e:#}"));
+            UiSettings::default()
+        }
+    };
+    ddc_writer().set_verify(live_settings.verify_writes);
+    spawn_control_poller(live_settings.poll_interval_ms);
+
     // Определяем текущую тему и добавляем соответствующий CSS класс
     if let Some(settings) = gtk::Settings::default() {
         let is_dark_theme = settings.is_gtk_application_prefer_dark_theme();
@@ -3032,34 +4164,14 @@ fn build_ui(app: &Application) {
     }
 
     headerbar.pack_end(&refresh_btn);
+    headerbar.pack_start(&create_all_inputs_button());
+    headerbar.pack_start(&create_power_button());
+    headerbar.pack_start(&create_history_buttons());
     headerbar.pack_end(&theme_toggle);
     win.set_titlebar(Some(&headerbar));
 
-    // Панель подтверждения изменений (нижняя всплывающая панель через Overlay+Revealer)
-    let confirm_bar = GtkBox::new(Orientation::Horizontal, 12);
-    confirm_bar.add_css_class("confirm-bar");
-    let timer_label = Label::new(Some("Изменения будут отменены через 20 сек"));
-    timer_label.add_css_class("timer-label");
-    timer_label.set_xalign(1.0);
-    let confirm_button = Button::with_label("Подтвердить изменения");
-    confirm_button.add_css_class("confirm-button");
-    let cancel_button = Button::with_label("Отменить");
-    cancel_button.add_css_class("cancel-button");
-    // Кнопки слева, текст таймера справа. Для этого используем растягивающийся спейсер.
-    confirm_bar.append(&confirm_button);
-    confirm_bar.append(&cancel_button);
-    let spacer = GtkBox::new(Orientation::Horizontal, 0);
-    spacer.set_hexpand(true);
-    confirm_bar.append(&spacer);
-    confirm_bar.append(&timer_label);
-    // revealer для анимированного появления сверху, без смещения контента
-    let confirm_revealer = gtk::Revealer::new();
-    // Переносим панель наверх: выравнивание по верхнему краю и анимация выпадания вниз
-    confirm_revealer.set_transition_type(gtk::RevealerTransitionType::SlideDown);
-    confirm_revealer.set_reveal_child(false);
-    confirm_revealer.set_halign(gtk::Align::Fill);
-    confirm_revealer.set_valign(gtk::Align::Start);
-    confirm_revealer.set_child(Some(&confirm_bar));
+    // Панель подтверждения изменений: общая с карточками мониторов
+    let confirm_revealer = confirm_bar();
 
     let list_box = GtkBox::new(Orientation::Vertical, 12);
     list_box.set_hexpand(true);
@@ -3069,241 +4181,75 @@ fn build_ui(app: &Application) {
     overlay.set_child(Some(&vbox));
     overlay.add_overlay(&confirm_revealer);
     
-    // Состояние яркости для отслеживания изменений
-    let brightness_state = Rc::new(RefCell::new(BrightnessState::new()));
     let slider_refs = Rc::new(RefCell::new(SliderRefs::new()));
     // Предпочитаемый метод управления яркостью по шине I2C (если монитор поддерживает оба)
     let control_pref_map: Rc<RefCell<HashMap<u8, ControlMethodPref>>> = Rc::new(RefCell::new(HashMap::new()));
 
     // Восстанавливаем настройки UI из persistent-хранилища
+    // (значения хранятся по мониторам, шины берутся из кэша дисплеев)
     let persisted = read_settings();
-    {
+    for display in cache::cached_displays() {
+        let Some(monitor) = persisted.monitor(&display) else { continue };
+        let bus = display.i2c_bus;
         let mut refs = slider_refs.borrow_mut();
-        refs.last_values_ddc = persisted.last_values_ddc.clone();
-        refs.last_values_xrandr = persisted.last_values_xrandr.clone();
-    }
-    {
-        let mut map = control_pref_map.borrow_mut();
-        for (bus, pref_str) in persisted.control_prefs.into_iter() {
-            let pref = if pref_str == "ddc" { ControlMethodPref::Ddc } else { ControlMethodPref::Xrandr };
-            map.insert(bus, pref);
+        if let Some(v) = monitor.brightness_ddc { refs.last_values_ddc.insert(bus, v); }
+        if let Some(v) = monitor.brightness_xrandr { refs.last_values_xrandr.insert(bus, v); }
+        if let Some(control) = monitor.control {
+            let pref = match control { ControlMethod::Ddc => ControlMethodPref::Ddc, ControlMethod::Xrandr => ControlMethodPref::Xrandr };
+            control_pref_map.borrow_mut().insert(bus, pref);
         }
     }
-    let timer_source_id: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    let list_box_for_populate = list_box.clone();
+    // Используем слабую ссылку на окно и ссылку на корневой контейнер для измерения
+    let win_weak_for_measure = win.downgrade();
+    let content_for_measure = vbox.clone();
     
-    // Функция для запуска таймера обратного отсчета
-    let start_confirmation_timer: Rc<dyn Fn(Vec<DisplayInfo>)> = Rc::new({
-        let brightness_state = brightness_state.clone();
+    // Все найденные дисплеи (пополняется по мере обнаружения, очищается при обновлении)
+    let all_displays: Rc<RefCell<Vec<DisplayInfo>>> = Rc::new(RefCell::new(Vec::new()));
+
+    // Подтвержденная яркость сохраняется в профиль
+    on_commit(&list_box, {
         let slider_refs = slider_refs.clone();
-        let timer_source_id = timer_source_id.clone();
-        let confirm_revealer = confirm_revealer.clone();
-        let timer_label = timer_label.clone();
-        
-        move |displays: Vec<DisplayInfo>| {
-            // Отменяем предыдущий таймер если есть
-            if let Some(source_id) = timer_source_id.borrow_mut().take() {
-                source_id.remove();
-            }
-            
-            brightness_state.borrow_mut().timer_active = true;
-            confirm_revealer.set_reveal_child(true);
-            
-            let countdown = Rc::new(Cell::new(20));
-            let brightness_state_timer = brightness_state.clone();
-            let slider_refs_timer = slider_refs.clone();
-            let timer_source_id_timer = timer_source_id.clone();
-            let timer_label_timer = timer_label.clone();
-            let displays_for_timer = displays.clone();
-            
-            let confirm_revealer_timer = confirm_revealer.clone();
-            let source_id = glib::timeout_add_seconds_local(1, move || {
-                let remaining = countdown.get();
-                if remaining > 0 {
-                    timer_label_timer.set_text(&format!("Изменения будут отменены через {} сек", remaining));
-                    countdown.set(remaining - 1);
-                    glib::ControlFlow::Continue
-                } else {
-                    // Время вышло - откатываем изменения
-                    println!("Timer expired - restoring original brightness values");
-                    
-                    let state = brightness_state_timer.borrow();
-                    let original_values = state.original_values.clone();
-                    drop(state); // Освобождаем borrow раньше
-                    
-                    // Безопасно восстанавливаем позиции слайдеров
-                    if let Ok(mut slider_refs) = slider_refs_timer.try_borrow_mut() {
-                        slider_refs.restore_all_sliders(&original_values);
-                    } else {
-                        println!("Warning: Could not restore slider positions - slider_refs is borrowed");
-                    }
-                    
-                    // Применяем исходные значения к мониторам
-                    for (&bus, &brightness) in &original_values {
-                        if let Some(display) = displays_for_timer.iter().find(|d| d.i2c_bus == bus) {
-                            let display_clone = display.clone();
-                            thread::spawn(move || {
-                                if let Err(e) = set_brightness_any_method(&display_clone, brightness) {
-                                    println!("Failed to restore brightness for {}: {}", display_clone.name, e);
-                                } else {
-                                    println!("Restored brightness {} for {}", brightness, display_clone.name);
-                                }
-                            });
-                        }
-                    }
-                    
-                    // Обновляем состояние
-                    if let Ok(mut state) = brightness_state_timer.try_borrow_mut() {
-                        state.reset_to_original();
-                    }
-                    
-                    // Скрываем панель подтверждения
-                    confirm_revealer_timer.set_reveal_child(false);
-                    
-                    // Очищаем ID таймера
-                    if let Ok(mut timer_id) = timer_source_id_timer.try_borrow_mut() {
-                        timer_id.take();
-                    }
-                    
-                    println!("Timer cleanup completed");
-                    glib::ControlFlow::Break
-                }
+        let all_displays = all_displays.clone();
+        move |changes| {
+            let brightness_changed = changes.iter().any(|(s, _)| {
+                matches!(s, Setting::Vcp { code: VCP_BRIGHTNESS, .. } | Setting::XrandrBrightness { .. })
             });
-            
-            *timer_source_id.borrow_mut() = Some(source_id);
-        }
-    });
-    
-    // Обработчик кнопки подтверждения
-    let confirm_handler = {
-        let brightness_state = brightness_state.clone();
-        let timer_source_id = timer_source_id.clone();
-        let confirm_revealer = confirm_revealer.clone();
-        
-        move |displays: Vec<DisplayInfo>| {
-            println!("Confirm button clicked - saving brightness profile");
-            
-            // Отменяем таймер
-            if let Ok(mut timer_id) = timer_source_id.try_borrow_mut() {
-                if let Some(source_id) = timer_id.take() {
-                    source_id.remove();
-                }
-            }
-            
-            let current_values = if let Ok(state) = brightness_state.try_borrow() {
-                state.current_values.clone()
-            } else {
-                println!("Warning: Could not access brightness state");
+            if !brightness_changed {
                 return;
-            };
-            
-            // Сохраняем профиль в XML
-            if let Err(e) = save_brightness_profile(&current_values, &displays) {
-                println!("Failed to save brightness profile: {}", e);
-            } else {
-                println!("Brightness profile saved successfully");
-            }
-            
-            // Подтверждаем изменения
-            if let Ok(mut state) = brightness_state.try_borrow_mut() {
-                state.confirm_changes();
-            }
-            
-            confirm_revealer.set_reveal_child(false);
-            println!("Confirm operation completed");
-        }
-    };
-    
-    // Обработчик кнопки отмены
-    let cancel_handler = {
-        let brightness_state = brightness_state.clone();
-        let slider_refs = slider_refs.clone();
-        let timer_source_id = timer_source_id.clone();
-        let confirm_revealer = confirm_revealer.clone();
-        
-        move |displays: Vec<DisplayInfo>| {
-            println!("Cancel button clicked - restoring original brightness values");
-            
-            // Отменяем таймер
-            if let Ok(mut timer_id) = timer_source_id.try_borrow_mut() {
-                if let Some(source_id) = timer_id.take() {
-                    source_id.remove();
-                }
             }
-            
-            let original_values = if let Ok(state) = brightness_state.try_borrow() {
-                state.original_values.clone()
-            } else {
-                println!("Warning: Could not access brightness state");
-                return;
-            };
-            
-            // Восстанавливаем позиции слайдеров
-            if let Ok(mut slider_refs_mut) = slider_refs.try_borrow_mut() {
-                slider_refs_mut.restore_all_sliders(&original_values);
-            } else {
-                println!("Warning: Could not restore slider positions");
-            }
-            
-            // Применяем исходные значения к мониторам
-            for (&bus, &brightness) in &original_values {
-                if let Some(display) = displays.iter().find(|d| d.i2c_bus == bus) {
-                    let display_clone = display.clone();
-                    thread::spawn(move || {
-                        if let Err(e) = set_brightness_any_method(&display_clone, brightness) {
-                            println!("Failed to restore brightness for {}: {}", display_clone.name, e);
-                        } else {
-                            println!("Restored brightness {} for {}", brightness, display_clone.name);
-                        }
-                    });
-                }
-            }
-            
-            // Обновляем состояние
-            if let Ok(mut state) = brightness_state.try_borrow_mut() {
-                state.reset_to_original();
+            let current_values: HashMap<u8, u8> = slider_refs.borrow().sliders.iter()
+                .map(|(&bus, (scale, _))| (bus, scale.value() as u8))
+                .collect();
+            if let Err(e) = save_brightness_profile(&current_values, &all_displays.borrow()) {
+                println!("Failed to save brightness profile: {}", e);
             }
-            
-            confirm_revealer.set_reveal_child(false);
-            println!("Cancel operation completed");
         }
-    };
-
-    // Делимся обработчиками через Rc для безопасного клонирования
-    let confirm_handler_shared: Rc<dyn Fn(Vec<DisplayInfo>)> = Rc::new(confirm_handler);
-    let cancel_handler_shared: Rc<dyn Fn(Vec<DisplayInfo>)> = Rc::new(cancel_handler);
-
-    let list_box_for_populate = list_box.clone();
-    // Используем слабую ссылку на окно и ссылку на корневой контейнер для измерения
-    let win_weak_for_measure = win.downgrade();
-    let content_for_measure = vbox.clone();
-    
-    // Обработчики кнопок подтверждения (нужно подключить после создания дисплеев)
-    let confirm_handler_rc = Rc::new(RefCell::new(None::<Box<dyn Fn()>>));
-    let cancel_handler_rc = Rc::new(RefCell::new(None::<Box<dyn Fn()>>));
+    });
     
     // Клонируем переменные для использования в populate
-    let brightness_state_for_populate = brightness_state.clone();
     let slider_refs_for_populate = slider_refs.clone();
     let control_pref_map_for_populate = control_pref_map.clone();
-    let start_confirmation_timer_for_populate = start_confirmation_timer.clone();
-    let confirm_handler_rc_for_populate = confirm_handler_rc.clone();
-    let cancel_handler_rc_for_populate = cancel_handler_rc.clone();
-    let confirm_handler_for_populate = confirm_handler_shared.clone();
-    let cancel_handler_for_populate = cancel_handler_shared.clone();
+    let all_displays_for_populate = all_displays.clone();
     
     let force_detect_next = Rc::new(Cell::new(false));
     let force_detect_for_populate = force_detect_next.clone();
+    // Отправитель событий текущего наполнения — для повторного опроса шин при hotplug
+    let detect_tx: Rc<RefCell<Option<async_channel::Sender<DetectEvent>>>> = Rc::new(RefCell::new(None));
+    let detect_tx_for_populate = detect_tx.clone();
+    // Номер текущего наполнения: события от прерванного обновлением детекта игнорируются
+    let populate_generation = Rc::new(Cell::new(0u64));
     let populate: Rc<dyn Fn()> = Rc::new(move || {
+        populate_generation.set(populate_generation.get() + 1);
+        let generation = populate_generation.get();
+        let populate_generation = populate_generation.clone();
         // Clear list and reset state
         while let Some(child) = list_box_for_populate.first_child() {
             list_box_for_populate.remove(&child);
         }
         
-        // Очищаем состояние при обновлении
-        brightness_state_for_populate.borrow_mut().original_values.clear();
-        brightness_state_for_populate.borrow_mut().current_values.clear();
-        brightness_state_for_populate.borrow_mut().has_changes = false;
-        brightness_state_for_populate.borrow_mut().timer_active = false;
+        // Очищаем состояние при обновлении (незавершенные изменения остаются в общей транзакции)
+        all_displays_for_populate.borrow_mut().clear();
         slider_refs_for_populate.borrow_mut().sliders.clear();
 
         let header = Label::new(Some("Видеокарты и мониторы"));
@@ -3316,436 +4262,548 @@ fn build_ui(app: &Application) {
         let content_for_measure_outer = content_for_measure.clone();
         
         // Клонируем переменные для async блока
-        let brightness_state_for_async = brightness_state_for_populate.clone();
         let slider_refs_for_async = slider_refs_for_populate.clone();
         let control_pref_map_for_async = control_pref_map_for_populate.clone();
-        let start_timer_for_async = start_confirmation_timer_for_populate.clone();
 
-        // Готовим канал результатов и решаем: грузить из кэша или запускать детект
-        let (tx, rx) = async_channel::unbounded::<Result<Vec<VideoCard>, String>>();
-        let mut rendered_from_cache = false;
+        // Готовим канал событий обнаружения и решаем: грузить из кэша или запускать детект
+        let (tx, rx) = async_channel::unbounded::<DetectEvent>();
+        // None — полный детект, Some(шины) — только перепроверка шин, не совпавших с кэшем
+        let mut detect_buses: Option<Vec<u8>> = None;
         if !force_detect_for_populate.get() {
             if let Ok(Some(cards)) = read_displays_cache() {
-                // Отрисовываем из кэша немедленно через канал, чтобы переиспользовать общий рендер-пайплайн
-                if tx.send_blocking(Ok(cards)).is_ok() {
-                    rendered_from_cache = true;
+                let check = cache::validate_displays(
+                    cards.into_iter().flat_map(|card| card.displays).collect(),
+                    &cache::connector_edid_hashes(),
+                );
+                // Отрисовываем подтвержденные записи кэша немедленно через канал, чтобы переиспользовать общий рендер-пайплайн
+                for d in check.valid.iter().cloned() {
+                    let _ = tx.send_blocking(DetectEvent::Found(d));
+                }
+                if check.is_clean() {
+                    detect_buses = Some(Vec::new());
+                    let _ = tx.send_blocking(DetectEvent::Done);
+                } else {
+                    println!(
+                        "Displays cache is stale: {} changed, new connectors {:?}",
+                        check.stale.len(),
+                        check.new_connectors
+                    );
+                    detect_buses = stale_cache_buses(&check);
                 }
             }
         }
-        if !rendered_from_cache {
-            // Запускаем детект в отдельном потоке и шлём результат
-            thread::spawn(move || {
-                let res = detect_i2c_displays().map(group_displays_by_card);
-                let _ = tx.send_blocking(res);
-            });
+        // Канал остается открытым для последующих hotplug-событий
+        *detect_tx_for_populate.borrow_mut() = Some(tx.clone());
+        match detect_buses {
+            Some(buses) if buses.is_empty() => {}
+            // Параллельный детект в фоне; мониторы появляются по мере обнаружения
+            only => {
+                thread::spawn(move || detect_i2c_displays_streaming(tx, only));
+            }
         }
 
-        // Клонируем Rc чтобы не перемещать исходные переменные в async
-        let confirm_handler_rc_async = confirm_handler_rc_for_populate.clone();
-        let cancel_handler_rc_async = cancel_handler_rc_for_populate.clone();
-        let confirm_handler_async = confirm_handler_for_populate.clone();
-        let cancel_handler_async = cancel_handler_for_populate.clone();
-        glib::spawn_future_local(async move {
-            if let Ok(msg) = rx.recv().await {
-                match msg {
-                    Ok(cards) if !cards.is_empty() => {
-                        // Пишем кэш (best-effort)
-                        let _ = write_displays_cache(&cards);
-                        // Собираем все дисплеи для использования в callbacks
-                        let all_displays: Vec<DisplayInfo> = cards.iter()
-                            .flat_map(|card| card.displays.iter())
-                            .cloned()
-                            .collect();
-                        for card in cards {
-                            // Add card header
-                            let card_header = Label::new(Some(&card.name));
-                            card_header.set_xalign(0.0);
-                            card_header.set_markup(&format!("<b>{}</b>", card.name));
-                            list_box_target.append(&card_header);
-
-                            // Add displays for this card (deferred per row via idle)
-                            for (display_index, d) in card.displays.into_iter().enumerate() {
-                                let list_box_target = list_box_target.clone();
-                                let control_pref_map_for_async = control_pref_map_for_async.clone();
-                                let slider_refs_for_async = slider_refs_for_async.clone();
-                                let brightness_state_for_async = brightness_state_for_async.clone();
-                                let start_timer_for_async = start_timer_for_async.clone();
-                                let all_displays = all_displays.clone();
-                                let win_weak_for_measure_outer = win_weak_for_measure_outer.clone();
-                                glib::idle_add_local_once(move || {
-                                    // Grid-ряд для ровных колонок: Порт | Тип | Название | Иконка | Бейдж | Слайдер | Значение
-                                    let grid = gtk::Grid::new();
-                                    grid.set_margin_start(20);
-                                    grid.set_column_spacing(12);
-                                    grid.set_row_spacing(6);
-                                    grid.add_css_class("row");
-                                    grid.set_hexpand(true);
-
-                                    // Create port and monitor info
-                                    let default_port = "Unknown Port".to_string();
-                                    let port_info = d.port_name.as_ref().unwrap_or(&default_port);
-                                    let monitor_name = match (&d.manufacturer, &d.model) {
-                                        (Some(mfg), Some(model)) => {
-                                            let expanded_mfg = match mfg.as_str() {
-                                                "ACR" => "Acer",
-                                                "GSM" => "LG",
-                                                "SAM" => "Samsung",
-                                                "DEL" => "Dell",
-                                                "AUS" => "ASUS",
-                                                "BNQ" => "BenQ",
-                                                "AOC" => "AOC",
-                                                "HPN" => "HP",
-                                                "LEN" => "Lenovo",
-                                                "MSI" => "MSI",
-                                                _ => mfg,
-                                            };
-                                            format!("{} {}", expanded_mfg, model)
-                                        }
-                                        _ => "Unknown Monitor".to_string(),
-                                    };
-
-                                    // Выбираем исходное предпочтение: из настроек, иначе по доступности
-                                    let pref_from_settings = control_pref_map_for_async.borrow().get(&d.i2c_bus).copied();
-                                    let resolved_pref = match pref_from_settings {
-                                        Some(ControlMethodPref::Ddc) if d.supports_ddc => Some(ControlMethodPref::Ddc),
-                                        Some(ControlMethodPref::Xrandr) if d.xrandr_output.is_some() => Some(ControlMethodPref::Xrandr),
-                                        _ => {
-                                            if d.supports_ddc { Some(ControlMethodPref::Ddc) }
-                                            else if d.xrandr_output.is_some() { Some(ControlMethodPref::Xrandr) }
-                                            else { None }
-                                        }
-                                    };
-                                    let (control_method, badge_class, tooltip) = match resolved_pref {
-                                        Some(ControlMethodPref::Ddc) => (
-                                            "аппаратно",
-                                            "badge-ddc",
-                                            format!(
-                                                "Управление: аппаратно (DDC/CI)\nШина: /dev/i2c-{}{}",
-                                                d.i2c_bus,
-                                                d.connector.as_ref().map(|c| format!("\nКоннектор: {}", c)).unwrap_or_default()
-                                            ),
-                                        ),
-                                        Some(ControlMethodPref::Xrandr) => {
-                                            let out = d.xrandr_output.clone().unwrap_or_default();
-                                            (
-                                                "программно",
-                                                "badge-xrandr",
-                                                format!(
-                                                    "Управление: программно (xrandr)\nВывод: {}{}",
-                                                    out,
-                                                    d.connector.as_ref().map(|c| format!("\nКоннектор: {}", c)).unwrap_or_default()
-                                                ),
-                                            )
-                                        },
-                                        None => (
-                                            "нет управления",
-                                            "badge-none",
-                                            d.connector.as_ref().map(|c| format!("Коннектор: {}", c)).unwrap_or_else(|| "Нет доступного метода".to_string())
-                                        )
-                                    };
-                                    // Инициализируем предпочтение метода
-                                    {
-                                        let mut pref_map = control_pref_map_for_async.borrow_mut();
-                                        let default_pref = if d.supports_ddc { ControlMethodPref::Ddc } else { ControlMethodPref::Xrandr };
-                                        pref_map.entry(d.i2c_bus).or_insert(default_pref);
-                                    }
+        let all_displays = all_displays_for_populate.clone();
+
+        // Отрисовка строки монитора в секции его видеокарты
+        let render_display: DisplayRowRenderer = Rc::new({
+            let all_displays = all_displays.clone();
+            let win_weak_for_measure_outer = win_weak_for_measure_outer.clone();
+            move |target: &GtkBox, d: DisplayInfo| {
+                // Grid-ряд для ровных колонок: Порт | Тип | Название | Иконка | Бейдж | Слайдер | Значение
+                let grid = gtk::Grid::new();
+                grid.set_margin_start(20);
+                grid.set_column_spacing(12);
+                grid.set_row_spacing(6);
+                grid.add_css_class("row");
+                grid.set_hexpand(true);
+
+                // Create port and monitor info
+                let default_port = "Unknown Port".to_string();
+                let port_info = d.port_name.as_ref().unwrap_or(&default_port);
+                let monitor_name = match (&d.manufacturer, &d.model) {
+                    (Some(mfg), Some(model)) => {
+                        let expanded_mfg = pnp::display_name(mfg);
+                        format!("{} {}", expanded_mfg, model)
+                    }
+                    _ => "Unknown Monitor".to_string(),
+                };
 
-                                    // Determine port type from connector info
-                                    let port_type = if let Some(ref connector) = d.connector {
-                                        if connector.contains("HDMI") {
-                                            "HDMI"
-                                        } else if connector.contains("DP") {
-                                            "DisplayPort"
-                                        } else if connector.contains("DVI") {
-                                            "DVI"
-                                        } else if connector.contains("VGA") {
-                                            "VGA"
-                                        } else {
-                                            "Unknown"
-                                        }
-                                    } else {
-                                        "Unknown"
-                                    };
-
-                                    // Колонки: порт, тип, название, иконка, бейдж
-                                    let port_lbl = Label::new(Some(port_info));
-                                    port_lbl.add_css_class("port");
-                                    port_lbl.set_xalign(0.0);
-                                    port_lbl.set_width_chars(14);
-                                    let type_lbl = Label::new(Some(port_type));
-                                    type_lbl.add_css_class("type");
-                                    type_lbl.set_xalign(0.0);
-                                    type_lbl.set_width_chars(12);
-                                    let monitor_lbl = Label::new(Some(&monitor_name));
-                                    monitor_lbl.add_css_class("monitor");
-                                    monitor_lbl.set_xalign(0.0);
-                                    monitor_lbl.set_ellipsize(gtk::pango::EllipsizeMode::End);
-                                    monitor_lbl.set_hexpand(true);
-                                    let badge_icon = match badge_class {
-                                        "badge-none" => Image::from_icon_name("dialog-warning-symbolic"),
-                                        _ => Image::from_icon_name("video-display-symbolic"),
-                                    };
-                                    // Выравнивание и стили для иконки-кнопки
-                                    badge_icon.set_halign(gtk::Align::Center);
-                                    badge_icon.set_valign(gtk::Align::Center);
-                                    badge_icon.set_margin_start(6);
-                                    badge_icon.set_margin_end(6);
-                                    badge_icon.set_pixel_size(18);
-                                    badge_icon.add_css_class("icon-button");
-                                    badge_icon.set_tooltip_text(Some("Открыть карточку монитора"));
-                                    let badge = Button::with_label(control_method);
-                                    badge.add_css_class("badge");
-                                    badge.add_css_class("badge-button");
-                                    badge.add_css_class(badge_class);
-                                    badge.add_css_class("flat");
-                                    badge.set_can_focus(false);
-                                    badge.set_tooltip_text(Some(&tooltip));
-                                    // Если доступны оба метода — делаем кнопку переключаемой
-                                    if d.supports_ddc && d.xrandr_output.is_some() {
-                                        let badge_btn = badge.clone();
-                                        let control_pref_map_for_toggle = control_pref_map_for_async.clone();
-                                        let slider_refs_for_toggle = slider_refs_for_async.clone();
-                                        let brightness_state_for_toggle = brightness_state_for_async.clone();
-                                        let d_for_toggle = d.clone();
-                                        badge.connect_clicked(move |_| {
-                                            // Переключаем предпочтение
-                                            let new_pref = {
-                                                let mut map = control_pref_map_for_toggle.borrow_mut();
-                                                let entry = map.entry(d_for_toggle.i2c_bus).or_insert(ControlMethodPref::Ddc);
-                                                *entry = match *entry { ControlMethodPref::Ddc => ControlMethodPref::Xrandr, ControlMethodPref::Xrandr => ControlMethodPref::Ddc };
-                                                *entry
-                                            };
-                                            // Обновляем визуал
-                                            badge_btn.remove_css_class("badge-ddc");
-                                            badge_btn.remove_css_class("badge-xrandr");
-                                            match new_pref {
-                                                ControlMethodPref::Ddc => {
-                                                    badge_btn.add_css_class("badge-ddc");
-                                                    badge_btn.set_label("аппаратно");
-                                                    badge_btn.set_tooltip_text(Some(&format!("Управление: аппаратно (DDC/CI)\nШина: /dev/i2c-{}", d_for_toggle.i2c_bus)));
-                                                }
-                                                ControlMethodPref::Xrandr => {
-                                                    badge_btn.add_css_class("badge-xrandr");
-                                                    badge_btn.set_label("программно");
-                                                    if let Some(ref out) = d_for_toggle.xrandr_output {
-                                                        badge_btn.set_tooltip_text(Some(&format!("Управление: программно (xrandr)\nВывод: {}", out)));
-                                                    }
-                                                }
-                                            }
-                                            // Независимые значения ползунка для каждого метода:
-                                            // 1) Берём сохранённое значение для НОВОГО метода, если есть, иначе текущее
-                                            let (target_val, have_stored) = if let Ok(refs) = slider_refs_for_toggle.try_borrow() {
-                                                let cur = if let Some((scale_ref, _)) = refs.sliders.get(&d_for_toggle.i2c_bus) { scale_ref.value() as u8 } else { 0 };
-                                                if let Some(v) = refs.get_last_value(d_for_toggle.i2c_bus, new_pref) { (v, true) } else { (cur, false) }
-                                            } else { (0, false) };
-                                            // 2) Если есть сохранённое — программно выставляем ползунок в него
-                                            if have_stored {
-                                                if let Ok(mut refs) = slider_refs_for_toggle.try_borrow_mut() {
-                                                    refs.update_slider_value(d_for_toggle.i2c_bus, target_val);
-                                                }
-                                            }
-                                            // 3) Применяем яркость выбранным методом
-                                            let d_set = d_for_toggle.clone();
-                                            thread::spawn(move || {
-                                                let _ = set_brightness_with_pref(&d_set, target_val, Some(new_pref));
-                                            });
-                                            // 4) Обновляем состояние и запоминаем это значение для нового метода
-                                            let brightness_state_ui = brightness_state_for_toggle.clone();
-                                            let slider_refs_ui2 = slider_refs_for_toggle.clone();
-                                            let control_pref_map_ui = control_pref_map_for_toggle.clone();
-                                            glib::spawn_future_local(clone!(@strong d_for_toggle => async move {
-                                                if let Ok(mut st) = brightness_state_ui.try_borrow_mut() {
-                                                    st.update_current(d_for_toggle.i2c_bus, target_val);
-                                                }
-                                                if let Ok(mut refs) = slider_refs_ui2.try_borrow_mut() {
-                                                    refs.remember_value(d_for_toggle.i2c_bus, new_pref, target_val);
-                                                }
-                                                // persist settings
-                                                save_settings_from_state(&control_pref_map_ui, &slider_refs_ui2);
-                                            }));
-                                        });
-                                    }
+                // Выбираем исходное предпочтение: из настроек, иначе по доступности
+                let pref_from_settings = control_pref_map_for_async.borrow().get(&d.i2c_bus).copied();
+                let resolved_pref = match pref_from_settings {
+                    Some(ControlMethodPref::Ddc) if d.supports_ddc => Some(ControlMethodPref::Ddc),
+                    Some(ControlMethodPref::Xrandr) if d.xrandr_output.is_some() => Some(ControlMethodPref::Xrandr),
+                    _ => {
+                        if d.supports_ddc { Some(ControlMethodPref::Ddc) }
+                        else if d.xrandr_output.is_some() { Some(ControlMethodPref::Xrandr) }
+                        else { None }
+                    }
+                };
+                let (control_method, badge_class, tooltip) = match resolved_pref {
+                    Some(ControlMethodPref::Ddc) => (
+                        "аппаратно",
+                        "badge-ddc",
+                        format!(
+                            "Управление: аппаратно (DDC/CI)\nШина: /dev/i2c-{}{}",
+                            d.i2c_bus,
+                            d.connector.as_ref().map(|c| format!("\nКоннектор: {}", c)).unwrap_or_default()
+                        ),
+                    ),
+                    Some(ControlMethodPref::Xrandr) => {
+                        let out = d.xrandr_output.clone().unwrap_or_default();
+                        (
+                            "программно",
+                            "badge-xrandr",
+                            format!(
+                                "Управление: программно (xrandr)\nВывод: {}{}",
+                                out,
+                                d.connector.as_ref().map(|c| format!("\nКоннектор: {}", c)).unwrap_or_default()
+                            ),
+                        )
+                    },
+                    None => (
+                        "нет управления",
+                        "badge-none",
+                        d.connector.as_ref().map(|c| format!("Коннектор: {}", c)).unwrap_or_else(|| "Нет доступного метода".to_string())
+                    )
+                };
+                // Инициализируем предпочтение метода
+                {
+                    let mut pref_map = control_pref_map_for_async.borrow_mut();
+                    let default_pref = if d.supports_ddc { ControlMethodPref::Ddc } else { ControlMethodPref::Xrandr };
+                    pref_map.entry(d.i2c_bus).or_insert(default_pref);
+                }
 
-                                    grid.attach(&port_lbl, 0, 0, 1, 1);
-                                    grid.attach(&type_lbl, 1, 0, 1, 1);
-                                    // Перемещаем иконку открытия карточки перед названием монитора
-                                    grid.attach(&badge_icon, 2, 0, 1, 1);
-                                    grid.attach(&monitor_lbl, 3, 0, 1, 1);
-                                    grid.attach(&badge, 4, 0, 1, 1);
-
-                                    // Правый слайдер и метка значения
-                                    let scale = Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 1.0);
-                                    scale.set_hexpand(false);
-                                    scale.set_width_request(320);
-                                    scale.set_halign(gtk::Align::End);
-                                    scale.set_draw_value(false);
-                                    let value_lbl = Label::new(Some("0%"));
-                                    value_lbl.set_width_chars(4); // фиксированная ширина
-                                    value_lbl.set_xalign(1.0);
-                                    grid.attach(&scale, 5, 0, 1, 1);
-                                    grid.attach(&value_lbl, 6, 0, 1, 1);
-
-                                    // Try to get brightness using any available method
-                                    if !d.supports_ddc && d.xrandr_output.is_none() {
-                                        scale.set_sensitive(false);
-                                        scale.set_value(0.0);
-                                    } else {
-                                        scale.set_sensitive(false);
-
-                                        // Get current brightness using any available method
-                                        let (s_tx, s_rx) = async_channel::unbounded::<Result<u8, String>>();
-                                        let display_for_brightness = d.clone();
-                                        let bus_for_pref = d.i2c_bus;
-                                        let pref_for_init = control_pref_map_for_async.borrow().get(&bus_for_pref).copied();
-                                        thread::spawn(move || {
-                                            let res = get_brightness_with_pref(&display_for_brightness, pref_for_init);
-                                            let _ = s_tx.send_blocking(res);
-                                        });
-
-                                        let grid_for_tooltip = grid.clone();
-                                        let brightness_state_for_init = brightness_state_for_async.clone();
-                                        let slider_refs_for_init = slider_refs_for_async.clone();
-                                        let control_pref_map_for_init = control_pref_map_for_async.clone();
-                                        glib::spawn_future_local(clone!(@strong scale, @strong grid_for_tooltip, @strong value_lbl, @strong control_pref_map_for_init => async move {
-                                            if let Ok(res) = s_rx.recv().await {
-                                                match res {
-                                                    Ok(v) => {
-                                                        // Регистрируем слайдер в SliderRefs
-                                                        slider_refs_for_init.borrow_mut().add_slider(bus_for_pref, scale.clone(), value_lbl.clone());
-
-                                                        // Сохраняем исходное устройство-значение как оригинал
-                                                        brightness_state_for_init.borrow_mut().save_original(bus_for_pref, v);
-
-                                                        // Если есть сохранённое последнее значение для текущего предпочитаемого метода — отобразим его,
-                                                        // иначе используем прочитанное устройство-значение
-                                                        if let Ok(pref_map) = control_pref_map_for_init.try_borrow() {
-                                                            if let Some(&pref_m) = pref_map.get(&bus_for_pref) {
-                                                                if let Ok(mut refs) = slider_refs_for_init.try_borrow_mut() {
-                                                                    if let Some(stored) = refs.get_last_value(bus_for_pref, pref_m) {
-                                                                        // Показываем сохранённое пользовательское
-                                                                        refs.update_slider_value(bus_for_pref, stored);
-                                                                        refs.remember_value(bus_for_pref, pref_m, stored);
-                                                                    } else {
-                                                                        // Сохраняем и показываем устройство-значение
-                                                                        scale.set_value(v as f64);
-                                                                        scale.set_sensitive(true);
-                                                                        value_lbl.set_text(&format!("{}%", v));
-                                                                        refs.remember_value(bus_for_pref, pref_m, v);
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                        // Разблокируем слайдер на всякий случай
-                                                        scale.set_sensitive(true);
-                                                    }
-                                                    Err(e) => {
-                                                        scale.set_sensitive(false);
-                                                        // Показываем ошибку в подсказке ряда
-                                                        grid_for_tooltip.set_tooltip_text(Some(&format!("Ошибка чтения яркости: {}", e)));
-                                                    }
-                                                }
-                                            }
-                                        }));
-
-                                        // Direct brightness control - immediate response
-                                        // Create a unique copy for this specific callback to avoid variable capture issues
-                                        let display_info_for_callback = d.clone();
-
-                                        let value_lbl_for_change = value_lbl.clone();
-                                        let brightness_state_for_callback = brightness_state_for_async.clone();
-                                        let control_pref_map_for_callback = control_pref_map_for_async.clone();
-                                        let slider_refs_for_callback = slider_refs_for_async.clone();
-                                        let start_timer_for_callback = start_timer_for_async.clone();
-                                        let all_displays_for_callback = all_displays.clone();
-
-                                        scale.connect_value_changed(move |s| {
-                                            let val = s.value() as u8;
-                                            let display_clone = display_info_for_callback.clone();
-
-                                            // Проверяем, является ли это программным обновлением
-                                            if let Ok(mut slider_refs_mut) = slider_refs_for_callback.try_borrow_mut() {
-                                                if slider_refs_mut.is_programmatic_update(display_clone.i2c_bus) {
-                                                    value_lbl_for_change.set_text(&format!("{}%", val));
-                                                    return;
-                                                }
-                                            } else {
-                                                value_lbl_for_change.set_text(&format!("{}%", val));
-                                                return;
-                                            }
+                // Determine port type from connector info
+                let port_type = if let Some(ref connector) = d.connector {
+                    if connector.contains("HDMI") {
+                        "HDMI"
+                    } else if connector.contains("DP") {
+                        "DisplayPort"
+                    } else if connector.contains("DVI") {
+                        "DVI"
+                    } else if connector.contains("VGA") {
+                        "VGA"
+                    } else {
+                        "Unknown"
+                    }
+                } else {
+                    "Unknown"
+                };
+
+                // Колонки: порт, тип, название, иконка, бейдж
+                let port_lbl = Label::new(Some(port_info));
+                port_lbl.add_css_class("port");
+                port_lbl.set_xalign(0.0);
+                port_lbl.set_width_chars(14);
+                let type_lbl = Label::new(Some(port_type));
+                type_lbl.add_css_class("type");
+                type_lbl.set_xalign(0.0);
+                type_lbl.set_width_chars(12);
+                let monitor_lbl = Label::new(Some(&monitor_name));
+                monitor_lbl.add_css_class("monitor");
+                monitor_lbl.set_xalign(0.0);
+                monitor_lbl.set_ellipsize(gtk::pango::EllipsizeMode::End);
+                monitor_lbl.set_hexpand(true);
+                let badge_icon = match badge_class {
+                    "badge-none" => Image::from_icon_name("dialog-warning-symbolic"),
+                    _ => Image::from_icon_name("video-display-symbolic"),
+                };
+                // Выравнивание и стили для иконки-кнопки
+                badge_icon.set_halign(gtk::Align::Center);
+                badge_icon.set_valign(gtk::Align::Center);
+                badge_icon.set_margin_start(6);
+                badge_icon.set_margin_end(6);
+                badge_icon.set_pixel_size(18);
+                badge_icon.add_css_class("icon-button");
+                badge_icon.set_tooltip_text(Some("Открыть карточку монитора"));
+                let badge = Button::with_label(control_method);
+                badge.add_css_class("badge");
+                badge.add_css_class("badge-button");
+                badge.add_css_class(badge_class);
+                badge.add_css_class("flat");
+                badge.set_can_focus(false);
+                badge.set_tooltip_text(Some(&tooltip));
+                // Яркость, записанная в монитор последней: исходное значение для отката следующего изменения
+                let applied = Rc::new(Cell::new(0u8));
+                // Если доступны оба метода — делаем кнопку переключаемой
+                if d.supports_ddc && d.xrandr_output.is_some() {
+                    let badge_btn = badge.clone();
+                    let control_pref_map_for_toggle = control_pref_map_for_async.clone();
+                    let slider_refs_for_toggle = slider_refs_for_async.clone();
+                    let applied_for_toggle = applied.clone();
+                    let d_for_toggle = d.clone();
+                    badge.connect_clicked(move |_| {
+                        // Переключаем предпочтение
+                        let new_pref = {
+                            let mut map = control_pref_map_for_toggle.borrow_mut();
+                            let entry = map.entry(d_for_toggle.i2c_bus).or_insert(ControlMethodPref::Ddc);
+                            *entry = match *entry { ControlMethodPref::Ddc => ControlMethodPref::Xrandr, ControlMethodPref::Xrandr => ControlMethodPref::Ddc };
+                            *entry
+                        };
+                        // Обновляем визуал
+                        badge_btn.remove_css_class("badge-ddc");
+                        badge_btn.remove_css_class("badge-xrandr");
+                        match new_pref {
+                            ControlMethodPref::Ddc => {
+                                badge_btn.add_css_class("badge-ddc");
+                                badge_btn.set_label("аппаратно");
+                                badge_btn.set_tooltip_text(Some(&format!("Управление: аппаратно (DDC/CI)\nШина: /dev/i2c-{}", d_for_toggle.i2c_bus)));
+                            }
+                            ControlMethodPref::Xrandr => {
+                                badge_btn.add_css_class("badge-xrandr");
+                                badge_btn.set_label("программно");
+                                if let Some(ref out) = d_for_toggle.xrandr_output {
+                                    badge_btn.set_tooltip_text(Some(&format!("Управление: программно (xrandr)\nВывод: {}", out)));
+                                }
+                            }
+                        }
+                        // Независимые значения ползунка для каждого метода:
+                        // 1) Берём сохранённое значение для НОВОГО метода, если есть, иначе текущее
+                        let (target_val, have_stored) = if let Ok(refs) = slider_refs_for_toggle.try_borrow() {
+                            let cur = if let Some((scale_ref, _)) = refs.sliders.get(&d_for_toggle.i2c_bus) { scale_ref.value() as u8 } else { 0 };
+                            if let Some(v) = refs.get_last_value(d_for_toggle.i2c_bus, new_pref) { (v, true) } else { (cur, false) }
+                        } else { (0, false) };
+                        // 2) Если есть сохранённое — программно выставляем ползунок в него
+                        if have_stored {
+                            if let Ok(mut refs) = slider_refs_for_toggle.try_borrow_mut() {
+                                refs.update_slider_value(d_for_toggle.i2c_bus, target_val);
+                            }
+                        }
+                        // 3) Применяем яркость выбранным методом
+                        queue_brightness_write(&d_for_toggle, target_val, Some(new_pref));
+                        applied_for_toggle.set(target_val);
+                        // 4) Запоминаем это значение для нового метода
+                        let slider_refs_ui2 = slider_refs_for_toggle.clone();
+                        let control_pref_map_ui = control_pref_map_for_toggle.clone();
+                        glib::spawn_future_local(clone!(@strong d_for_toggle => async move {
+                            if let Ok(mut refs) = slider_refs_ui2.try_borrow_mut() {
+                                refs.remember_value(d_for_toggle.i2c_bus, new_pref, target_val);
+                            }
+                            // persist settings
+                            save_settings_from_state(&control_pref_map_ui, &slider_refs_ui2);
+                        }));
+                    });
+                }
+
+                grid.attach(&port_lbl, 0, 0, 1, 1);
+                grid.attach(&type_lbl, 1, 0, 1, 1);
+                // Перемещаем иконку открытия карточки перед названием монитора
+                grid.attach(&badge_icon, 2, 0, 1, 1);
+                grid.attach(&monitor_lbl, 3, 0, 1, 1);
+                grid.attach(&badge, 4, 0, 1, 1);
+
+                // Правый слайдер и метка значения
+                let scale = Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 1.0);
+                scale.set_hexpand(false);
+                scale.set_width_request(320);
+                scale.set_halign(gtk::Align::End);
+                scale.set_draw_value(false);
+                let value_lbl = Label::new(Some("0%"));
+                value_lbl.set_width_chars(4); // фиксированная ширина
+                value_lbl.set_xalign(1.0);
+                grid.attach(&scale, 5, 0, 1, 1);
+                grid.attach(&value_lbl, 6, 0, 1, 1);
+
+                // Try to get brightness using any available method
+                if !d.supports_ddc && d.xrandr_output.is_none() {
+                    scale.set_sensitive(false);
+                    scale.set_value(0.0);
+                } else {
+                    scale.set_sensitive(false);
+
+                    // Get current brightness using any available method
+                    let (s_tx, s_rx) = async_channel::unbounded::<Result<u8, String>>();
+                    let display_for_brightness = d.clone();
+                    let bus_for_pref = d.i2c_bus;
+                    let pref_for_init = control_pref_map_for_async.borrow().get(&bus_for_pref).copied();
+                    thread::spawn(move || {
+                        let res = get_brightness_with_pref(&display_for_brightness, pref_for_init);
+                        let _ = s_tx.send_blocking(res);
+                    });
 
-                                            value_lbl_for_change.set_text(&format!("{}%", val));
-
-                                            // Обновляем состояние
-                                            let (has_changes, _timer_active) = if let Ok(mut state) = brightness_state_for_callback.try_borrow_mut() {
-                                                state.update_current(display_clone.i2c_bus, val);
-                                                (state.has_changes, state.timer_active)
-                                            } else {
-                                                (false, false)
-                                            };
-                                            // Запоминаем значение для текущего метода
-                                            if let Ok(pref_map) = control_pref_map_for_callback.try_borrow() {
-                                                if let Some(&pref_m) = pref_map.get(&display_clone.i2c_bus) {
-                                                    if let Ok(mut refs) = slider_refs_for_callback.try_borrow_mut() {
-                                                        refs.remember_value(display_clone.i2c_bus, pref_m, val);
-                                                    }
+                    let grid_for_tooltip = grid.clone();
+                    let applied_for_init = applied.clone();
+                    let slider_refs_for_init = slider_refs_for_async.clone();
+                    let control_pref_map_for_init = control_pref_map_for_async.clone();
+                    glib::spawn_future_local(clone!(@strong scale, @strong grid_for_tooltip, @strong value_lbl, @strong control_pref_map_for_init => async move {
+                        if let Ok(res) = s_rx.recv().await {
+                            match res {
+                                Ok(v) => {
+                                    // Регистрируем слайдер в SliderRefs
+                                    slider_refs_for_init.borrow_mut().add_slider(bus_for_pref, scale.clone(), value_lbl.clone());
+
+                                    // Сохраняем исходное устройство-значение как оригинал
+                                    applied_for_init.set(v);
+
+                                    // Если есть сохранённое последнее значение для текущего предпочитаемого метода — отобразим его,
+                                    // иначе используем прочитанное устройство-значение
+                                    if let Ok(pref_map) = control_pref_map_for_init.try_borrow() {
+                                        if let Some(&pref_m) = pref_map.get(&bus_for_pref) {
+                                            if let Ok(mut refs) = slider_refs_for_init.try_borrow_mut() {
+                                                if let Some(stored) = refs.get_last_value(bus_for_pref, pref_m) {
+                                                    // Показываем сохранённое пользовательское
+                                                    refs.update_slider_value(bus_for_pref, stored);
+                                                    refs.remember_value(bus_for_pref, pref_m, stored);
+                                                } else {
+                                                    // Сохраняем и показываем устройство-значение
+                                                    scale.set_value(v as f64);
+                                                    scale.set_sensitive(true);
+                                                    value_lbl.set_text(&format!("{}%", v));
+                                                    refs.remember_value(bus_for_pref, pref_m, v);
                                                 }
                                             }
-                                            // persist settings
-                                            save_settings_from_state(&control_pref_map_for_callback, &slider_refs_for_callback);
-
-                                            // Set brightness immediately in background thread, учитывая предпочтение
-                                            let pref = control_pref_map_for_callback.borrow().get(&display_clone.i2c_bus).copied();
-                                            thread::spawn(move || {
-                                                let _ = set_brightness_with_pref(&display_clone, val, pref);
-                                            });
-
-                                            // Перезапускаем таймер подтверждения при каждом изменении
-                                            if has_changes {
-                                                (start_timer_for_callback)(all_displays_for_callback.clone());
-                                            }
-                                        });
+                                        }
                                     }
+                                    // Разблокируем слайдер на всякий случай
+                                    scale.set_sensitive(true);
+                                }
+                                Err(e) => {
+                                    scale.set_sensitive(false);
+                                    // Показываем ошибку в подсказке ряда
+                                    grid_for_tooltip.set_tooltip_text(Some(&format!("Ошибка чтения яркости: {}", e)));
+                                }
+                            }
+                        }
+                    }));
+
+                    // Direct brightness control - immediate response
+                    // Create a unique copy for this specific callback to avoid variable capture issues
+                    let display_info_for_callback = d.clone();
+
+                    let value_lbl_for_change = value_lbl.clone();
+                    let control_pref_map_for_callback = control_pref_map_for_async.clone();
+                    let slider_refs_for_callback = slider_refs_for_async.clone();
+                    let applied_for_callback = applied.clone();
+
+                    scale.connect_value_changed(move |s| {
+                        let val = s.value() as u8;
+                        let display_clone = display_info_for_callback.clone();
+
+                        // Проверяем, является ли это программным обновлением
+                        if let Ok(mut slider_refs_mut) = slider_refs_for_callback.try_borrow_mut() {
+                            if slider_refs_mut.is_programmatic_update(display_clone.i2c_bus) {
+                                value_lbl_for_change.set_text(&format!("{}%", val));
+                                return;
+                            }
+                        } else {
+                            value_lbl_for_change.set_text(&format!("{}%", val));
+                            return;
+                        }
 
-                                    // Оборачиваем в "карточку"
-                                    let frame = gtk::Frame::new(None);
-                                    frame.add_css_class("card");
-                                    frame.set_hexpand(true);
-                                    frame.set_child(Some(&grid));
-
-                                    // Добавляем обработчик клика ТОЛЬКО на иконку монитора
-                                    let click_gesture = GestureClick::new();
-                                    let display_for_click = d.clone();
-                                    let win_for_dialog = win_weak_for_measure_outer.clone();
-                                    click_gesture.connect_pressed(move |_, _, _, _| {
-                                        if let Some(win) = win_for_dialog.upgrade() {
-                                            show_monitor_details_dialog(&win, &display_for_click);
-                                        }
-                                    });
-                                    badge_icon.add_controller(click_gesture);
+                        value_lbl_for_change.set_text(&format!("{}%", val));
 
-                                    list_box_target.append(&frame);
-                                });
+                        // Запоминаем значение для текущего метода
+                        if let Ok(pref_map) = control_pref_map_for_callback.try_borrow() {
+                            if let Some(&pref_m) = pref_map.get(&display_clone.i2c_bus) {
+                                if let Ok(mut refs) = slider_refs_for_callback.try_borrow_mut() {
+                                    refs.remember_value(display_clone.i2c_bus, pref_m, val);
+                                }
                             }
-                            let separator = Separator::new(Orientation::Horizontal);
-                            separator.set_margin_top(8);
-                            separator.set_margin_bottom(8);
-                            list_box_target.append(&separator);
                         }
+                        // persist settings
+                        save_settings_from_state(&control_pref_map_for_callback, &slider_refs_for_callback);
+
+                        // Запись через очередь монитора (при перетаскивании пишется только последнее значение), учитывая предпочтение
+                        let pref = control_pref_map_for_callback.borrow().get(&display_clone.i2c_bus).copied();
+                        queue_brightness_write(&display_clone, val, pref);
+
+                        // Изменение попадает в общую транзакцию, отсчет подтверждения начинается заново
+                        if let Some(setting) = brightness_setting(&display_clone, pref) {
+                            let original = brightness_value(&setting, applied_for_callback.get());
+                            let current = brightness_value(&setting, val);
+                            record_change(setting, || Some(original), Some(current));
+                        }
+                        applied_for_callback.set(val);
+                    });
 
-                        // Устанавливаем обработчики кнопок подтверждения
-                        *confirm_handler_rc_async.borrow_mut() = Some(Box::new({
-                            let all_displays = all_displays.clone();
-                            let confirm_handler = confirm_handler_async.clone();
-                            move || confirm_handler(all_displays.clone())
-                        }));
+                    // Откат транзакции возвращает ползунок к исходной яркости
+                    register_setting_view(&scale, {
+                        let slider_refs = slider_refs_for_async.clone();
+                        let applied = applied.clone();
+                        let d = d.clone();
+                        move |setting, value| {
+                            let is_this = match setting {
+                                Setting::Vcp { bus, code } => *bus == d.i2c_bus && *code == VCP_BRIGHTNESS,
+                                Setting::XrandrBrightness { output } => d.xrandr_output.as_ref() == Some(output),
+                                _ => false,
+                            };
+                            let percent = match value {
+                                Value::Raw(raw) => (*raw).min(100) as u8,
+                                Value::Percent(p) => *p,
+                                _ => return,
+                            };
+                            if is_this {
+                                applied.set(percent);
+                                if let Ok(mut refs) = slider_refs.try_borrow_mut() {
+                                    refs.update_slider_value(d.i2c_bus, percent);
+                                }
+                            }
+                        }
+                    });
 
-                        *cancel_handler_rc_async.borrow_mut() = Some(Box::new({
-                            let all_displays = all_displays.clone();
-                            let cancel_handler = cancel_handler_async.clone();
-                            move || cancel_handler(all_displays.clone())
-                        }));
+                    // Программная установка реальной яркости монитора (без повторной записи)
+                    let show_actual = {
+                        let slider_refs = slider_refs_for_async.clone();
+                        let scale = scale.clone();
+                        let bus = d.i2c_bus;
+                        move |value: u16| {
+                            let value = value.min(100) as u8;
+                            if scale.value() as u8 != value {
+                                if let Ok(mut refs) = slider_refs.try_borrow_mut() {
+                                    refs.update_slider_value(bus, value);
+                                }
+                            }
+                        }
+                    };
+
+                    // Ошибка записи яркости — в подсказке ряда, прочитанное значение — на ползунке
+                    let grid_weak = grid.downgrade();
+                    let show_readback = show_actual.clone();
+                    on_write_result(d.i2c_bus, VCP_BRIGHTNESS, move |result| {
+                        let Some(grid) = grid_weak.upgrade() else { return false };
+                        match &result.result {
+                            Ok(()) => grid.set_tooltip_text(None),
+                            Err(e) => grid.set_tooltip_text(Some(&format!("Ошибка записи яркости: {}", e))),
+                        }
+                        if let Some(actual) = result.readback {
+                            if actual != result.value && ddc_writer().is_settled(result.bus, VCP_BRIGHTNESS, Duration::ZERO) {
+                                show_readback(actual);
+                            }
+                        }
+                        true
+                    });
+                    // Опрос DDC-яркости (только если монитор управляется по DDC)
+                    if d.supports_ddc {
+                        let control_pref_map = control_pref_map_for_async.clone();
+                        let bus = d.i2c_bus;
+                        poll_control(bus, VCP_BRIGHTNESS, &scale, move |value| {
+                            if control_pref_map.borrow().get(&bus) != Some(&ControlMethodPref::Xrandr) {
+                                show_actual(value);
+                            }
+                        });
+                    }
+                }
 
-                        // После полного наполнения списка — один раз подстроим окно под контент
+                // Оборачиваем в "карточку"
+                let frame = gtk::Frame::new(None);
+                frame.add_css_class("card");
+                frame.set_hexpand(true);
+                frame.set_child(Some(&grid));
+
+                // Добавляем обработчик клика ТОЛЬКО на иконку монитора
+                let click_gesture = GestureClick::new();
+                let display_for_click = d.clone();
+                let win_for_dialog = win_weak_for_measure_outer.clone();
+                click_gesture.connect_pressed(move |_, _, _, _| {
+                    if let Some(win) = win_for_dialog.upgrade() {
+                        show_monitor_details_dialog(&win, &display_for_click);
+                    }
+                });
+                badge_icon.add_controller(click_gesture);
+
+                target.append(&frame);
+            }
+        });
+
+        glib::spawn_future_local(async move {
+            // Секции видеокарт по имени (отсортированы), строки мониторов и строки «идет опрос» по шинам
+            let mut sections: std::collections::BTreeMap<String, (GtkBox, GtkBox)> = std::collections::BTreeMap::new();
+            let mut display_rows: HashMap<u8, (String, GtkBox)> = HashMap::new();
+            let mut placeholders: HashMap<u8, GtkBox> = HashMap::new();
+            let mut empty_label: Option<Label> = None;
+            let mut window_measured = false;
+
+            // Убираем строку монитора (и пустую секцию видеокарты)
+            let remove_display_row = |bus: u8,
+                                      display_rows: &mut HashMap<u8, (String, GtkBox)>,
+                                      sections: &mut std::collections::BTreeMap<String, (GtkBox, GtkBox)>| {
+                let Some((card_name, slot)) = display_rows.remove(&bus) else { return };
+                all_displays.borrow_mut().retain(|d| d.i2c_bus != bus);
+                if let Some((section, rows)) = sections.get(&card_name) {
+                    rows.remove(&slot);
+                    if rows.first_child().is_none() {
+                        list_box_target.remove(section);
+                        sections.remove(&card_name);
+                    }
+                }
+            };
+
+            while let Ok(event) = rx.recv().await {
+                if populate_generation.get() != generation {
+                    break;
+                }
+                match event {
+                    DetectEvent::Probing(buses) => {
+                        for (bus, connector) in buses {
+                            let row = create_probe_placeholder(bus, connector.as_deref());
+                            list_box_target.append(&row);
+                            placeholders.insert(bus, row);
+                        }
+                    }
+                    DetectEvent::Found(d) => {
+                        if let Some(row) = placeholders.remove(&d.i2c_bus) {
+                            list_box_target.remove(&row);
+                        }
+                        if let Some(lbl) = empty_label.take() {
+                            list_box_target.remove(&lbl);
+                        }
+                        // Повторное обнаружение на той же шине заменяет строку
+                        remove_display_row(d.i2c_bus, &mut display_rows, &mut sections);
+
+                        let card_name = d.card_name.clone().unwrap_or_else(|| "Unknown GPU".to_string());
+                        if !sections.contains_key(&card_name) {
+                            // Вставляем после предыдущей по имени секции (или после заголовка)
+                            let prev: gtk::Widget = sections
+                                .range(..card_name.clone())
+                                .next_back()
+                                .map(|(_, (section, _))| section.clone().upcast())
+                                .unwrap_or_else(|| header.clone().upcast());
+                            let (section, rows) = create_card_section(&card_name);
+                            list_box_target.insert_child_after(&section, Some(&prev));
+                            sections.insert(card_name.clone(), (section, rows));
+                        }
+                        all_displays.borrow_mut().push(d.clone());
+                        if let Some((_, rows)) = sections.get(&card_name) {
+                            let slot = GtkBox::new(Orientation::Vertical, 0);
+                            rows.append(&slot);
+                            display_rows.insert(d.i2c_bus, (card_name, slot.clone()));
+                            render_display(&slot, d);
+                        }
+                    }
+                    DetectEvent::Finished(bus) => {
+                        if let Some(row) = placeholders.remove(&bus) {
+                            list_box_target.remove(&row);
+                        }
+                        if display_rows.contains_key(&bus) {
+                            println!("Bus {}: monitor disconnected", bus);
+                            remove_display_row(bus, &mut display_rows, &mut sections);
+                        }
+                    }
+                    DetectEvent::TimedOut(bus) => {
+                        if let Some(row) = placeholders.remove(&bus) {
+                            list_box_target.remove(&row);
+                        }
+                    }
+                    DetectEvent::Done => {
+                        let displays = all_displays.borrow().clone();
+                        if displays.is_empty() {
+                            if empty_label.is_none() {
+                                let lbl = Label::new(Some("(Видеокарт с мониторами не найдено)"));
+                                lbl.set_xalign(0.0);
+                                list_box_target.append(&lbl);
+                                empty_label = Some(lbl);
+                            }
+                            continue;
+                        }
+                        // Пишем кэш (best-effort)
+                        let _ = write_displays_cache(&group_displays_by_card(displays));
+
+                        // После первого полного наполнения списка — один раз подстроим окно под контент
+                        if window_measured {
+                            continue;
+                        }
+                        window_measured = true;
                         let win_weak_local = win_weak_for_measure_outer.clone();
                         let content_for_measure_local = content_for_measure_outer.clone();
                         glib::idle_add_local_once(move || {
@@ -3757,16 +4815,6 @@ fn build_ui(app: &Application) {
                             }
                         });
                     }
-                    Ok(_) => {
-                        let lbl = Label::new(Some("(Видеокарт с мониторами не найдено)"));
-                        lbl.set_xalign(0.0);
-                        list_box_target.append(&lbl);
-                    }
-                    Err(err) => {
-                        let err_lbl = Label::new(Some(&format!("Ошибка: {}", err)));
-                        err_lbl.set_xalign(0.0);
-                        list_box_target.append(&err_lbl);
-                    }
                 }
             }
         });
@@ -3776,17 +4824,36 @@ fn build_ui(app: &Application) {
     // Initial population
     populate();
 
-    let confirm_handler_for_button = confirm_handler_rc.clone();
-    confirm_button.connect_clicked(move |_| {
-        if let Some(ref handler) = *confirm_handler_for_button.borrow() {
-            handler();
-        }
-    });
-    
-    let cancel_handler_for_button = cancel_handler_rc.clone();
-    cancel_button.connect_clicked(move |_| {
-        if let Some(ref handler) = *cancel_handler_for_button.borrow() {
-            handler();
+    // Hotplug: повторно опрашиваем затронутые шины; если шина неизвестна — полное обновление
+    let (hotplug_tx, hotplug_rx) = async_channel::unbounded::<Vec<HotplugEvent>>();
+    if let Err(e) = hotplug::spawn_listener(HOTPLUG_QUIET, move |events| { let _ = hotplug_tx.send_blocking(events); }) {
+        println!("Hotplug monitoring unavailable: {e:#}");
+    }
+    let populate_for_hotplug = populate.clone();
+    let force_detect_for_hotplug = force_detect_next.clone();
+    glib::spawn_future_local(async move {
+        while let Ok(events) = hotplug_rx.recv().await {
+            println!("Hotplug: {:?}", events);
+            let mut buses: Vec<u8> = Vec::new();
+            let mut full = false;
+            for event in &events {
+                match hotplug::affected_buses(event) {
+                    Some(list) => buses.extend(list),
+                    None => full = true,
+                }
+            }
+            let tx = detect_tx.borrow().clone();
+            match tx {
+                Some(tx) if !full => {
+                    buses.sort_unstable();
+                    buses.dedup();
+                    thread::spawn(move || detect_i2c_displays_streaming(tx, Some(buses)));
+                }
+                _ => {
+                    force_detect_for_hotplug.set(true);
+                    populate_for_hotplug();
+                }
+            }
         }
     });
 
@@ -0,0 +1,338 @@
+//! Действия, выполняемые по глобальным горячим клавишам.
+
+use anyhow::{anyhow, Result};
+use mondis_core::audio::{self, VCP_AUDIO_MUTE, VCP_AUDIO_VOLUME};
+use mondis_core::cache::cached_displays;
+use mondis_core::history;
+use mondis_core::hotkeys::{HotkeyAction, HotkeyBinding, HotkeyTarget};
+use mondis_core::inputs::{input_label, resolve_input, VCP_INPUT_SOURCE};
+use mondis_core::model::DisplayInfo;
+use mondis_core::power::PowerMode;
+use mondis_core::profile::{list_profiles, load_profile};
+use mondis_core::settings::{read_settings, update_settings, ControlMethod, MonitorSettings, UiSettings};
+use mondis_core::transaction::{Setting, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::{confirm, osd};
+
+// Ночной режим: теплая гамма через XRandR (программно, поверх текущей яркости)
+const NIGHT_GAMMA: (f32, f32, f32) = (1.0, 0.88, 0.76);
+const DAY_GAMMA: (f32, f32, f32) = (1.0, 1.0, 1.0);
+
+static NIGHT_MODE: AtomicBool = AtomicBool::new(false);
+static NEXT_PROFILE: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Method { Ddc, Xrandr }
+
+fn method_for(display: &DisplayInfo, settings: &UiSettings) -> Option<Method> {
+    let pref = settings.monitor(display).and_then(|m| m.control);
+    match pref {
+        Some(ControlMethod::Xrandr) if display.xrandr_output.is_some() => Some(Method::Xrandr),
+        _ if display.supports_ddc => Some(Method::Ddc),
+        _ if display.xrandr_output.is_some() => Some(Method::Xrandr),
+        _ => None,
+    }
+}
+
+fn get_brightness(display: &DisplayInfo, method: Method) -> Result<u8> {
+    match (method, &display.xrandr_output) {
+        (Method::Ddc, _) => mondis_ddc::i2c::get_brightness(display.i2c_bus),
+        (Method::Xrandr, Some(out)) => mondis_x11::xrandr::get_brightness(out),
+        (Method::Xrandr, None) => Err(anyhow!("XRandR not available")),
+    }
+}
+
+fn set_brightness(display: &DisplayInfo, method: Method, value: u8) -> Result<()> {
+    match (method, &display.xrandr_output) {
+        (Method::Ddc, _) => mondis_ddc::i2c::set_brightness(display.i2c_bus, value),
+        (Method::Xrandr, Some(out)) => mondis_x11::xrandr::set_brightness(out, value),
+        (Method::Xrandr, None) => Err(anyhow!("XRandR not available")),
+    }
+}
+
+fn last_brightness(display: &DisplayInfo, method: Method, settings: &UiSettings) -> Option<u8> {
+    let monitor = settings.monitor(display)?;
+    match method {
+        Method::Ddc => monitor.brightness_ddc,
+        Method::Xrandr => monitor.brightness_xrandr,
+    }
+}
+
+// Запоминание установленной яркости; файл пишется один раз, после записи в мониторы
+fn save_brightness(applied: &[(DisplayInfo, Method, u8)]) -> Result<()> {
+    if applied.is_empty() {
+        return Ok(());
+    }
+    update_settings(|settings| {
+        for (display, method, value) in applied {
+            let monitor = settings.monitor_mut(display);
+            match method {
+                Method::Ddc => monitor.brightness_ddc = Some(*value),
+                Method::Xrandr => monitor.brightness_xrandr = Some(*value),
+            }
+        }
+    })
+}
+
+fn target_displays(target: HotkeyTarget) -> Result<Vec<DisplayInfo>> {
+    let displays = cached_displays();
+    if displays.is_empty() {
+        return Err(anyhow!("кэш дисплеев пуст — откройте панель Mondis хотя бы один раз"));
+    }
+    if target == HotkeyTarget::Focused {
+        // Монитор под указателем мыши; если определить не удалось — все мониторы
+        if let Ok(Some(output)) = mondis_x11::output_under_pointer() {
+            let focused: Vec<DisplayInfo> = displays.iter()
+                .filter(|d| d.xrandr_output.as_deref() == Some(output.as_str()))
+                .cloned()
+                .collect();
+            if !focused.is_empty() { return Ok(focused); }
+        }
+    }
+    Ok(displays)
+}
+
+pub fn step_brightness(target: HotkeyTarget, delta: i16) -> Result<()> {
+    let settings = read_settings();
+    let mut applied = Vec::new();
+    for display in target_displays(target)? {
+        let Some(method) = method_for(&display, &settings) else { continue };
+        let last = last_brightness(&display, method, &settings);
+        let current = get_brightness(&display, method).ok().or(last).unwrap_or(50);
+        let value = (current as i16 + delta).clamp(0, 100) as u8;
+        match set_brightness(&display, method, value) {
+            Ok(()) => {
+                eprintln!("mondis-tray: {} -> {}%", display.name, value);
+                osd::show_brightness(&display, value);
+                applied.push((display, method, value));
+            }
+            Err(e) => eprintln!("mondis-tray: failed to set brightness on {}: {e:#}", display.name),
+        }
+    }
+    save_brightness(&applied)
+}
+
+/// Яркость одного монитора (пункты меню трея).
+pub fn set_display_brightness(display: &DisplayInfo, value: u8) -> Result<()> {
+    let method = method_for(display, &read_settings()).ok_or_else(|| anyhow!("нет метода управления для {}", display.name))?;
+    set_brightness(display, method, value)?;
+    osd::show_brightness(display, value);
+    save_brightness(&[(display.clone(), method, value)])
+}
+
+// Вход подтверждается так же, как в панели (политика `input`)
+fn change_input(display: &DisplayInfo, value: u8, settings: &UiSettings) -> Result<()> {
+    let label = input_label(settings, display, value);
+    confirm::change_vcp(display, VCP_INPUT_SOURCE, value as u16, format!("{}: вход — {}", display.name, label))?;
+    eprintln!("mondis-tray: {} -> {}", display.name, label);
+    Ok(())
+}
+
+/// Источник входа одного монитора (пункты меню трея).
+pub fn set_display_input(display: &DisplayInfo, value: u8) -> Result<()> {
+    change_input(display, value, &read_settings())
+}
+
+/// Переключение входа по имени (метка пользователя, имя MCCS или номер); имя разрешается
+/// для каждого монитора отдельно, так как метки у мониторов свои.
+pub fn switch_input(target: HotkeyTarget, input: &str) -> Result<()> {
+    let settings = read_settings();
+    let mut switched = 0;
+    for display in target_displays(target)? {
+        if !display.supports_ddc { continue; }
+        let Some(value) = resolve_input(&settings, &display, input) else {
+            eprintln!("mondis-tray: unknown input '{}' for {}", input, display.name);
+            continue;
+        };
+        match change_input(&display, value, &settings) {
+            Ok(()) => switched += 1,
+            Err(e) => eprintln!("mondis-tray: failed to switch input on {}: {e:#}", display.name),
+        }
+    }
+    if switched == 0 {
+        return Err(anyhow!("вход '{}' не переключен ни на одном мониторе", input));
+    }
+    Ok(())
+}
+
+/// Питание монитора: DDC (VCP 0xD6), выключение и ожидание требуют подтверждения. DPMS действует
+/// сразу на все мониторы, поэтому заменяет DDC, только когда других мониторов нет (без подтверждения,
+/// как и в панели); иначе возвращается ошибка DDC.
+pub fn set_display_power(display: &DisplayInfo, mode: PowerMode) -> Result<()> {
+    let error = if display.supports_ddc {
+        match confirm::change_power(display, mode) {
+            Ok(()) => {
+                eprintln!("mondis-tray: {} power {}", display.name, mode.name());
+                return Ok(());
+            }
+            Err(e) => e,
+        }
+    } else {
+        anyhow!("нет DDC/CI")
+    };
+    if cached_displays().iter().any(|d| d.i2c_bus != display.i2c_bus) {
+        return Err(error.context(format!("питание {} не изменено", display.name)));
+    }
+    eprintln!("mondis-tray: DDC power control failed on {}: {error:#}; trying DPMS", display.name);
+    mondis_x11::dpms::force_level(mode)?;
+    eprintln!("mondis-tray: DPMS {} (all monitors)", mode.name());
+    Ok(())
+}
+
+/// Мониторы с DDC, кроме основного (XRandR primary).
+pub fn secondary_displays() -> Result<Vec<DisplayInfo>> {
+    let primary = mondis_x11::primary_output()?.ok_or_else(|| anyhow!("основной монитор не задан"))?;
+    Ok(cached_displays()
+        .into_iter()
+        .filter(|d| d.supports_ddc && d.xrandr_output.as_deref() != Some(primary.as_str()))
+        .collect())
+}
+
+/// «Выключить все, кроме основного» из меню: выключение требует подтверждения, как в панели.
+pub fn power_off_secondary_confirmed() -> Result<()> {
+    for display in secondary_displays()? {
+        match confirm::change_power(&display, PowerMode::Off) {
+            Ok(()) => eprintln!("mondis-tray: {} power off", display.name),
+            Err(e) => eprintln!("mondis-tray: failed to power off {}: {e:#}", display.name),
+        }
+    }
+    Ok(())
+}
+
+/// Выключает все мониторы, кроме основного (только DDC: DPMS выключил бы и основной), без
+/// подтверждения: для правила простоя. Возвращает выключенные мониторы.
+pub fn power_off_secondary() -> Result<Vec<DisplayInfo>> {
+    let mut switched = Vec::new();
+    for display in secondary_displays()? {
+        match mondis_ddc::i2c::set_power(display.i2c_bus, PowerMode::Off) {
+            Ok(()) => {
+                eprintln!("mondis-tray: {} power off", display.name);
+                switched.push(display);
+            }
+            Err(e) => eprintln!("mondis-tray: failed to power off {}: {e:#}", display.name),
+        }
+    }
+    Ok(switched)
+}
+
+/// Включает мониторы (DDC; снимает неподтвержденное выключение) и снимает DPMS.
+pub fn power_on(displays: &[DisplayInfo]) {
+    for display in displays.iter().filter(|d| d.supports_ddc) {
+        if let Err(e) = confirm::change_power(display, PowerMode::On) {
+            eprintln!("mondis-tray: failed to power on {}: {e:#}", display.name);
+        }
+    }
+    if let Err(e) = mondis_x11::dpms::force_level(PowerMode::On) {
+        eprintln!("mondis-tray: DPMS on failed: {e:#}");
+    }
+}
+
+// Звук идет тем же путем, что и в панели: прежнее значение читается до записи, изменение
+// становится шагом истории, новое значение сохраняется в настройках монитора
+fn change_audio(display: &DisplayInfo, code: u8, value: u16, save: impl FnOnce(&mut MonitorSettings)) -> Result<()> {
+    let bus = display.i2c_bus;
+    let old = mondis_ddc::i2c::get_vcp(bus, code).map(|(cur, _)| cur);
+    mondis_ddc::i2c::set_vcp(bus, code, value)?;
+    match old {
+        Ok(old) if old != value => {
+            if let Err(e) = history::record_change(display, Setting::Vcp { bus, code }, Value::Raw(old), Value::Raw(value)) {
+                eprintln!("mondis-tray: failed to save history: {e:#}");
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("mondis-tray: VCP 0x{:02X} on {} not recorded in history: {e:#}", code, display.name),
+    }
+    update_settings(|settings| save(settings.monitor_mut(display)))
+}
+
+/// Громкость динамиков монитора (VCP 0x62); сохраняется в настройках монитора.
+pub fn set_display_volume(display: &DisplayInfo, value: u8) -> Result<()> {
+    let value = value.min(100);
+    change_audio(display, VCP_AUDIO_VOLUME, value as u16, |monitor| monitor.volume = Some(value))?;
+    eprintln!("mondis-tray: {} volume {}%", display.name, value);
+    Ok(())
+}
+
+/// Отключение звука монитора (VCP 0x8D).
+pub fn set_display_mute(display: &DisplayInfo, muted: bool) -> Result<()> {
+    change_audio(display, VCP_AUDIO_MUTE, audio::mute_value(muted), |monitor| monitor.muted = Some(muted))?;
+    eprintln!("mondis-tray: {} {}", display.name, if muted { "muted" } else { "unmuted" });
+    Ok(())
+}
+
+/// Аудиовход монитора (код из базы особенностей).
+pub fn set_display_audio_input(display: &DisplayInfo, code: u8, value: u8) -> Result<()> {
+    change_audio(display, code, value as u16, |monitor| monitor.audio_input = Some(value))?;
+    eprintln!("mondis-tray: {} audio input 0x{:02X}", display.name, value);
+    Ok(())
+}
+
+// Профили по кругу: сначала яркость, подтвержденная в панели (None), затем profiles/*.xml
+fn cycle_profile() -> Result<()> {
+    let settings = read_settings();
+    let mut profiles: Vec<Option<PathBuf>> = Vec::new();
+    if settings.monitors.values().any(|m| m.profile_brightness.is_some()) {
+        profiles.push(None);
+    }
+    profiles.extend(list_profiles().into_iter().map(Some));
+    if profiles.is_empty() {
+        return Err(anyhow!("нет сохраненных профилей"));
+    }
+    let profile = &profiles[NEXT_PROFILE.fetch_add(1, Ordering::Relaxed) % profiles.len()];
+    let values = match profile {
+        Some(path) => load_profile(path)?,
+        None => HashMap::new(),
+    };
+    let mut applied = Vec::new();
+    for display in cached_displays() {
+        let value = match profile {
+            Some(_) => values.get(&display.i2c_bus).copied(),
+            None => settings.monitor(&display).and_then(|m| m.profile_brightness),
+        };
+        let Some(value) = value else { continue };
+        let Some(method) = method_for(&display, &settings) else { continue };
+        match set_brightness(&display, method, value) {
+            Ok(()) => {
+                osd::show_brightness(&display, value);
+                applied.push((display, method, value));
+            }
+            Err(e) => eprintln!("mondis-tray: failed to apply profile on {}: {e:#}", display.name),
+        }
+    }
+    match profile {
+        Some(path) => eprintln!("mondis-tray: applied profile {}", path.display()),
+        None => eprintln!("mondis-tray: applied saved brightness"),
+    }
+    save_brightness(&applied)
+}
+
+fn toggle_night_mode() -> Result<()> {
+    let night = !NIGHT_MODE.load(Ordering::Relaxed);
+    let gamma = if night { NIGHT_GAMMA } else { DAY_GAMMA };
+    for display in cached_displays() {
+        let Some(ref output) = display.xrandr_output else { continue };
+        let brightness = mondis_x11::xrandr::get_brightness(output).unwrap_or(100);
+        if let Err(e) = mondis_x11::xrandr::set_gamma(output, gamma, brightness) {
+            eprintln!("mondis-tray: failed to set gamma on {}: {e:#}", output);
+        }
+    }
+    NIGHT_MODE.store(night, Ordering::Relaxed);
+    eprintln!("mondis-tray: night mode {}", if night { "on" } else { "off" });
+    Ok(())
+}
+
+pub fn run(binding: &HotkeyBinding, step: u8) -> Result<()> {
+    match binding.action {
+        HotkeyAction::BrightnessUp => step_brightness(binding.target, step as i16),
+        HotkeyAction::BrightnessDown => step_brightness(binding.target, -(step as i16)),
+        HotkeyAction::CycleProfile => cycle_profile(),
+        HotkeyAction::ToggleNightMode => toggle_night_mode(),
+        HotkeyAction::SwitchInput => {
+            let input = binding.input.as_deref().ok_or_else(|| anyhow!("в привязке {} не указан input", binding.keys))?;
+            switch_input(binding.target, input)
+        }
+    }
+}
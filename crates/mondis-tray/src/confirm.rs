@@ -0,0 +1,231 @@
+//! Подтверждение изменений входа и питания из трея: тот же движок транзакций, что и в панели
+//! (`mondis_core::transaction`), с политиками и временем из settings.json. Вместо панели
+//! подтверждения — уведомление с кнопками «Оставить» и «Отменить»; без ответа изменения
+//! откатываются по таймеру.
+
+use anyhow::{Context, Result};
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::message::{MatchRule, Message};
+use mondis_core::history;
+use mondis_core::model::DisplayInfo;
+use mondis_core::power::{PowerMode, VCP_POWER_MODE};
+use mondis_core::settings::read_settings;
+use mondis_core::transaction::{ConfirmPolicy, Resolved, Setting, Transaction, Value};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const NOTIFY_DEST: &str = "org.freedesktop.Notifications";
+const NOTIFY_PATH: &str = "/org/freedesktop/Notifications";
+const TICK: Duration = Duration::from_millis(250);
+
+struct State {
+    transaction: Transaction,
+    // Монитор и описание каждого изменения: для истории и текста уведомления
+    changes: BTreeMap<Setting, (DisplayInfo, String)>,
+    // Растет с каждым изменением: уведомление показывается заново, когда отсчет начался заново
+    generation: u64,
+    watching: bool,
+}
+
+// Одна транзакция на весь трей: меню и горячие клавиши подтверждаются вместе
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+fn lock_state() -> MutexGuard<'static, Option<State>> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Запись VCP с подтверждением: исходное значение читается с монитора до записи, изменение
+/// попадает в транзакцию трея. `what` — строка для уведомления.
+pub fn change_vcp(display: &DisplayInfo, code: u8, value: u16, what: String) -> Result<()> {
+    let bus = display.i2c_bus;
+    let original = mondis_ddc::i2c::get_vcp(bus, code).ok().map(|(cur, _)| Value::Raw(cur));
+    mondis_ddc::i2c::set_vcp(bus, code, value)?;
+    record(display, Setting::Vcp { bus, code }, original, Value::Raw(value), what);
+    Ok(())
+}
+
+/// Питание по DDC. Как в панели, включение само по себе не откатывается: оно только снимает
+/// неподтвержденное выключение.
+pub fn change_power(display: &DisplayInfo, mode: PowerMode) -> Result<()> {
+    let setting = Setting::Vcp { bus: display.i2c_bus, code: VCP_POWER_MODE };
+    let tracked = lock_state().as_ref().is_some_and(|s| s.transaction.contains(&setting));
+    if mode == PowerMode::On && !tracked {
+        return mondis_ddc::i2c::set_power(display.i2c_bus, mode);
+    }
+    change_vcp(display, VCP_POWER_MODE, mode.vcp_value(), format!("{}: питание — {}", display.name, mode.name()))
+}
+
+fn record(display: &DisplayInfo, setting: Setting, original: Option<Value>, current: Value, what: String) {
+    let mut guard = lock_state();
+    let state = guard.get_or_insert_with(|| State {
+        transaction: Transaction::from_settings(&read_settings()),
+        changes: BTreeMap::new(),
+        generation: 0,
+        watching: false,
+    });
+    // Новая транзакция берет время и политики из текущих настроек
+    if !state.transaction.is_pending() {
+        state.transaction = Transaction::from_settings(&read_settings());
+    }
+    if state.transaction.record(setting.clone(), || original, Some(current), Instant::now()) {
+        state.changes.insert(setting, (display.clone(), what));
+    } else {
+        state.changes.remove(&setting);
+    }
+    state.generation += 1;
+    if state.transaction.is_pending() && !state.watching {
+        state.watching = true;
+        thread::spawn(watch);
+    }
+}
+
+// Отсчет транзакции: ответ из уведомления, автоподтверждение и откат по таймеру
+fn watch() {
+    let mut notifier = Notifier::connect()
+        .map_err(|e| eprintln!("mondis-tray: notifications unavailable: {e:#}"))
+        .ok();
+    let mut shown = None;
+    loop {
+        let action = match notifier.as_ref() {
+            Some(n) => n.poll(TICK),
+            None => {
+                thread::sleep(TICK);
+                None
+            }
+        };
+        let mut guard = lock_state();
+        let Some(state) = guard.as_mut() else { return };
+        let transaction = &mut state.transaction;
+        let resolved = match action.as_deref() {
+            Some("keep") => Resolved { committed: transaction.commit(), rolled_back: Vec::new() },
+            Some("revert") => Resolved { committed: Vec::new(), rolled_back: transaction.rollback() },
+            _ => transaction.expire(Instant::now()),
+        };
+        let pending = transaction.is_pending();
+        let remaining = transaction.remaining(Instant::now());
+        let generation = state.generation;
+        let ask = remaining.is_some() && shown != Some(generation);
+        let body = transaction
+            .changes()
+            .filter(|(_, c)| c.policy == ConfirmPolicy::Confirm)
+            .filter_map(|(s, _)| state.changes.get(s).map(|(_, what)| what.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let done = take_resolved(&mut state.changes, &resolved);
+        if !pending {
+            state.watching = false;
+        }
+        drop(guard);
+
+        finish(resolved, done);
+
+        match (remaining, notifier.as_mut()) {
+            (Some(remaining), Some(n)) if ask => match n.show(&body, remaining) {
+                Ok(()) => shown = Some(generation),
+                Err(e) => {
+                    eprintln!("mondis-tray: failed to show confirmation: {e:#}");
+                    notifier = None;
+                }
+            },
+            (None, Some(n)) => n.close(),
+            _ => {}
+        }
+        // Спросить некого: изменение остается, как до появления подтверждения в трее
+        if notifier.is_none() && remaining.is_some() {
+            eprintln!("mondis-tray: no notification service, keeping the changes without confirmation");
+            let mut guard = lock_state();
+            if let Some(state) = guard.as_mut() {
+                let resolved = Resolved { committed: state.transaction.commit(), rolled_back: Vec::new() };
+                let done = take_resolved(&mut state.changes, &resolved);
+                state.watching = false;
+                drop(guard);
+                finish(resolved, done);
+            }
+            return;
+        }
+        if !pending {
+            return;
+        }
+    }
+}
+
+// Мониторы разрешенных изменений; сами изменения больше не отслеживаются
+fn take_resolved(changes: &mut BTreeMap<Setting, (DisplayInfo, String)>, resolved: &Resolved) -> BTreeMap<Setting, DisplayInfo> {
+    let settings = resolved.committed.iter().map(|(s, _)| s).chain(resolved.rolled_back.iter().map(|(s, _)| s));
+    settings.filter_map(|s| changes.remove(s).map(|(display, _)| (s.clone(), display))).collect()
+}
+
+// Откат пишется в мониторы, подтвержденное попадает в историю
+fn finish(resolved: Resolved, displays: BTreeMap<Setting, DisplayInfo>) {
+    for (setting, value) in &resolved.rolled_back {
+        let (Setting::Vcp { bus, code }, Some(raw)) = (setting, value.raw()) else { continue };
+        eprintln!("mondis-tray: not confirmed, restoring VCP 0x{:02X} on i2c-{} to {}", code, bus, raw);
+        if let Err(e) = mondis_ddc::i2c::set_vcp(*bus, *code, raw) {
+            eprintln!("mondis-tray: failed to restore VCP 0x{:02X} on i2c-{}: {e:#}", code, bus);
+        }
+    }
+    for (setting, change) in resolved.committed {
+        let (Some(new), Some(display)) = (change.current, displays.get(&setting)) else { continue };
+        if let Err(e) = history::record_change(display, setting, change.original, new) {
+            eprintln!("mondis-tray: failed to save history: {e:#}");
+        }
+    }
+}
+
+// Уведомление org.freedesktop.Notifications с кнопками; нажатия приходят сигналом ActionInvoked
+struct Notifier {
+    conn: Connection,
+    actions: Receiver<(u32, String)>,
+    id: u32,
+}
+
+impl Notifier {
+    fn connect() -> Result<Self> {
+        let conn = Connection::new_session().context("failed to connect to session bus")?;
+        let (sender, actions) = mpsc::channel();
+        let rule = MatchRule::new_signal(NOTIFY_DEST, "ActionInvoked").with_path(NOTIFY_PATH);
+        conn.add_match(rule, move |(id, action): (u32, String), _: &Connection, _: &Message| {
+            let _ = sender.send((id, action));
+            true
+        })
+        .context("failed to subscribe to notification actions")?;
+        Ok(Self { conn, actions, id: 0 })
+    }
+
+    fn show(&mut self, body: &str, remaining: Duration) -> Result<()> {
+        let proxy = self.conn.with_proxy(NOTIFY_DEST, NOTIFY_PATH, Duration::from_millis(500));
+        let mut hints = PropMap::new();
+        // Критичное уведомление не исчезает само, пока идет отсчет
+        hints.insert("urgency".into(), Variant(Box::new(2u8) as Box<dyn RefArg>));
+        let summary = format!("Оставить изменения? Отмена через {} с", remaining.as_secs().max(1));
+        let actions = vec!["keep", "Оставить", "revert", "Отменить"];
+        let timeout_ms = remaining.as_millis().min(i32::MAX as u128) as i32;
+        let (id,): (u32,) = proxy
+            .method_call(NOTIFY_DEST, "Notify", ("Mondis", self.id, "video-display", summary, body, actions, hints, timeout_ms))
+            .context("Notify failed")?;
+        self.id = id;
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        if self.id == 0 {
+            return;
+        }
+        let proxy = self.conn.with_proxy(NOTIFY_DEST, NOTIFY_PATH, Duration::from_millis(500));
+        let _ = proxy.method_call::<(), _, _, _>(NOTIFY_DEST, "CloseNotification", (self.id,));
+        self.id = 0;
+    }
+
+    // Кнопка, нажатая в нашем уведомлении; ждет сообщений не дольше timeout
+    fn poll(&self, timeout: Duration) -> Option<String> {
+        if let Err(e) = self.conn.process(timeout) {
+            eprintln!("mondis-tray: D-Bus error: {e}");
+            thread::sleep(timeout);
+        }
+        self.actions.try_iter().filter(|(id, _)| self.id != 0 && *id == self.id).map(|(_, action)| action).last()
+    }
+}
@@ -0,0 +1,131 @@
+//! Список мониторов трея и его обновление при подключении/отключении (uevent).
+
+use mondis_core::audio::{VCP_AUDIO_MUTE, VCP_AUDIO_VOLUME};
+use mondis_core::cache::{cached_displays, write_displays_cache};
+use mondis_core::capabilities::Capabilities;
+use mondis_core::inputs::VCP_INPUT_SOURCE;
+use mondis_core::model::{DisplayInfo, VideoCard};
+use mondis_ddc::hotplug::{self, HotplugEvent};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+// Пауза после последнего события, прежде чем перечитывать мониторы
+const HOTPLUG_QUIET: Duration = Duration::from_millis(1500);
+
+// Вывод XRandR для нового монитора: по первым 16 байтам EDID (как в mondis_x11::list_monitors)
+fn xrandr_output_for(edid: &[u8]) -> Option<String> {
+    let key: String = edid.iter().take(16).map(|b| format!("{:02X}", b)).collect();
+    mondis_x11::list_monitors()
+        .ok()?
+        .into_iter()
+        .find(|m| m.id.edid_hash.as_deref() == Some(key.as_str()))
+        .map(|m| m.id.name)
+}
+
+/// Новый список мониторов после событий: затронутые шины опрашиваются заново,
+/// пропавшие мониторы удаляются.
+pub fn apply_hotplug(events: &[HotplugEvent], current: &[DisplayInfo]) -> Vec<DisplayInfo> {
+    let mut buses = BTreeSet::new();
+    for event in events {
+        match hotplug::affected_buses(event) {
+            Some(list) => buses.extend(list),
+            // Шина неизвестна — перепроверяем все адаптеры видеокарт
+            None => buses.extend(mondis_ddc::adapters::display_adapters().iter().map(|a| a.bus)),
+        }
+    }
+
+    let mut displays: Vec<DisplayInfo> = current.iter().filter(|d| !buses.contains(&d.i2c_bus)).cloned().collect();
+    for bus in buses {
+        let Some((mut display, edid)) = mondis_ddc::probe::probe_bus(bus) else {
+            if current.iter().any(|d| d.i2c_bus == bus) {
+                eprintln!("mondis-tray: monitor on /dev/i2c-{} removed", bus);
+            }
+            continue;
+        };
+        // Сохраняем то, что знала панель (вывод XRandR, имя), если это тот же монитор
+        match current.iter().find(|d| d.i2c_bus == bus && d.edid_hash == display.edid_hash) {
+            Some(known) => display = known.clone(),
+            None => {
+                display.xrandr_output = xrandr_output_for(&edid);
+                eprintln!("mondis-tray: monitor added: {}", display.name);
+            }
+        }
+        displays.push(display);
+    }
+    displays.sort_by_key(|d| d.i2c_bus);
+    displays
+}
+
+/// Что монитор умеет по DDC (из capabilities и базы особенностей) — для пунктов меню.
+#[derive(Clone, Debug, Default)]
+pub struct DdcFeatures {
+    pub inputs: Vec<u8>,
+    pub volume: bool,
+    pub mute: bool,
+    /// Аудиовходы из базы особенностей: (VCP-код, значение, название)
+    pub audio_inputs: Vec<(u8, u8, String)>,
+}
+
+fn read_features_of(bus: u8) -> anyhow::Result<DdcFeatures> {
+    let caps = Capabilities::parse(&mondis_ddc::i2c::capabilities(bus)?);
+    let quirks = mondis_ddc::timing::bus_quirks(bus);
+    let available = |code: u8| caps.supports(code) && !quirks.is_hidden(code);
+    let mut inputs = caps.values(VCP_INPUT_SOURCE).to_vec();
+    if inputs.is_empty() && available(VCP_INPUT_SOURCE) {
+        inputs.extend(mondis_ddc::i2c::get_input(bus).ok());
+    }
+    let audio_inputs = match quirks.audio_input.as_ref().filter(|a| !quirks.is_hidden(a.code)) {
+        Some(a) if a.inputs.is_empty() => caps.values(a.code).iter().map(|&v| (a.code, v, format!("0x{:02X}", v))).collect(),
+        Some(a) => a.inputs.iter().map(|n| (a.code, n.value, n.name.clone())).collect(),
+        None => Vec::new(),
+    };
+    Ok(DdcFeatures { inputs, volume: available(VCP_AUDIO_VOLUME), mute: available(VCP_AUDIO_MUTE), audio_inputs })
+}
+
+/// Возможности мониторов с DDC (чтение capabilities занимает до секунды на монитор).
+pub fn read_features(displays: &[DisplayInfo]) -> HashMap<u8, DdcFeatures> {
+    let mut features = HashMap::new();
+    for display in displays.iter().filter(|d| d.supports_ddc) {
+        match read_features_of(display.i2c_bus) {
+            Ok(f) => {
+                features.insert(display.i2c_bus, f);
+            }
+            Err(e) => eprintln!("mondis-tray: failed to read capabilities of {}: {e:#}", display.name),
+        }
+    }
+    features
+}
+
+fn group_by_card(displays: &[DisplayInfo]) -> Vec<VideoCard> {
+    let mut cards: Vec<VideoCard> = Vec::new();
+    for display in displays {
+        let name = display.card_name.clone().unwrap_or_else(|| "Unknown GPU".to_string());
+        match cards.iter_mut().find(|c| c.name == name) {
+            Some(card) => card.displays.push(display.clone()),
+            None => cards.push(VideoCard { name, displays: vec![display.clone()] }),
+        }
+    }
+    cards.sort_by(|a, b| a.name.cmp(&b.name));
+    cards
+}
+
+/// Слушатель hotplug: обновляет кэш дисплеев (если панель не запущена — она пишет кэш сама)
+/// и передает новый список в `on_change`.
+pub fn spawn<F>(panel_running: fn() -> bool, on_change: F)
+where
+    F: Fn(Vec<DisplayInfo>) + Send + 'static,
+{
+    let result = hotplug::spawn_listener(HOTPLUG_QUIET, move |events| {
+        let current = cached_displays();
+        let displays = apply_hotplug(&events, &current);
+        if !panel_running() {
+            if let Err(e) = write_displays_cache(&group_by_card(&displays)) {
+                eprintln!("mondis-tray: failed to update displays cache: {e:#}");
+            }
+        }
+        on_change(displays);
+    });
+    if let Err(e) = result {
+        eprintln!("mondis-tray: hotplug monitoring unavailable: {e:#}");
+    }
+}
@@ -0,0 +1,141 @@
+//! Глобальные горячие клавиши: XGrabKey на X11, XDG GlobalShortcuts portal на Wayland.
+
+use anyhow::{anyhow, Context, Result};
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::LocalConnection;
+use dbus::message::MatchRule;
+use mondis_core::hotkeys::{Accelerator, HotkeyBinding};
+use mondis_core::settings::read_settings;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use crate::actions;
+
+const PORTAL_DEST: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SHORTCUTS_IFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+
+// (код ответа портала, session_handle)
+type PortalResponse = Rc<RefCell<Option<(u32, Option<String>)>>>;
+
+fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE").map(|s| s == "wayland").unwrap_or(false)
+}
+
+/// Запускает слушатель горячих клавиш в отдельном потоке. Привязки читаются
+/// из settings.json один раз при старте трея.
+pub fn spawn() {
+    thread::spawn(|| {
+        let settings = read_settings();
+        if settings.hotkeys.is_empty() {
+            return;
+        }
+        let res = if is_wayland() {
+            run_portal(&settings.hotkeys, settings.brightness_step)
+        } else {
+            run_x11(&settings.hotkeys, settings.brightness_step)
+        };
+        if let Err(e) = res {
+            eprintln!("mondis-tray: global hotkeys disabled: {e:#}");
+        }
+    });
+}
+
+fn dispatch(binding: &HotkeyBinding, step: u8) {
+    eprintln!("mondis-tray: hotkey {} ({})", binding.keys, binding.description());
+    if let Err(e) = actions::run(binding, step) {
+        eprintln!("mondis-tray: hotkey action failed: {e:#}");
+    }
+}
+
+fn run_x11(bindings: &[HotkeyBinding], step: u8) -> Result<()> {
+    let mut accels = Vec::new();
+    let mut active = Vec::new();
+    for b in bindings {
+        match Accelerator::parse(&b.keys) {
+            Some(a) => { accels.push(a); active.push(b.clone()); }
+            None => eprintln!("mondis-tray: invalid hotkey '{}'", b.keys),
+        }
+    }
+    let (listener, errors) = mondis_x11::hotkeys::HotkeyListener::grab(&accels)?;
+    for e in errors {
+        eprintln!("mondis-tray: hotkey skipped: {}", e);
+    }
+    listener.run(|index| dispatch(&active[index], step))
+}
+
+fn variant<T: RefArg + 'static>(value: T) -> Variant<Box<dyn RefArg>> {
+    Variant(Box::new(value))
+}
+
+/// Вызывает метод портала, возвращающий Request, и ждет сигнал Response.
+/// Подписка оформляется до вызова по предсказуемому пути запроса (handle_token).
+fn portal_request<A: dbus::arg::AppendAll>(conn: &LocalConnection, method: &str, token: &str, args: A) -> Result<Option<String>> {
+    let sender = conn.unique_name().trim_start_matches(':').replace('.', "_");
+    let request_path = format!("{}/request/{}/{}", PORTAL_PATH, sender, token);
+    let rule = MatchRule::new_signal("org.freedesktop.portal.Request", "Response")
+        .with_path(request_path.clone());
+    let response: PortalResponse = Rc::new(RefCell::new(None));
+    let slot = response.clone();
+    conn.add_match(rule, move |(code, results): (u32, PropMap), _, _| {
+        let session = results.get("session_handle").and_then(|v| v.0.as_str()).map(str::to_string);
+        *slot.borrow_mut() = Some((code, session));
+        true
+    }).context("failed to subscribe to portal response")?;
+
+    let proxy = conn.with_proxy(PORTAL_DEST, PORTAL_PATH, Duration::from_secs(5));
+    let _: (dbus::Path,) = proxy.method_call(SHORTCUTS_IFACE, method, args)
+        .with_context(|| format!("GlobalShortcuts.{} failed", method))?;
+
+    // BindShortcuts может показать диалог подтверждения — ждем ответ без ограничения
+    loop {
+        if let Some((code, session)) = response.borrow_mut().take() {
+            return match code {
+                0 => Ok(session),
+                1 => Err(anyhow!("{} cancelled by user", method)),
+                _ => Err(anyhow!("{} failed (response {})", method, code)),
+            };
+        }
+        conn.process(Duration::from_millis(500))?;
+    }
+}
+
+fn run_portal(bindings: &[HotkeyBinding], step: u8) -> Result<()> {
+    let conn = LocalConnection::new_session().context("failed to connect to session bus")?;
+
+    let mut options = PropMap::new();
+    options.insert("handle_token".into(), variant("mondis_session".to_string()));
+    options.insert("session_handle_token".into(), variant("mondis".to_string()));
+    let session = portal_request(&conn, "CreateSession", "mondis_session", (options,))?
+        .ok_or_else(|| anyhow!("portal did not return session_handle"))?;
+    let session = dbus::Path::new(session).map_err(|e| anyhow!("invalid session handle: {}", e))?;
+
+    // Идентификатор ярлыка — индекс привязки
+    let shortcuts: Vec<(String, PropMap)> = bindings.iter().enumerate().map(|(i, b)| {
+        let mut props = PropMap::new();
+        props.insert("description".into(), variant(b.description()));
+        if let Some(accel) = Accelerator::parse(&b.keys) {
+            props.insert("preferred_trigger".into(), variant(accel.to_portal_trigger()));
+        }
+        (format!("mondis-{}", i), props)
+    }).collect();
+    let mut options = PropMap::new();
+    options.insert("handle_token".into(), variant("mondis_bind".to_string()));
+    portal_request(&conn, "BindShortcuts", "mondis_bind", (session.clone(), shortcuts, String::new(), options))?;
+
+    let bindings = bindings.to_vec();
+    let rule = MatchRule::new_signal(SHORTCUTS_IFACE, "Activated");
+    conn.add_match(rule, move |(_session, id): (dbus::Path, String), _, _| {
+        if let Some(b) = id.strip_prefix("mondis-").and_then(|i| i.parse::<usize>().ok()).and_then(|i| bindings.get(i)) {
+            dispatch(b, step);
+        }
+        true
+    }).context("failed to subscribe to Activated")?;
+
+    loop {
+        conn.process(Duration::from_secs(60))?;
+    }
+}
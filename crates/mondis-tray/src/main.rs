@@ -3,17 +3,198 @@ use std::process::Command;
 use std::{env, path::PathBuf};
 use which::which;
 
+mod actions;
+mod confirm;
+mod displays;
+mod hotkeys;
+mod osd;
+mod power;
+
 #[cfg(not(feature = "xfce-gtk-tray"))]
 use ksni::{self, menu::MenuItem};
 #[cfg(not(feature = "xfce-gtk-tray"))]
-use ksni::menu::StandardItem;
+use ksni::menu::{StandardItem, SubMenu};
 #[cfg(not(feature = "xfce-gtk-tray"))]
 use tracing_subscriber::EnvFilter;
+#[cfg(not(feature = "xfce-gtk-tray"))]
+use mondis_core::{cache::{cached_displays, connector_edid_hashes, validate_displays}, hotkeys::HotkeyTarget, inputs, model::DisplayInfo, pnp, power::PowerMode, settings::{read_settings, UiSettings}};
+#[cfg(not(feature = "xfce-gtk-tray"))]
+use std::collections::{BTreeSet, HashMap};
 #[cfg(feature = "xfce-gtk-tray")]
 use gtk::prelude::*;
 
 #[cfg(not(feature = "xfce-gtk-tray"))]
-struct MondisTray;
+struct MondisTray {
+    // Подключенные мониторы; обновляются при hotplug
+    displays: Vec<DisplayInfo>,
+    // Возможности DDC (входы, звук) из capabilities: шина -> возможности; читаются в фоне
+    features: HashMap<u8, displays::DdcFeatures>,
+}
+
+// Уровни яркости в подменю монитора
+#[cfg(not(feature = "xfce-gtk-tray"))]
+const BRIGHTNESS_PRESETS: [u8; 5] = [10, 25, 50, 75, 100];
+// Уровни громкости динамиков монитора
+#[cfg(not(feature = "xfce-gtk-tray"))]
+const VOLUME_PRESETS: [u8; 5] = [0, 25, 50, 75, 100];
+
+// Пункт меню, выполняющий действие с монитором в отдельном потоке
+#[cfg(not(feature = "xfce-gtk-tray"))]
+fn display_action<F>(label: String, display: &DisplayInfo, action: F) -> MenuItem<MondisTray>
+where
+    F: Fn(&DisplayInfo) -> Result<()> + Clone + Send + 'static,
+{
+    let display = display.clone();
+    MenuItem::Standard(StandardItem {
+        label,
+        activate: Box::new(move |_this: &mut MondisTray| {
+            let display = display.clone();
+            let action = action.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = action(&display) {
+                    eprintln!("mondis-tray: {}: {e:#}", display.name);
+                }
+            });
+        }),
+        ..Default::default()
+    })
+}
+
+// Подменю «Звук»: громкость, отключение звука, аудиовход
+#[cfg(not(feature = "xfce-gtk-tray"))]
+fn audio_menu(display: &DisplayInfo, features: &displays::DdcFeatures, settings: &UiSettings) -> Option<MenuItem<MondisTray>> {
+    let mut submenu: Vec<MenuItem<MondisTray>> = Vec::new();
+    if features.volume {
+        let last = settings.monitor(display).and_then(|m| m.volume);
+        submenu.extend(VOLUME_PRESETS.iter().map(|&value| {
+            let mark = if last == Some(value) { " •" } else { "" };
+            display_action(format!("Громкость {}%{}", value, mark), display, move |d| actions::set_display_volume(d, value))
+        }));
+    }
+    if features.mute {
+        let muted = settings.monitor(display).and_then(|m| m.muted).unwrap_or(false);
+        let label = if muted { "Включить звук" } else { "Выключить звук" };
+        submenu.push(display_action(label.into(), display, move |d| actions::set_display_mute(d, !muted)));
+    }
+    if !features.audio_inputs.is_empty() {
+        let current = settings.monitor(display).and_then(|m| m.audio_input);
+        submenu.push(MenuItem::Separator);
+        submenu.extend(features.audio_inputs.iter().map(|(code, value, name)| {
+            let (code, value) = (*code, *value);
+            let mark = if current == Some(value) { " •" } else { "" };
+            display_action(format!("Аудиовход: {}{}", name, mark), display, move |d| actions::set_display_audio_input(d, code, value))
+        }));
+    }
+    if submenu.is_empty() {
+        return None;
+    }
+    Some(SubMenu { label: "Звук".into(), submenu, ..Default::default() }.into())
+}
+
+#[cfg(not(feature = "xfce-gtk-tray"))]
+fn display_menu(display: &DisplayInfo, features: &displays::DdcFeatures, settings: &UiSettings) -> MenuItem<MondisTray> {
+    let inputs = &features.inputs;
+    let label = match (&display.manufacturer, &display.model) {
+        (Some(mfg), Some(model)) => format!("{} {}", pnp::display_name(mfg), model),
+        _ => display.name.clone(),
+    };
+    let mut submenu: Vec<MenuItem<MondisTray>> = BRIGHTNESS_PRESETS
+        .iter()
+        .map(|&value| {
+            let display = display.clone();
+            MenuItem::Standard(StandardItem {
+                label: format!("Яркость {}%", value),
+                activate: Box::new(move |_this: &mut MondisTray| {
+                    let display = display.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = actions::set_display_brightness(&display, value) {
+                            eprintln!("mondis-tray: failed to set brightness on {}: {e:#}", display.name);
+                        }
+                    });
+                }),
+                ..Default::default()
+            })
+        })
+        .collect();
+    submenu.push(MenuItem::Separator);
+    submenu.extend([PowerMode::On, PowerMode::Standby, PowerMode::Off].into_iter().map(|mode| {
+        let display = display.clone();
+        let what = match mode {
+            PowerMode::On => "Включить",
+            PowerMode::Off => "Выключить",
+            _ => "Режим ожидания",
+        };
+        MenuItem::Standard(StandardItem {
+            label: what.into(),
+            activate: Box::new(move |_this: &mut MondisTray| {
+                let display = display.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = actions::set_display_power(&display, mode) {
+                        eprintln!("mondis-tray: failed to set power on {}: {e:#}", display.name);
+                    }
+                });
+            }),
+            ..Default::default()
+        })
+    }));
+    if let Some(audio) = audio_menu(display, features, settings) {
+        submenu.push(MenuItem::Separator);
+        submenu.push(audio);
+    }
+    if !inputs.is_empty() {
+        submenu.push(MenuItem::Separator);
+        submenu.extend(inputs.iter().map(|&value| {
+            let display = display.clone();
+            MenuItem::Standard(StandardItem {
+                label: format!("Вход: {}", inputs::input_label(settings, &display, value)),
+                activate: Box::new(move |_this: &mut MondisTray| {
+                    let display = display.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = actions::set_display_input(&display, value) {
+                            eprintln!("mondis-tray: failed to switch input on {}: {e:#}", display.name);
+                        }
+                    });
+                }),
+                ..Default::default()
+            })
+        }));
+    }
+    SubMenu { label, submenu, ..Default::default() }.into()
+}
+
+// "Все мониторы": вход переключается на каждом мониторе, у которого он есть
+#[cfg(not(feature = "xfce-gtk-tray"))]
+fn all_inputs_menu(tray: &MondisTray) -> Option<MenuItem<MondisTray>> {
+    let values: BTreeSet<u8> = tray.features.values().flat_map(|f| f.inputs.iter().copied()).collect();
+    if tray.features.len() < 2 || values.is_empty() {
+        return None;
+    }
+    let submenu = values
+        .into_iter()
+        .map(|value| {
+            let targets: Vec<DisplayInfo> = tray.displays.iter()
+                .filter(|d| tray.features.get(&d.i2c_bus).is_some_and(|f| f.inputs.contains(&value)))
+                .cloned()
+                .collect();
+            let name = inputs::standard_name(value).map(str::to_string).unwrap_or_else(|| format!("0x{:02X}", value));
+            MenuItem::Standard(StandardItem {
+                label: format!("{} ({} мон.)", name, targets.len()),
+                activate: Box::new(move |_this: &mut MondisTray| {
+                    let targets = targets.clone();
+                    std::thread::spawn(move || {
+                        for display in &targets {
+                            if let Err(e) = actions::set_display_input(display, value) {
+                                eprintln!("mondis-tray: failed to switch input on {}: {e:#}", display.name);
+                            }
+                        }
+                    });
+                }),
+                ..Default::default()
+            })
+        })
+        .collect();
+    Some(SubMenu { label: "Все мониторы: вход".into(), submenu, ..Default::default() }.into())
+}
 
 #[cfg(not(feature = "xfce-gtk-tray"))]
 impl ksni::Tray for MondisTray {
@@ -40,6 +221,37 @@ impl ksni::Tray for MondisTray {
                 ..Default::default()
             }));
         }
+        if !self.displays.is_empty() {
+            items.push(MenuItem::Separator);
+            let settings = read_settings();
+            let no_features = displays::DdcFeatures::default();
+            items.extend(self.displays.iter().map(|d| {
+                display_menu(d, self.features.get(&d.i2c_bus).unwrap_or(&no_features), &settings)
+            }));
+            items.extend(all_inputs_menu(self));
+            if self.displays.len() > 1 {
+                items.push(MenuItem::Standard(StandardItem {
+                    label: "Выключить все, кроме основного".into(),
+                    activate: Box::new(|_this: &mut MondisTray| {
+                        std::thread::spawn(|| {
+                            if let Err(e) = actions::power_off_secondary_confirmed() {
+                                eprintln!("mondis-tray: failed to power off secondary monitors: {e:#}");
+                            }
+                        });
+                    }),
+                    ..Default::default()
+                }));
+                let displays = self.displays.clone();
+                items.push(MenuItem::Standard(StandardItem {
+                    label: "Включить все мониторы".into(),
+                    activate: Box::new(move |_this: &mut MondisTray| {
+                        let displays = displays.clone();
+                        std::thread::spawn(move || actions::power_on(&displays));
+                    }),
+                    ..Default::default()
+                }));
+            }
+        }
         items.push(MenuItem::Separator);
         items.push(MenuItem::Standard(StandardItem {
             label: "Выход".into(),
@@ -70,6 +282,17 @@ impl ksni::Tray for MondisTray {
         }
     }
 
+    // Колесо мыши над значком: яркость всех мониторов на шаг из настроек
+    fn scroll(&mut self, delta: i32, dir: &str) {
+        if dir != "vertical" || delta == 0 { return; }
+        let step = read_settings().brightness_step as i16;
+        let delta = if delta > 0 { step } else { -step };
+        std::thread::spawn(move || {
+            if let Err(e) = actions::step_brightness(HotkeyTarget::All, delta) {
+                eprintln!("mondis-tray: scroll brightness failed: {e:#}");
+            }
+        });
+    }
 }
 
 fn open_mondis(pos: Option<(i32, i32)>) -> Result<()> {
@@ -135,8 +358,31 @@ fn close_panel() -> Result<()> {
 #[cfg(not(feature = "xfce-gtk-tray"))]
 fn main() -> Result<()> {
     tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env()).init();
-    let service = ksni::TrayService::new(MondisTray);
-    let _handle = service.spawn();
+    // Без мониторов, которые отключены или заменены с момента записи кэша
+    let startup_displays = validate_displays(cached_displays(), &connector_edid_hashes()).valid;
+    let service = ksni::TrayService::new(MondisTray {
+        displays: startup_displays.clone(),
+        features: HashMap::new(),
+    });
+    let handle = service.handle();
+    service.spawn();
+    hotkeys::spawn();
+    power::spawn_idle_rule();
+    {
+        let handle = handle.clone();
+        std::thread::spawn(move || {
+            let features = displays::read_features(&startup_displays);
+            handle.update(|tray| tray.features = features);
+        });
+    }
+    // Подключение/отключение мониторов: перестраиваем меню
+    displays::spawn(panel_running, move |displays| {
+        let features = displays::read_features(&displays);
+        handle.update(|tray| {
+            tray.displays = displays;
+            tray.features = features;
+        });
+    });
     // Block forever
     loop { std::thread::park(); }
 }
@@ -154,6 +400,7 @@ fn main() -> Result<()> {
         eprintln!("mondis-tray(gtk): activate()");
         if let Err(e) = open_mondis(None) { eprintln!("failed to open mondis: {e:#}"); }
     });
+    hotkeys::spawn();
     // Keep running main loop
     gtk::main();
     Ok(())
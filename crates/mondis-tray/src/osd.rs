@@ -0,0 +1,64 @@
+//! Вызов экранного индикатора (mondis-osd): D-Bus, при недоступности — запуск бинарника.
+
+use anyhow::{Context, Result};
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::Connection;
+use mondis_core::model::DisplayInfo;
+use mondis_core::settings::read_settings;
+use std::env;
+use std::process::Command;
+use std::time::Duration;
+use which::which;
+
+const OSD_DEST: &str = "com.mondis.Osd";
+const OSD_PATH: &str = "/com/mondis/Osd";
+
+/// Имя выхода для OSD: XRandR-выход, иначе коннектор без префикса карты ("card0-DP-1" -> "DP-1").
+pub fn output_name(display: &DisplayInfo) -> String {
+    if let Some(ref out) = display.xrandr_output {
+        return out.clone();
+    }
+    display.connector.as_deref()
+        .map(|c| c.split_once('-').map(|(_, rest)| rest).unwrap_or(c).to_string())
+        .unwrap_or_default()
+}
+
+pub fn show_brightness(display: &DisplayInfo, value: u8) {
+    if !read_settings().osd_enabled {
+        return;
+    }
+    let output = output_name(display);
+    if let Err(e) = show_dbus(&output, &display.name, value) {
+        eprintln!("mondis-tray: OSD over D-Bus unavailable ({e:#}), starting mondis-osd");
+        if let Err(e) = spawn_osd(&output, &display.name, value) {
+            eprintln!("mondis-tray: failed to start OSD: {e:#}");
+        }
+    }
+}
+
+fn show_dbus(output: &str, label: &str, value: u8) -> Result<()> {
+    let conn = Connection::new_session().context("failed to connect to session bus")?;
+    let proxy = conn.with_proxy(OSD_DEST, OSD_PATH, Duration::from_millis(500));
+    let param: Variant<Box<dyn RefArg>> = Variant(Box::new((output.to_string(), label.to_string(), value as u32)));
+    // GApplication экспортирует свои действия через org.gtk.Actions
+    proxy.method_call::<(), _, _, _>("org.gtk.Actions", "Activate", ("show", vec![param], PropMap::new()))
+        .context("org.gtk.Actions.Activate failed")?;
+    Ok(())
+}
+
+fn spawn_osd(output: &str, label: &str, value: u8) -> Result<()> {
+    let mut path = env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("mondis-osd")))
+        .filter(|p| p.is_file());
+    if path.is_none() {
+        path = which("mondis-osd").ok();
+    }
+    let path = path.context("не найден бинарник mondis-osd ни рядом с трейем, ни в PATH")?;
+    Command::new(&path)
+        .arg("--output").arg(output)
+        .arg("--label").arg(label)
+        .arg("--value").arg(value.to_string())
+        .spawn()
+        .with_context(|| format!("spawn {}", path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Правило простоя: после `idle_power_off_s` секунд без ввода выключаются все мониторы,
+//! кроме основного; при первом вводе они включаются обратно.
+
+use mondis_core::model::DisplayInfo;
+use mondis_core::settings::read_settings;
+use std::thread;
+use std::time::Duration;
+
+use crate::actions;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Запускает наблюдение за простоем, если правило включено в settings.json.
+pub fn spawn_idle_rule() {
+    let timeout_s = read_settings().idle_power_off_s;
+    if timeout_s == 0 {
+        return;
+    }
+    let timeout = Duration::from_secs(timeout_s as u64);
+    thread::spawn(move || {
+        // Мониторы, выключенные правилом; включаем только их
+        let mut powered_off: Option<Vec<DisplayInfo>> = None;
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+            let idle = match mondis_x11::dpms::idle_time() {
+                Ok(idle) => idle,
+                Err(e) => {
+                    eprintln!("mondis-tray: idle power rule disabled: {e:#}");
+                    return;
+                }
+            };
+            match (&powered_off, idle >= timeout) {
+                (None, true) => match actions::power_off_secondary() {
+                    Ok(displays) => powered_off = Some(displays),
+                    Err(e) => {
+                        eprintln!("mondis-tray: idle power rule disabled: {e:#}");
+                        return;
+                    }
+                },
+                (Some(displays), false) => {
+                    eprintln!("mondis-tray: user is back, powering on {} monitor(s)", displays.len());
+                    actions::power_on(displays);
+                    powered_off = None;
+                }
+                _ => {}
+            }
+        }
+    });
+}
@@ -0,0 +1,79 @@
+//! X11 DPMS (power level of all monitors of the screen at once) and user idle time.
+
+use anyhow::{anyhow, Result};
+use mondis_core::power::PowerMode;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::protocol::dpms::{ConnectionExt as DpmsConnectionExt, DPMSMode};
+use x11rb::protocol::screensaver::ConnectionExt as ScreensaverConnectionExt;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as XprotoConnectionExt, PropMode};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as WrapperConnectionExt;
+
+/// Root window property set while DPMS stays enabled only because `force_level` turned the
+/// screen off; kept on the X server so a later call from another process can restore it.
+const ENABLED_BY_US: &[u8] = b"_MONDIS_DPMS_ENABLED";
+
+fn to_dpms(mode: PowerMode) -> DPMSMode {
+    match mode {
+        PowerMode::On => DPMSMode::ON,
+        PowerMode::Standby => DPMSMode::STANDBY,
+        PowerMode::Suspend => DPMSMode::SUSPEND,
+        PowerMode::Off => DPMSMode::OFF,
+    }
+}
+
+/// Force the DPMS level. ForceLevel fails while DPMS is disabled, so it is enabled first;
+/// forcing On turns it back off if it was disabled before (here or by an earlier Off,
+/// possibly from another process).
+pub fn force_level(mode: PowerMode) -> Result<()> {
+    let (conn, screen_num) = RustConnection::connect(None)?;
+    if !conn.dpms_capable()?.reply()?.capable {
+        return Err(anyhow!("X server does not support DPMS"));
+    }
+    let root = conn.setup().roots[screen_num].root;
+    let marker = conn.intern_atom(false, ENABLED_BY_US)?.reply()?.atom;
+    let was_disabled = !conn.dpms_info()?.reply()?.state;
+    if was_disabled {
+        conn.dpms_enable()?.check()?;
+    }
+    conn.dpms_force_level(to_dpms(mode))?.check()?;
+    if mode == PowerMode::On {
+        let enabled_by_us = conn
+            .get_property(true, root, marker, AtomEnum::CARDINAL, 0, 1)?
+            .reply()?
+            .value_len
+            > 0;
+        if was_disabled || enabled_by_us {
+            conn.dpms_disable()?.check()?;
+        }
+    } else if was_disabled {
+        conn.change_property32(PropMode::REPLACE, root, marker, AtomEnum::CARDINAL, &[1])?
+            .check()?;
+    }
+    conn.flush()?;
+    Ok(())
+}
+
+/// Current DPMS level; None when DPMS is disabled.
+pub fn current_level() -> Result<Option<PowerMode>> {
+    let (conn, _) = RustConnection::connect(None)?;
+    let info = conn.dpms_info()?.reply()?;
+    if !info.state {
+        return Ok(None);
+    }
+    Ok(Some(match info.power_level {
+        DPMSMode::STANDBY => PowerMode::Standby,
+        DPMSMode::SUSPEND => PowerMode::Suspend,
+        DPMSMode::OFF => PowerMode::Off,
+        _ => PowerMode::On,
+    }))
+}
+
+/// Time since the last keyboard or mouse input (MIT-SCREEN-SAVER).
+pub fn idle_time() -> Result<Duration> {
+    let (conn, screen_num) = RustConnection::connect(None)?;
+    let root = conn.setup().roots[screen_num].root;
+    let info = conn.screensaver_query_info(root)?.reply()?;
+    Ok(Duration::from_millis(info.ms_since_user_input as u64))
+}
@@ -0,0 +1,130 @@
+//! Global hotkeys on X11 via XGrabKey on the root window.
+
+use anyhow::{anyhow, Result};
+use mondis_core::hotkeys::Accelerator;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt as XprotoConnectionExt, GrabMode, ModMask, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+const LOCK_VARIANTS: [u16; 4] = [0, 0x02 /* Lock */, 0x10 /* Mod2 = NumLock */, 0x12];
+const MOD_RELEVANT: u16 = 0x01 | 0x04 | 0x08 | 0x40; // Shift | Control | Mod1 | Mod4
+
+/// X keysym value by name (subset sufficient for hotkeys).
+pub fn keysym_from_name(name: &str) -> Option<u32> {
+    let sym = match name {
+        "Page_Up" | "Prior" => 0xff55,
+        "Page_Down" | "Next" => 0xff56,
+        "Home" => 0xff50,
+        "End" => 0xff57,
+        "Left" => 0xff51,
+        "Up" => 0xff52,
+        "Right" => 0xff53,
+        "Down" => 0xff54,
+        "Insert" => 0xff63,
+        "Delete" => 0xffff,
+        "Print" => 0xff61,
+        "Pause" => 0xff13,
+        "space" => 0x20,
+        "plus" => 0x2b,
+        "minus" => 0x2d,
+        "equal" => 0x3d,
+        "bracketleft" => 0x5b,
+        "bracketright" => 0x5d,
+        "KP_Add" => 0xffab,
+        "KP_Subtract" => 0xffad,
+        "XF86MonBrightnessUp" => 0x1008ff02,
+        "XF86MonBrightnessDown" => 0x1008ff03,
+        _ => {
+            if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse::<u32>().ok()) {
+                if (1..=24).contains(&n) { return Some(0xffbe + n - 1); }
+                return None;
+            }
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphanumeric() => c.to_ascii_lowercase() as u32,
+                _ => return None,
+            }
+        }
+    };
+    Some(sym)
+}
+
+fn modifier_mask(accel: &Accelerator) -> u16 {
+    let m = &accel.modifiers;
+    let mut mask = 0u16;
+    if m.shift { mask |= 0x01; }
+    if m.ctrl { mask |= 0x04; }
+    if m.alt { mask |= 0x08; }
+    if m.super_key { mask |= 0x40; }
+    mask
+}
+
+struct Grab {
+    keycode: u8,
+    mask: u16,
+    index: usize,
+}
+
+pub struct HotkeyListener {
+    conn: RustConnection,
+    grabs: Vec<Grab>,
+}
+
+impl HotkeyListener {
+    /// Grab all accelerators on the root window. Returns the listener and per-binding errors
+    /// (unknown key, key already grabbed by another client, ...) — those bindings are skipped.
+    pub fn grab(accels: &[Accelerator]) -> Result<(HotkeyListener, Vec<String>)> {
+        let (conn, screen_num) = RustConnection::connect(None)?;
+        let root: Window = conn.setup().roots[screen_num].root;
+        let min = conn.setup().min_keycode;
+        let max = conn.setup().max_keycode;
+        let mapping = conn.get_keyboard_mapping(min, max - min + 1)?.reply()?;
+        let per = mapping.keysyms_per_keycode as usize;
+
+        let mut grabs = Vec::new();
+        let mut errors = Vec::new();
+        for (index, accel) in accels.iter().enumerate() {
+            let Some(sym) = keysym_from_name(&accel.key) else {
+                errors.push(format!("неизвестная клавиша '{}'", accel.key));
+                continue;
+            };
+            let keycode = mapping.keysyms.chunks(per.max(1)).position(|syms| syms.contains(&sym));
+            let Some(pos) = keycode else {
+                errors.push(format!("клавиша '{}' отсутствует в раскладке", accel.key));
+                continue;
+            };
+            let keycode = min + pos as u8;
+            let mask = modifier_mask(accel);
+            let mut failed = None;
+            for extra in LOCK_VARIANTS {
+                let res = conn
+                    .grab_key(true, root, ModMask::from(mask | extra), keycode, GrabMode::ASYNC, GrabMode::ASYNC)?
+                    .check();
+                if let Err(e) = res { failed = Some(e); break; }
+            }
+            if let Some(e) = failed {
+                errors.push(format!("{}: не удалось захватить ({:?})", accel.key, e));
+                continue;
+            }
+            grabs.push(Grab { keycode, mask, index });
+        }
+        conn.flush()?;
+        Ok((HotkeyListener { conn, grabs }, errors))
+    }
+
+    /// Block and call `on_press(binding_index)` for every grabbed key press.
+    pub fn run(self, mut on_press: impl FnMut(usize)) -> Result<()> {
+        if self.grabs.is_empty() {
+            return Err(anyhow!("no hotkeys grabbed"));
+        }
+        loop {
+            if let Event::KeyPress(ev) = self.conn.wait_for_event()? {
+                let state = u16::from(ev.state) & MOD_RELEVANT;
+                if let Some(g) = self.grabs.iter().find(|g| g.keycode == ev.detail && g.mask == state) {
+                    on_press(g.index);
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,57 @@
-use anyhow::{anyhow, Result};
+pub mod dpms;
+pub mod hotkeys;
+pub mod xrandr;
+
+use anyhow::Result;
+use mondis_core::edid::Edid;
 use mondis_core::model::{MonitorId, MonitorInfo};
 use x11rb::connection::Connection;
 use x11rb::protocol::randr::{ConnectionExt as RandrConnectionExt, GetOutputInfoReply};
 use x11rb::protocol::xproto::ConnectionExt as XprotoConnectionExt;
 use x11rb::rust_connection::RustConnection;
 
+/// RandR output name of the monitor under the mouse pointer (e.g. "DP-1").
+pub fn output_under_pointer() -> Result<Option<String>> {
+    let (conn, screen_num) = RustConnection::connect(None)?;
+    let root = conn.setup().roots[screen_num].root;
+    let pointer = conn.query_pointer(root)?.reply()?;
+    let (px, py) = (pointer.root_x as i32, pointer.root_y as i32);
+    let monitors = conn.randr_get_monitors(root, true)?.reply()?;
+    for m in monitors.monitors {
+        let inside = px >= m.x as i32 && px < m.x as i32 + m.width as i32
+            && py >= m.y as i32 && py < m.y as i32 + m.height as i32;
+        if !inside { continue; }
+        if let Some(&output) = m.outputs.first() {
+            let info = conn.randr_get_output_info(output, x11rb::CURRENT_TIME)?.reply()?;
+            return Ok(Some(String::from_utf8_lossy(&info.name).to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// RandR output name of the primary monitor, if one is set.
+pub fn primary_output() -> Result<Option<String>> {
+    let (conn, screen_num) = RustConnection::connect(None)?;
+    let root = conn.setup().roots[screen_num].root;
+    let primary = conn.randr_get_output_primary(root)?.reply()?.output;
+    if primary == x11rb::NONE {
+        return Ok(None);
+    }
+    let info = conn.randr_get_output_info(primary, x11rb::CURRENT_TIME)?.reply()?;
+    Ok(Some(String::from_utf8_lossy(&info.name).to_string()))
+}
+
+/// Turn an unmapped top-level window into an override-redirect popup at (x, y).
+/// Used for the OSD: the window manager then neither decorates nor moves it.
+pub fn make_override_redirect(window: u32, x: i32, y: i32) -> Result<()> {
+    use x11rb::protocol::xproto::{ChangeWindowAttributesAux, ConfigureWindowAux};
+    let (conn, _) = RustConnection::connect(None)?;
+    conn.change_window_attributes(window, &ChangeWindowAttributesAux::new().override_redirect(1))?.check()?;
+    conn.configure_window(window, &ConfigureWindowAux::new().x(x).y(y))?.check()?;
+    conn.flush()?;
+    Ok(())
+}
+
 pub fn list_monitors() -> Result<Vec<MonitorInfo>> {
     let (conn, screen_num) = RustConnection::connect(None)?;
     let screen = &conn.setup().roots[screen_num];
@@ -43,10 +90,10 @@ pub fn list_monitors() -> Result<Vec<MonitorInfo>> {
                 if !bytes.is_empty() {
                     let n = bytes.len().min(16);
                     edid_hash = Some(bytes[..n].iter().map(|b| format!("{:02X}", b)).collect());
-                    if let Ok((mfg, mdl, ser)) = parse_edid(bytes) {
-                        manufacturer = mfg;
-                        model = mdl;
-                        serial = ser;
+                    if let Ok(edid) = Edid::parse(bytes) {
+                        manufacturer = (!edid.manufacturer.contains('?')).then(|| edid.manufacturer_name());
+                        model = edid.name().map(str::to_string);
+                        serial = edid.serial_string().map(str::to_string);
                     }
                 }
             }
@@ -64,48 +111,3 @@ pub fn list_monitors() -> Result<Vec<MonitorInfo>> {
     }
     Ok(list)
 }
-
-fn parse_edid(edid: &[u8]) -> Result<(Option<String>, Option<String>, Option<String>)> {
-    if edid.len() < 128 { return Err(anyhow!("EDID too short")); }
-    // Manufacturer ID: bytes 8-9 (big-endian, 5-bit letters)
-    let mfg_id = u16::from_be_bytes([edid[8], edid[9]]);
-    let c1 = (((mfg_id >> 10) & 0x1F) as u8 + 0x40) as char;
-    let c2 = (((mfg_id >> 5) & 0x1F) as u8 + 0x40) as char;
-    let c3 = ((mfg_id & 0x1F) as u8 + 0x40) as char;
-    let mfg = if c1.is_ascii_uppercase() && c2.is_ascii_uppercase() && c3.is_ascii_uppercase() {
-        Some(format!("{}{}{}", c1, c2, c3))
-    } else { None };
-
-    // Search descriptor blocks for model name (type 0xFC) and serial string (0xFF)
-    let mut model: Option<String> = None;
-    let mut serial: Option<String> = None;
-    // Detailed timing/descriptor blocks from 54 to 126 in 18-byte chunks
-    let mut i = 54usize;
-    while i + 18 <= edid.len() {
-        let block = &edid[i..i + 18];
-        if block[0] == 0 && block[1] == 0 {
-            // Descriptor
-            match block[3] {
-                0xFC => { // Monitor name
-                    let text = parse_descriptor_text(&block[5..18]);
-                    if !text.is_empty() { model = Some(text); }
-                }
-                0xFF => { // Serial string
-                    let text = parse_descriptor_text(&block[5..18]);
-                    if !text.is_empty() { serial = Some(text); }
-                }
-                _ => {}
-            }
-        }
-        i += 18;
-        if i >= 126 { break; }
-    }
-    Ok((mfg, model, serial))
-}
-
-fn parse_descriptor_text(bytes: &[u8]) -> String {
-    let mut s: Vec<u8> = bytes.iter().copied().take_while(|&b| b != 0x0A && b != 0x00).collect();
-    // Trim trailing spaces
-    while let Some(b) = s.last() { if *b == b' ' { s.pop(); } else { break; } }
-    String::from_utf8_lossy(&s).trim().to_string()
-}
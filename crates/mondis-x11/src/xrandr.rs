@@ -0,0 +1,75 @@
+//! Software brightness/gamma through the `xrandr` command (fallback when DDC/CI is unavailable).
+
+use anyhow::{anyhow, Context, Result};
+use std::process::Command;
+
+/// Set software brightness, 0..=100 percent.
+pub fn set_brightness(output: &str, value: u8) -> Result<()> {
+    let factor = (value.min(100) as f32) / 100.0;
+    let status = Command::new("xrandr")
+        .arg("--output").arg(output)
+        .arg("--brightness").arg(format!("{:.2}", factor))
+        .status()
+        .context("failed to run xrandr")?;
+    if status.success() { Ok(()) } else { Err(anyhow!("xrandr exited with status: {:?}", status.code())) }
+}
+
+// Lines of the `xrandr --verbose` block describing `output` (the command lists every output)
+fn verbose_section(output: &str) -> Result<Vec<String>> {
+    let out = Command::new("xrandr")
+        .arg("--verbose")
+        .output()
+        .context("failed to run xrandr --verbose")?;
+    if !out.status.success() {
+        return Err(anyhow!("xrandr --verbose failed: {:?}", out.status.code()));
+    }
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let header = format!("{} ", output);
+    Ok(stdout
+        .lines()
+        .skip_while(|line| !line.starts_with(&header))
+        .enumerate()
+        .take_while(|(i, line)| *i == 0 || line.starts_with(char::is_whitespace))
+        .map(|(_, line)| line.trim().to_string())
+        .collect())
+}
+
+/// Current software brightness, 0..=100 percent (100 if xrandr does not report it).
+pub fn get_brightness(output: &str) -> Result<u8> {
+    for line in verbose_section(output)? {
+        if let Some(rest) = line.strip_prefix("Brightness:") {
+            if let Ok(f) = rest.trim().parse::<f32>() {
+                return Ok((f * 100.0).round().clamp(0.0, 100.0) as u8);
+            }
+        }
+    }
+    Ok(100)
+}
+
+/// Current per-channel gamma as accepted by [`set_gamma`]. `xrandr --verbose` prints the
+/// reciprocal of the value set with `--gamma`, so it is inverted here.
+pub fn get_gamma(output: &str) -> Result<(f32, f32, f32)> {
+    for line in verbose_section(output)? {
+        if let Some(rest) = line.strip_prefix("Gamma:") {
+            let parts: Vec<f32> = rest.trim().split(':').filter_map(|p| p.trim().parse::<f32>().ok()).collect();
+            if let [r, g, b] = parts[..] {
+                let inv = |v: f32| if v > 0.0 { 1.0 / v } else { 1.0 };
+                return Ok((inv(r), inv(g), inv(b)));
+            }
+        }
+    }
+    Ok((1.0, 1.0, 1.0))
+}
+
+/// Set per-channel gamma together with brightness: xrandr recomputes the ramp from both,
+/// so brightness has to be passed again to keep it unchanged.
+pub fn set_gamma(output: &str, gamma: (f32, f32, f32), brightness: u8) -> Result<()> {
+    let factor = (brightness.min(100) as f32) / 100.0;
+    let status = Command::new("xrandr")
+        .arg("--output").arg(output)
+        .arg("--gamma").arg(format!("{:.2}:{:.2}:{:.2}", gamma.0, gamma.1, gamma.2))
+        .arg("--brightness").arg(format!("{:.2}", factor))
+        .status()
+        .context("failed to run xrandr")?;
+    if status.success() { Ok(()) } else { Err(anyhow!("xrandr exited with status: {:?}", status.code())) }
+}
@@ -0,0 +1,60 @@
+#!/usr/bin/env python3
+# Generates crates/mondis-core/src/pnp/table.rs from the hwdata pnp.ids list
+# (UEFI PNP ID registry). Usage:
+#   scripts/gen-pnp-ids.py [path-or-url-to-pnp.ids]
+# Default source: /usr/share/hwdata/pnp.ids, then the upstream hwdata repository.
+# The output is re-exported as mondis_core::pnp::PNP_IDS, so it must stay `pub`.
+import os
+import re
+import sys
+import urllib.request
+
+UPSTREAM = "https://raw.githubusercontent.com/vcrhonek/hwdata/master/pnp.ids"
+# The full registry has a few thousand entries; fewer means a partial source
+FULL_MIN = 2000
+LOCAL = ["/usr/share/hwdata/pnp.ids", "/usr/share/misc/pnp.ids"]
+
+SCRIPT_DIR = os.path.dirname(os.path.abspath(__file__))
+OUT = os.path.join(SCRIPT_DIR, "..", "crates", "mondis-core", "src", "pnp", "table.rs")
+
+
+def load(source):
+    if re.match(r"^https?://", source):
+        with urllib.request.urlopen(source) as r:
+            return r.read().decode("utf-8", "replace"), source
+    with open(source, encoding="utf-8", errors="replace") as f:
+        return f.read(), source
+
+
+def main():
+    if len(sys.argv) > 1:
+        text, source = load(sys.argv[1])
+    else:
+        local = next((p for p in LOCAL if os.path.exists(p)), None)
+        text, source = load(local or UPSTREAM)
+
+    ids = {}
+    for line in text.splitlines():
+        if not line or line.startswith("#"):
+            continue
+        code, _, name = line.partition("\t")
+        code, name = code.strip(), name.strip()
+        if re.fullmatch(r"[A-Z@]{3}", code) and name:
+            ids[code] = name
+
+    with open(OUT, "w", encoding="utf-8") as out:
+        name = os.path.basename(source.rstrip("/"))
+        out.write(f"// Generated by scripts/gen-pnp-ids.py from {name} ({len(ids)} IDs) — do not edit by hand.\n")
+        out.write("// Sorted by code for binary search.\n\n")
+        out.write("pub static PNP_IDS: &[(&str, &str)] = &[\n")
+        for code in sorted(ids):
+            name = ids[code].replace("\\", "\\\\").replace('"', '\\"')
+            out.write(f'    ("{code}", "{name}"),\n')
+        out.write("];\n")
+    print(f"{len(ids)} PNP IDs from {source} -> {os.path.normpath(OUT)}")
+    if len(ids) < FULL_MIN:
+        print(f"warning: {source} is not the full hwdata pnp.ids", file=sys.stderr)
+
+
+if __name__ == "__main__":
+    main()
@@ -147,8 +147,8 @@ fi
 
 # 3) Build required crates in release
 cd "$REPO_ROOT"
-echo -e "${Y}Building mondis-tray and mondis-panel-direct (release)...${Z}"
-"$HOME/.cargo/bin/cargo" build --release -p mondis-tray -p mondis-panel-direct
+echo -e "${Y}Building mondis-tray, mondis-panel-direct, mondis-osd and mondis CLI (release)...${Z}"
+"$HOME/.cargo/bin/cargo" build --release -p mondis-tray -p mondis-panel-direct -p mondis-osd -p mondis-cli
 
 # 4) Install binaries to ~/.local/bin
 INSTALL_BIN="$HOME/.local/bin"
@@ -158,6 +158,14 @@ install -m 0755 "$REPO_ROOT/target/release/mondis-tray" "$INSTALL_BIN/" || true
 if [ -f "$REPO_ROOT/target/release/mondis-panel-direct" ]; then
   install -m 0755 "$REPO_ROOT/target/release/mondis-panel-direct" "$INSTALL_BIN/" || true
 fi
+# on-screen display, started on demand by the tray
+if [ -f "$REPO_ROOT/target/release/mondis-osd" ]; then
+  install -m 0755 "$REPO_ROOT/target/release/mondis-osd" "$INSTALL_BIN/" || true
+fi
+# command line tool
+if [ -f "$REPO_ROOT/target/release/mondis" ]; then
+  install -m 0755 "$REPO_ROOT/target/release/mondis" "$INSTALL_BIN/" || true
+fi
 
 # 5) Create autostart .desktop for tray
 AUTOSTART_DIR="$HOME/.config/autostart"
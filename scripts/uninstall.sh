@@ -18,7 +18,7 @@ else
 fi
 
 # Remove binaries (ask user)
-for bin in mondis-tray mondis-panel-direct; do
+for bin in mondis-tray mondis-panel-direct mondis-osd mondis; do
   if [ -f "$INSTALL_BIN/$bin" ]; then
     read -r -p "Remove $INSTALL_BIN/$bin? [y/N] " ans || true
     if [[ "${ans:-}" =~ ^[Yy]$ ]]; then